@@ -0,0 +1,623 @@
+use crate::analysis::lockfiles::ResolvedDependency;
+use ignore::WalkBuilder;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Unknown,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// A resolved dependency matched by an advisory
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VulnerabilityFinding {
+    pub advisory_id: String,
+    pub aliases: Vec<String>,
+    pub ecosystem: String,
+    pub package: String,
+    pub version: String,
+    pub direct: bool,
+    pub severity: Severity,
+    pub summary: String,
+    /// Versions (or requirements, for RustSec) that fix the issue
+    pub fixed: Vec<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VulnerabilityAudit {
+    /// Advisory snapshot directories that were read
+    pub sources: Vec<String>,
+    pub advisories_loaded: usize,
+    /// Most severe first
+    pub findings: Vec<VulnerabilityFinding>,
+}
+
+enum AffectedVersions {
+    /// OSV `ranges` (each a list of events) plus explicitly listed versions
+    Osv {
+        ranges: Vec<Vec<(EventKind, String)>>,
+        versions: Vec<String>,
+    },
+    /// RustSec: anything not patched and not unaffected
+    Requirements {
+        patched: Vec<VersionReq>,
+        unaffected: Vec<VersionReq>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EventKind {
+    Introduced,
+    Fixed,
+    LastAffected,
+}
+
+struct Advisory {
+    id: String,
+    aliases: Vec<String>,
+    ecosystem: String,
+    package: String,
+    severity: Severity,
+    summary: String,
+    fixed: Vec<String>,
+    url: Option<String>,
+    affected: AffectedVersions,
+}
+
+/// Map an OSV ecosystem name onto the inventory's
+fn inventory_ecosystem(osv: &str) -> Option<&'static str> {
+    match osv {
+        "npm" => Some("npm"),
+        "crates.io" => Some("cargo"),
+        _ => None,
+    }
+}
+
+fn parse_severity_label(label: &str) -> Severity {
+    match label.to_ascii_lowercase().as_str() {
+        "critical" => Severity::Critical,
+        "high" => Severity::High,
+        "moderate" | "medium" => Severity::Medium,
+        "low" => Severity::Low,
+        _ => Severity::Unknown,
+    }
+}
+
+/// CVSS v3.1 Roundup: the smallest one-decimal number >= `value`, computed on
+/// integers so floating point error can't turn 4.0 into 4.1
+fn roundup_v31(value: f64) -> f64 {
+    let int_input = (value * 100_000.0).round() as i64;
+    if int_input % 10_000 == 0 {
+        int_input as f64 / 100_000.0
+    } else {
+        (int_input / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+fn roundup_v30(value: f64) -> f64 {
+    (value * 10.0).ceil() / 10.0
+}
+
+/// Base score of a CVSS v3.0 or v3.1 vector. Both versions share the base
+/// equations (3.1's `ISS × 0.9731` term is only in the environmental score);
+/// they differ in how the result is rounded.
+fn cvss3_score(vector: &str) -> Option<f64> {
+    let (rest, roundup): (&str, fn(f64) -> f64) = if let Some(rest) = vector.strip_prefix("CVSS:3.1/") {
+        (rest, roundup_v31)
+    } else {
+        (vector.strip_prefix("CVSS:3.0/")?, roundup_v30)
+    };
+    let metrics: HashMap<&str, &str> = rest.split('/').filter_map(|m| m.split_once(':')).collect();
+    let changed = *metrics.get("S")? == "C";
+
+    let av = match *metrics.get("AV")? { "N" => 0.85, "A" => 0.62, "L" => 0.55, _ => 0.2 };
+    let ac = match *metrics.get("AC")? { "L" => 0.77, _ => 0.44 };
+    let pr = match (*metrics.get("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        (_, false) => 0.27,
+        (_, true) => 0.5,
+    };
+    let ui = match *metrics.get("UI")? { "N" => 0.85, _ => 0.62 };
+    let cia = |key: &str| match metrics.get(key).copied() {
+        Some("H") => Some(0.56),
+        Some("L") => Some(0.22),
+        Some(_) => Some(0.0),
+        None => None,
+    };
+    let iss: f64 = 1.0 - (1.0 - cia("C")?) * (1.0 - cia("I")?) * (1.0 - cia("A")?);
+
+    let impact = if changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15)
+    } else {
+        6.42 * iss
+    };
+    let exploitability = 8.22 * av * ac * pr * ui;
+    Some(if impact <= 0.0 {
+        0.0
+    } else if changed {
+        roundup((1.08 * (impact + exploitability)).min(10.0))
+    } else {
+        roundup((impact + exploitability).min(10.0))
+    })
+}
+
+/// Base score of a CVSS v3.x vector, bucketed into a severity
+fn cvss3_severity(vector: &str) -> Option<Severity> {
+    Some(match cvss3_score(vector)? {
+        s if s >= 9.0 => Severity::Critical,
+        s if s >= 7.0 => Severity::High,
+        s if s >= 4.0 => Severity::Medium,
+        s if s > 0.0 => Severity::Low,
+        _ => Severity::Unknown,
+    })
+}
+
+/// npm versions are semver; tolerate a leading `v`
+fn parse_version(version: &str) -> Option<Version> {
+    Version::parse(version.trim().trim_start_matches('v')).ok()
+}
+
+impl AffectedVersions {
+    fn matches(&self, version: &str) -> bool {
+        match self {
+            AffectedVersions::Osv { ranges, versions } => {
+                if versions.iter().any(|v| v == version) {
+                    return true;
+                }
+                let Some(current) = parse_version(version) else {
+                    return false;
+                };
+                ranges.iter().any(|events| {
+                    // Walk the events in version order; `introduced: "0"` sorts first
+                    let mut sorted: Vec<(EventKind, Version)> = events
+                        .iter()
+                        .filter_map(|(kind, v)| {
+                            let boundary = if v == "0" { Version::new(0, 0, 0) } else { parse_version(v)? };
+                            Some((*kind, boundary))
+                        })
+                        .collect();
+                    sorted.sort_by(|a, b| a.1.cmp(&b.1));
+
+                    let mut affected = false;
+                    for (kind, boundary) in sorted {
+                        let reached = match kind {
+                            EventKind::LastAffected => current > boundary,
+                            _ => current >= boundary,
+                        };
+                        if reached {
+                            affected = kind == EventKind::Introduced;
+                        }
+                    }
+                    affected
+                })
+            }
+            AffectedVersions::Requirements { patched, unaffected } => {
+                let Some(current) = parse_version(version) else {
+                    return false;
+                };
+                !patched.iter().chain(unaffected).any(|req| req.matches(&current))
+            }
+        }
+    }
+}
+
+/// One OSV record (https://ossf.github.io/osv-schema/); a record may cover several packages
+fn parse_osv(content: &str) -> Vec<Advisory> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    if json["withdrawn"].is_string() {
+        return Vec::new();
+    }
+
+    let id = json["id"].as_str().unwrap_or_default().to_string();
+    let aliases: Vec<String> = json["aliases"]
+        .as_array()
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+    let summary = json["summary"]
+        .as_str()
+        .or_else(|| json["details"].as_str())
+        .unwrap_or_default()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let url = json["references"]
+        .as_array()
+        .and_then(|refs| refs.iter().find(|r| r["type"] == "ADVISORY").or_else(|| refs.first()))
+        .and_then(|r| r["url"].as_str())
+        .map(|s| s.to_string());
+
+    // GitHub's label when present, otherwise computed from a CVSS v3 vector
+    let record_severity = json["database_specific"]["severity"]
+        .as_str()
+        .map(parse_severity_label)
+        .or_else(|| {
+            json["severity"]
+                .as_array()?
+                .iter()
+                .find_map(|s| s["score"].as_str().and_then(cvss3_severity))
+        })
+        .unwrap_or(Severity::Unknown);
+
+    let Some(affected) = json["affected"].as_array() else {
+        return Vec::new();
+    };
+
+    affected
+        .iter()
+        .filter_map(|entry| {
+            let ecosystem = inventory_ecosystem(entry["package"]["ecosystem"].as_str()?)?;
+            let package = entry["package"]["name"].as_str()?.to_string();
+
+            let mut fixed = Vec::new();
+            let ranges = entry["ranges"]
+                .as_array()
+                .map(|ranges| {
+                    ranges
+                        .iter()
+                        .filter(|r| r["type"] != "GIT")
+                        .map(|r| {
+                            r["events"]
+                                .as_array()
+                                .map(|events| {
+                                    events
+                                        .iter()
+                                        .filter_map(|e| {
+                                            let (kind, key) = if e["introduced"].is_string() {
+                                                (EventKind::Introduced, "introduced")
+                                            } else if e["fixed"].is_string() {
+                                                (EventKind::Fixed, "fixed")
+                                            } else if e["last_affected"].is_string() {
+                                                (EventKind::LastAffected, "last_affected")
+                                            } else {
+                                                return None;
+                                            };
+                                            let version = e[key].as_str()?.to_string();
+                                            if kind == EventKind::Fixed {
+                                                fixed.push(version.clone());
+                                            }
+                                            Some((kind, version))
+                                        })
+                                        .collect()
+                                })
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let versions = entry["versions"]
+                .as_array()
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default();
+
+            let severity = entry["ecosystem_specific"]["severity"]
+                .as_str()
+                .map(parse_severity_label)
+                .unwrap_or(record_severity);
+
+            Some(Advisory {
+                id: id.clone(),
+                aliases: aliases.clone(),
+                ecosystem: ecosystem.to_string(),
+                package,
+                severity,
+                summary: summary.clone(),
+                fixed,
+                url: url.clone(),
+                affected: AffectedVersions::Osv { ranges, versions },
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct RustSecFile {
+    advisory: RustSecAdvisory,
+    #[serde(default)]
+    versions: RustSecVersions,
+}
+
+#[derive(Deserialize)]
+struct RustSecAdvisory {
+    id: String,
+    package: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    cvss: Option<String>,
+    url: Option<String>,
+    informational: Option<String>,
+    withdrawn: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct RustSecVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// A RustSec advisory: TOML front matter in a ```toml fence, then a markdown
+/// body whose first heading is the summary
+fn parse_rustsec(content: &str) -> Option<Advisory> {
+    let body = content.trim_start().strip_prefix("```toml")?;
+    let (front_matter, markdown) = body.split_once("```")?;
+    let file: RustSecFile = toml::from_str(front_matter).ok()?;
+
+    // Unmaintained/notice advisories aren't vulnerabilities
+    if file.advisory.withdrawn.is_some() || file.advisory.informational.is_some() {
+        return None;
+    }
+
+    let summary = markdown
+        .lines()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .trim_start_matches('#')
+        .trim()
+        .to_string();
+    let parse_reqs = |reqs: &[String]| reqs.iter().filter_map(|r| VersionReq::parse(r).ok()).collect();
+
+    Some(Advisory {
+        url: file
+            .advisory
+            .url
+            .or_else(|| Some(format!("https://rustsec.org/advisories/{}", file.advisory.id))),
+        id: file.advisory.id,
+        aliases: file.advisory.aliases,
+        ecosystem: "cargo".to_string(),
+        package: file.advisory.package,
+        severity: file
+            .advisory
+            .cvss
+            .as_deref()
+            .and_then(cvss3_severity)
+            .unwrap_or(Severity::Unknown),
+        summary,
+        affected: AffectedVersions::Requirements {
+            patched: parse_reqs(&file.versions.patched),
+            unaffected: parse_reqs(&file.versions.unaffected),
+        },
+        fixed: file.versions.patched,
+    })
+}
+
+/// Load advisories for the given packages from one snapshot directory, which may
+/// be an OSV export (`*.json`) or a clone of the RustSec advisory-db (`*.md`)
+fn load_advisories(dir: &Path, packages: &HashSet<(&str, &str)>) -> Vec<Advisory> {
+    let mut advisories = Vec::new();
+
+    let walk = WalkBuilder::new(dir).standard_filters(false).build();
+    for entry in walk.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let parsed = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => fs::read_to_string(path).map(|c| parse_osv(&c)).unwrap_or_default(),
+            Some("md") => fs::read_to_string(path)
+                .ok()
+                .and_then(|c| parse_rustsec(&c))
+                .into_iter()
+                .collect(),
+            _ => continue,
+        };
+        advisories.extend(
+            parsed
+                .into_iter()
+                .filter(|a| packages.contains(&(a.ecosystem.as_str(), a.package.as_str()))),
+        );
+    }
+
+    advisories
+}
+
+/// Match resolved dependencies against locally stored advisory snapshots
+pub fn audit_dependencies(sources: &[String], dependencies: &[ResolvedDependency]) -> VulnerabilityAudit {
+    let packages: HashSet<(&str, &str)> = dependencies
+        .iter()
+        .map(|d| (d.ecosystem.as_str(), d.name.as_str()))
+        .collect();
+
+    let mut read_sources = Vec::new();
+    let mut advisories = Vec::new();
+    for source in sources {
+        let dir = Path::new(source);
+        if !dir.is_dir() {
+            log::warn!("Advisory snapshot {} is not a directory", source);
+            continue;
+        }
+        advisories.extend(load_advisories(dir, &packages));
+        read_sources.push(source.clone());
+    }
+
+    let mut findings: Vec<VulnerabilityFinding> = Vec::new();
+    for dep in dependencies {
+        let mut matched: Vec<&Advisory> = advisories
+            .iter()
+            .filter(|a| a.ecosystem == dep.ecosystem && a.package == dep.name && a.affected.matches(&dep.version))
+            .collect();
+        matched.sort_by_key(|a| std::cmp::Reverse(a.severity));
+
+        let first = findings.len();
+        for advisory in matched {
+            // The same issue often appears in several databases under aliases;
+            // keep the copy with the best severity information
+            let duplicate = findings[first..].iter().any(|f| {
+                f.advisory_id == advisory.id
+                    || f.aliases.contains(&advisory.id)
+                    || advisory.aliases.contains(&f.advisory_id)
+            });
+            if duplicate {
+                continue;
+            }
+            findings.push(VulnerabilityFinding {
+                advisory_id: advisory.id.clone(),
+                aliases: advisory.aliases.clone(),
+                ecosystem: dep.ecosystem.clone(),
+                package: dep.name.clone(),
+                version: dep.version.clone(),
+                direct: dep.direct,
+                severity: advisory.severity,
+                summary: advisory.summary.clone(),
+                fixed: advisory.fixed.clone(),
+                url: advisory.url.clone(),
+            });
+        }
+    }
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.package.cmp(&b.package)));
+
+    VulnerabilityAudit {
+        sources: read_sources,
+        advisories_loaded: advisories.len(),
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osv_range(events: &[(EventKind, &str)]) -> AffectedVersions {
+        AffectedVersions::Osv {
+            ranges: vec![events.iter().map(|(kind, v)| (*kind, v.to_string())).collect()],
+            versions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn cvss_scores_match_published_values() {
+        let cases = [
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H", 9.8),
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H", 10.0),
+            ("CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:C/C:H/I:H/A:H", 9.9),
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N", 6.1),
+            ("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N", 7.5),
+            ("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:N/A:N", 5.5),
+            ("CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:N/A:N", 0.0),
+            ("CVSS:3.0/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H", 9.8),
+            ("CVSS:3.0/AV:N/AC:L/PR:L/UI:N/S:C/C:H/I:H/A:H", 9.9),
+            ("CVSS:3.0/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N", 6.1),
+        ];
+        for (vector, expected) in cases {
+            assert_eq!(cvss3_score(vector), Some(expected), "{}", vector);
+        }
+    }
+
+    #[test]
+    fn cvss_severity_buckets() {
+        assert_eq!(cvss3_severity("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"), Some(Severity::Critical));
+        assert_eq!(cvss3_severity("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N"), Some(Severity::High));
+        assert_eq!(cvss3_severity("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"), Some(Severity::Medium));
+        assert_eq!(cvss3_severity("CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:N/A:N"), Some(Severity::Unknown));
+        assert_eq!(cvss3_severity("CVSS:2.0/AV:N/AC:L/Au:N/C:P/I:P/A:P"), None);
+        assert_eq!(cvss3_severity("CVSS:3.1/AV:N/AC:L"), None);
+    }
+
+    #[test]
+    fn roundup_ignores_floating_point_noise() {
+        assert_eq!(roundup_v31(4.000_000_000_1), 4.0);
+        assert_eq!(roundup_v31(4.02), 4.1);
+        assert_eq!(roundup_v30(4.02), 4.1);
+    }
+
+    #[test]
+    fn osv_introduced_fixed_range() {
+        let affected = osv_range(&[(EventKind::Introduced, "1.2.0"), (EventKind::Fixed, "1.4.1")]);
+        assert!(!affected.matches("1.1.9"));
+        assert!(affected.matches("1.2.0"));
+        assert!(affected.matches("1.4.0"));
+        assert!(affected.matches("v1.3.0"));
+        assert!(!affected.matches("1.4.1"));
+        assert!(!affected.matches("2.0.0"));
+    }
+
+    #[test]
+    fn osv_range_from_zero_with_last_affected() {
+        let affected = osv_range(&[(EventKind::Introduced, "0"), (EventKind::LastAffected, "2.1.0")]);
+        assert!(affected.matches("0.1.0"));
+        assert!(affected.matches("2.1.0"));
+        assert!(!affected.matches("2.1.1"));
+    }
+
+    #[test]
+    fn osv_multiple_introduced_fixed_pairs() {
+        let affected = osv_range(&[
+            (EventKind::Introduced, "1.0.0"),
+            (EventKind::Fixed, "1.0.5"),
+            (EventKind::Introduced, "2.0.0"),
+            (EventKind::Fixed, "2.0.3"),
+        ]);
+        assert!(affected.matches("1.0.4"));
+        assert!(!affected.matches("1.5.0"));
+        assert!(affected.matches("2.0.2"));
+        assert!(!affected.matches("2.0.3"));
+    }
+
+    #[test]
+    fn osv_prereleases_sort_before_release() {
+        let affected = osv_range(&[(EventKind::Introduced, "0"), (EventKind::Fixed, "3.0.0")]);
+        assert!(affected.matches("3.0.0-beta.1"));
+        assert!(!affected.matches("3.0.0"));
+    }
+
+    #[test]
+    fn osv_listed_versions_and_unparseable() {
+        let affected = AffectedVersions::Osv {
+            ranges: Vec::new(),
+            versions: vec!["0.9.1".to_string()],
+        };
+        assert!(affected.matches("0.9.1"));
+        assert!(!affected.matches("0.9.2"));
+        assert!(!osv_range(&[(EventKind::Introduced, "0")]).matches("not-a-version"));
+    }
+
+    #[test]
+    fn rustsec_requirements() {
+        let affected = AffectedVersions::Requirements {
+            patched: vec![VersionReq::parse(">= 1.8.1").unwrap(), VersionReq::parse("^1.7.3, < 1.8.0").unwrap()],
+            unaffected: vec![VersionReq::parse("< 1.0.0").unwrap()],
+        };
+        assert!(!affected.matches("0.5.0"));
+        assert!(affected.matches("1.7.2"));
+        assert!(!affected.matches("1.7.4"));
+        assert!(affected.matches("1.8.0"));
+        assert!(!affected.matches("1.9.0"));
+    }
+
+    #[test]
+    fn parses_osv_record() {
+        let record = r#"{
+            "id": "GHSA-xxxx",
+            "aliases": ["CVE-2024-0001"],
+            "summary": "Prototype pollution",
+            "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N"}],
+            "affected": [
+                {
+                    "package": {"ecosystem": "npm", "name": "lodash"},
+                    "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "4.17.21"}]}]
+                },
+                {"package": {"ecosystem": "PyPI", "name": "lodash"}}
+            ]
+        }"#;
+        let advisories = parse_osv(record);
+        assert_eq!(advisories.len(), 1);
+        let advisory = &advisories[0];
+        assert_eq!(advisory.package, "lodash");
+        assert_eq!(advisory.severity, Severity::High);
+        assert_eq!(advisory.fixed, vec!["4.17.21"]);
+        assert!(advisory.affected.matches("4.17.20"));
+        assert!(!advisory.affected.matches("4.17.21"));
+    }
+}
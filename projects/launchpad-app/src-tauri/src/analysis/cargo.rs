@@ -0,0 +1,427 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use toml::{Table, Value};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CargoTomlInfo {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub edition: Option<String>,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub rust_version: Option<String>,
+    pub dependencies: Vec<CargoDependency>,
+    pub dev_dependencies: Vec<CargoDependency>,
+    pub build_dependencies: Vec<CargoDependency>,
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Members of a `[workspace]`, each with its own parsed manifest
+    pub workspace_members: Vec<CargoWorkspaceMember>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CargoDependency {
+    /// The name the crate is imported as
+    pub name: String,
+    /// The crate on the registry, when renamed with `package = "..."`
+    pub package: Option<String>,
+    pub version_req: Option<String>,
+    pub path: Option<String>,
+    pub git: Option<String>,
+    pub optional: bool,
+    pub features: Vec<String>,
+    /// Platform for `[target.'cfg(...)'.dependencies]` entries
+    pub target: Option<String>,
+    /// Inherited from `[workspace.dependencies]`
+    pub workspace: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CargoWorkspaceMember {
+    /// Member directory relative to the workspace root
+    pub path: String,
+    pub manifest: CargoTomlInfo,
+}
+
+fn string_field(table: &Table, key: &str) -> Option<String> {
+    table.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default()
+}
+
+/// Parse one dependency table (`[dependencies]`, `[dev-dependencies]`, ...)
+fn parse_dependencies(
+    section: Option<&Value>,
+    target: Option<&str>,
+    workspace_deps: &BTreeMap<String, CargoDependency>,
+) -> Vec<CargoDependency> {
+    let Some(table) = section.and_then(|v| v.as_table()) else {
+        return Vec::new();
+    };
+
+    table
+        .iter()
+        .map(|(name, spec)| {
+            let mut dep = match spec {
+                // `serde = "1.0"`
+                Value::String(version) => CargoDependency {
+                    name: name.clone(),
+                    package: None,
+                    version_req: Some(version.clone()),
+                    path: None,
+                    git: None,
+                    optional: false,
+                    features: Vec::new(),
+                    target: None,
+                    workspace: false,
+                },
+                // `serde = { version = "1.0", features = ["derive"] }`
+                Value::Table(t) => CargoDependency {
+                    name: name.clone(),
+                    package: string_field(t, "package"),
+                    version_req: string_field(t, "version"),
+                    path: string_field(t, "path"),
+                    git: string_field(t, "git"),
+                    optional: t.get("optional").and_then(|v| v.as_bool()).unwrap_or(false),
+                    features: string_list(t.get("features")),
+                    target: None,
+                    workspace: t.get("workspace").and_then(|v| v.as_bool()).unwrap_or(false),
+                },
+                _ => CargoDependency {
+                    name: name.clone(),
+                    package: None,
+                    version_req: None,
+                    path: None,
+                    git: None,
+                    optional: false,
+                    features: Vec::new(),
+                    target: None,
+                    workspace: false,
+                },
+            };
+
+            // `foo = { workspace = true }` takes its source from the workspace root
+            if dep.workspace {
+                if let Some(inherited) = workspace_deps.get(name) {
+                    dep.package = dep.package.or_else(|| inherited.package.clone());
+                    dep.version_req = inherited.version_req.clone();
+                    dep.path = inherited.path.clone();
+                    dep.git = inherited.git.clone();
+                    dep.features.extend(inherited.features.iter().cloned());
+                }
+            }
+            dep.target = target.map(|t| t.to_string());
+            dep
+        })
+        .collect()
+}
+
+/// Parse a manifest's dependency sections, including target-specific ones
+fn parse_manifest(
+    manifest: &Table,
+    workspace_package: &Table,
+    workspace_deps: &BTreeMap<String, CargoDependency>,
+) -> CargoTomlInfo {
+    let empty = Table::new();
+    let package = manifest.get("package").and_then(|v| v.as_table()).unwrap_or(&empty);
+
+    // Package fields may be inherited from `[workspace.package]` (`version.workspace = true`)
+    let package_field = |key: &str| match package.get(key) {
+        Some(Value::Table(t)) if t.get("workspace").and_then(|v| v.as_bool()) == Some(true) => {
+            string_field(workspace_package, key)
+        }
+        _ => string_field(package, key),
+    };
+
+    let mut info = CargoTomlInfo {
+        name: package_field("name"),
+        version: package_field("version"),
+        edition: package_field("edition"),
+        description: package_field("description"),
+        license: package_field("license"),
+        rust_version: package_field("rust-version"),
+        dependencies: parse_dependencies(manifest.get("dependencies"), None, workspace_deps),
+        dev_dependencies: parse_dependencies(manifest.get("dev-dependencies"), None, workspace_deps),
+        build_dependencies: parse_dependencies(manifest.get("build-dependencies"), None, workspace_deps),
+        features: manifest
+            .get("features")
+            .and_then(|v| v.as_table())
+            .map(|t| t.iter().map(|(k, v)| (k.clone(), string_list(Some(v)))).collect())
+            .unwrap_or_default(),
+        workspace_members: Vec::new(),
+    };
+
+    if let Some(targets) = manifest.get("target").and_then(|v| v.as_table()) {
+        for (target, sections) in targets {
+            info.dependencies.extend(parse_dependencies(sections.get("dependencies"), Some(target), workspace_deps));
+            info.dev_dependencies.extend(parse_dependencies(sections.get("dev-dependencies"), Some(target), workspace_deps));
+            info.build_dependencies.extend(parse_dependencies(sections.get("build-dependencies"), Some(target), workspace_deps));
+        }
+    }
+
+    info
+}
+
+/// Expand `[workspace] members` globs relative to the workspace root, minus `exclude`
+fn workspace_member_dirs(root: &Path, workspace: &Table) -> Vec<String> {
+    let normalize = |path: &str| path.trim_start_matches("./").trim_end_matches('/').to_string();
+    let exclude: Vec<String> = string_list(workspace.get("exclude")).iter().map(|e| normalize(e)).collect();
+    // The root itself may contain glob metacharacters, e.g. `projects [old]`
+    let escaped_root = glob::Pattern::escape(&root.to_string_lossy());
+    let mut dirs = Vec::new();
+
+    for member in string_list(workspace.get("members")) {
+        let pattern = Path::new(&escaped_root).join(normalize(&member)).to_string_lossy().to_string();
+        let Ok(paths) = glob::glob(&pattern) else {
+            continue;
+        };
+        for path in paths.filter_map(|p| p.ok()) {
+            if !path.join("Cargo.toml").is_file() {
+                continue;
+            }
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            if !exclude.contains(&relative) && !dirs.contains(&relative) {
+                dirs.push(relative);
+            }
+        }
+    }
+
+    dirs.sort();
+    dirs
+}
+
+/// Parse `<dir>/Cargo.toml`, analyzing each workspace member in turn
+pub fn parse_cargo_toml(dir: &Path) -> Result<CargoTomlInfo, String> {
+    let content = fs::read_to_string(dir.join("Cargo.toml")).map_err(|e| e.to_string())?;
+    let manifest: Table = content
+        .parse()
+        .map_err(|e| format!("Invalid Cargo.toml: {}", e))?;
+
+    let workspace = manifest.get("workspace").and_then(|v| v.as_table());
+    let workspace_deps: BTreeMap<String, CargoDependency> = workspace
+        .map(|ws| {
+            parse_dependencies(ws.get("dependencies"), None, &BTreeMap::new())
+                .into_iter()
+                .map(|d| (d.name.clone(), d))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let empty = Table::new();
+    let workspace_package = workspace
+        .and_then(|ws| ws.get("package"))
+        .and_then(|v| v.as_table())
+        .unwrap_or(&empty);
+
+    let mut info = parse_manifest(&manifest, workspace_package, &workspace_deps);
+
+    if let Some(workspace) = workspace {
+        for member in workspace_member_dirs(dir, workspace) {
+            let member_manifest = fs::read_to_string(dir.join(&member).join("Cargo.toml"))
+                .ok()
+                .and_then(|c| c.parse::<Table>().ok());
+            match member_manifest {
+                Some(m) => info.workspace_members.push(CargoWorkspaceMember {
+                    path: member,
+                    manifest: parse_manifest(&m, workspace_package, &workspace_deps),
+                }),
+                None => log::warn!("Skipping unreadable workspace member {}", member),
+            }
+        }
+    }
+
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn dep<'a>(deps: &'a [CargoDependency], name: &str) -> &'a CargoDependency {
+        deps.iter().find(|d| d.name == name).unwrap_or_else(|| panic!("no dependency {}", name))
+    }
+
+    const WORKSPACE: &str = r#"
+[workspace]
+members = ["crates/*", "./tools/cli/"]
+exclude = ["crates/scratch/"]
+resolver = "2"
+
+[workspace.package]
+version = "0.3.0"
+edition = "2021"
+license = "MIT"
+
+[workspace.dependencies]
+serde = { version = "1.0", features = ["derive"] }
+tokio = "1"
+internal = { path = "crates/internal", package = "acme-internal" }
+"#;
+
+    const CORE: &str = r#"
+[package]
+name = "core"
+version.workspace = true
+edition = { workspace = true }
+description = "Core types"
+
+[dependencies]
+serde = { workspace = true, features = ["rc"] }
+log = "0.4"
+json = { package = "serde_json", version = "1" }
+internal = { workspace = true }
+fancy = { version = "0.2", optional = true, default-features = false }
+upstream = { git = "https://github.com/acme/upstream" }
+
+[target.'cfg(windows)'.dependencies]
+winapi = { version = "0.3", features = ["winuser"] }
+
+[target.'cfg(unix)'.dev-dependencies]
+nix = "0.27"
+
+[dev-dependencies]
+tokio = { workspace = true }
+
+[build-dependencies]
+cc = "1"
+
+[features]
+default = ["fancy"]
+extra = []
+"#;
+
+    fn workspace(root: &Path) {
+        write(root, "Cargo.toml", WORKSPACE);
+        write(root, "crates/core/Cargo.toml", CORE);
+        write(root, "crates/internal/Cargo.toml", "[package]\nname = \"acme-internal\"\nversion = \"0.1.0\"\n");
+        write(root, "crates/scratch/Cargo.toml", "[package]\nname = \"scratch\"\n");
+        write(root, "crates/notes/README.md", "not a crate");
+        write(root, "tools/cli/Cargo.toml", "[package]\nname = \"cli\"\nversion = \"2.0.0\"\n");
+    }
+
+    #[test]
+    fn members_expand_globs_minus_excludes() {
+        let dir = tempfile::tempdir().unwrap();
+        workspace(dir.path());
+
+        let info = parse_cargo_toml(dir.path()).unwrap();
+        assert_eq!(info.name, None);
+        let members: Vec<&str> = info.workspace_members.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(members, ["crates/core", "crates/internal", "tools/cli"]);
+    }
+
+    #[test]
+    fn roots_with_glob_metacharacters_still_expand() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("projects [old]");
+        workspace(&root);
+
+        let info = parse_cargo_toml(&root).unwrap();
+        assert_eq!(info.workspace_members.len(), 3);
+    }
+
+    #[test]
+    fn package_fields_inherit_from_the_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        workspace(dir.path());
+
+        let info = parse_cargo_toml(dir.path()).unwrap();
+        let core = &info.workspace_members[0].manifest;
+        assert_eq!(core.name.as_deref(), Some("core"));
+        assert_eq!(core.version.as_deref(), Some("0.3.0"));
+        assert_eq!(core.edition.as_deref(), Some("2021"));
+        assert_eq!(core.description.as_deref(), Some("Core types"));
+        // Only fields that opt in are inherited
+        assert_eq!(core.license, None);
+        assert_eq!(info.workspace_members[2].manifest.version.as_deref(), Some("2.0.0"));
+    }
+
+    #[test]
+    fn dependency_specs_in_every_form() {
+        let dir = tempfile::tempdir().unwrap();
+        workspace(dir.path());
+
+        let info = parse_cargo_toml(dir.path()).unwrap();
+        let core = &info.workspace_members[0].manifest;
+
+        let log = dep(&core.dependencies, "log");
+        assert_eq!(log.version_req.as_deref(), Some("0.4"));
+        assert!(!log.workspace && !log.optional && log.target.is_none());
+
+        let json = dep(&core.dependencies, "json");
+        assert_eq!(json.package.as_deref(), Some("serde_json"));
+        assert_eq!(json.version_req.as_deref(), Some("1"));
+
+        let fancy = dep(&core.dependencies, "fancy");
+        assert!(fancy.optional);
+
+        let upstream = dep(&core.dependencies, "upstream");
+        assert_eq!(upstream.git.as_deref(), Some("https://github.com/acme/upstream"));
+        assert_eq!(upstream.version_req, None);
+
+        let winapi = dep(&core.dependencies, "winapi");
+        assert_eq!(winapi.target.as_deref(), Some("cfg(windows)"));
+        assert_eq!(winapi.features, ["winuser"]);
+
+        let nix = dep(&core.dev_dependencies, "nix");
+        assert_eq!(nix.target.as_deref(), Some("cfg(unix)"));
+        assert_eq!(dep(&core.build_dependencies, "cc").version_req.as_deref(), Some("1"));
+
+        assert_eq!(core.features["default"], ["fancy"]);
+        assert!(core.features["extra"].is_empty());
+    }
+
+    #[test]
+    fn workspace_dependencies_are_inherited() {
+        let dir = tempfile::tempdir().unwrap();
+        workspace(dir.path());
+
+        let info = parse_cargo_toml(dir.path()).unwrap();
+        let core = &info.workspace_members[0].manifest;
+
+        let serde = dep(&core.dependencies, "serde");
+        assert!(serde.workspace);
+        assert_eq!(serde.version_req.as_deref(), Some("1.0"));
+        // Member features add to the workspace's
+        assert_eq!(serde.features, ["rc", "derive"]);
+
+        let internal = dep(&core.dependencies, "internal");
+        assert_eq!(internal.package.as_deref(), Some("acme-internal"));
+        assert_eq!(internal.path.as_deref(), Some("crates/internal"));
+
+        let tokio = dep(&core.dev_dependencies, "tokio");
+        assert!(tokio.workspace);
+        assert_eq!(tokio.version_req.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn a_single_crate_has_no_members() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "Cargo.toml", "[package]\nname = \"solo\"\nrust-version = \"1.77\"\n\n[dependencies]\nanyhow = \"1\"\n");
+
+        let info = parse_cargo_toml(dir.path()).unwrap();
+        assert_eq!(info.name.as_deref(), Some("solo"));
+        assert_eq!(info.rust_version.as_deref(), Some("1.77"));
+        assert_eq!(info.dependencies.len(), 1);
+        assert!(info.workspace_members.is_empty());
+
+        write(dir.path(), "Cargo.toml", "[package\nname = ");
+        assert!(parse_cargo_toml(dir.path()).unwrap_err().starts_with("Invalid Cargo.toml"));
+    }
+}
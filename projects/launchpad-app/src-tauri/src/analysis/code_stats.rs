@@ -0,0 +1,378 @@
+use crate::analysis::gates::{inline_tests_start, is_test_path};
+use crate::commands::walker::ProjectWalker;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+
+const MAX_FILE_SIZE: u64 = 1_000_000;
+const LARGEST_FILES: usize = 10;
+/// How many leading lines to search for a "generated" marker
+const HEADER_LINES: usize = 5;
+
+/// Vendored code checked into the repo rather than ignored
+const VENDOR_DIRS: &[&str] = &["vendor", "third_party", "third-party"];
+/// Lockfiles with a source-like extension
+const GENERATED_FILES: &[&str] = &["package-lock.json", "npm-shrinkwrap.json", "pnpm-lock.yaml", "deno.lock"];
+const GENERATED_SUFFIXES: &[&str] = &[".min.js", ".min.css", ".pb.go", "_pb2.py", ".g.dart", ".generated.ts"];
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT", "auto-generated", "autogenerated"];
+
+struct Language {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Counts toward the test-to-source ratio; data and prose formats don't
+    source: bool,
+}
+
+const fn lang(
+    name: &'static str,
+    extensions: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    source: bool,
+) -> Language {
+    Language { name, extensions, line_comments, block_comment, source }
+}
+
+const C_BLOCK: Option<(&str, &str)> = Some(("/*", "*/"));
+const HTML_BLOCK: Option<(&str, &str)> = Some(("<!--", "-->"));
+
+const LANGUAGES: &[Language] = &[
+    lang("Rust", &["rs"], &["//"], C_BLOCK, true),
+    lang("TypeScript", &["ts", "tsx", "mts", "cts"], &["//"], C_BLOCK, true),
+    lang("JavaScript", &["js", "jsx", "mjs", "cjs"], &["//"], C_BLOCK, true),
+    lang("Python", &["py", "pyi"], &["#"], None, true),
+    lang("Go", &["go"], &["//"], C_BLOCK, true),
+    lang("Java", &["java"], &["//"], C_BLOCK, true),
+    lang("Kotlin", &["kt", "kts"], &["//"], C_BLOCK, true),
+    lang("Swift", &["swift"], &["//"], C_BLOCK, true),
+    lang("C", &["c", "h"], &["//"], C_BLOCK, true),
+    lang("C++", &["cpp", "cc", "cxx", "hpp", "hh"], &["//"], C_BLOCK, true),
+    lang("C#", &["cs"], &["//"], C_BLOCK, true),
+    lang("Ruby", &["rb"], &["#"], Some(("=begin", "=end")), true),
+    lang("PHP", &["php"], &["//", "#"], C_BLOCK, true),
+    lang("Vue", &["vue"], &["//"], HTML_BLOCK, true),
+    lang("Svelte", &["svelte"], &["//"], HTML_BLOCK, true),
+    lang("Astro", &["astro"], &["//"], HTML_BLOCK, true),
+    lang("Shell", &["sh", "bash", "zsh"], &["#"], None, true),
+    lang("SQL", &["sql"], &["--"], C_BLOCK, true),
+    lang("CSS", &["css", "scss", "sass", "less"], &["//"], C_BLOCK, false),
+    lang("HTML", &["html", "htm"], &[], HTML_BLOCK, false),
+    lang("JSON", &["json", "jsonc"], &["//"], C_BLOCK, false),
+    lang("TOML", &["toml"], &["#"], None, false),
+    lang("YAML", &["yml", "yaml"], &["#"], None, false),
+    lang("Markdown", &["md", "mdx"], &[], HTML_BLOCK, false),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileLines {
+    /// Relative to the project root
+    pub path: String,
+    pub language: String,
+    pub code: usize,
+}
+
+/// Line counts for every recognised source file the walker visits
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CodeStats {
+    /// Most code first
+    pub languages: Vec<LanguageStats>,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+    /// Largest by code lines
+    pub largest_files: Vec<FileLines>,
+    /// Code lines in test files of source languages
+    pub test_code: usize,
+    /// Code lines in everything else of source languages
+    pub source_code: usize,
+    /// `test_code / source_code`; `None` when there is no source
+    pub test_to_source_ratio: Option<f64>,
+    /// Minified, generated or vendored files left out of the counts
+    pub skipped_files: usize,
+}
+
+#[derive(Default)]
+struct LineCounts {
+    code: usize,
+    comments: usize,
+    blanks: usize,
+}
+
+/// Whether a code line leaves a block comment open, e.g. `let x = 1; /* note`.
+/// Markers inside string literals or after a line comment don't count.
+fn opens_block(line: &str, language: &Language) -> bool {
+    let Some((start, end)) = language.block_comment else {
+        return false;
+    };
+    // Rust's `'` is as often a lifetime as a char literal
+    let quotes: &[char] = if language.name == "Rust" { &['"'] } else { &['"', '\'', '`'] };
+
+    let mut open = false;
+    let mut quote: Option<char> = None;
+    let mut index = 0;
+    while let Some(c) = line[index..].chars().next() {
+        let rest = &line[index..];
+        let mut step = c.len_utf8();
+        if open {
+            if rest.starts_with(end) {
+                open = false;
+                step = end.len();
+            }
+        } else if let Some(q) = quote {
+            if c == '\\' {
+                step += rest[step..].chars().next().map_or(0, char::len_utf8);
+            } else if c == q {
+                quote = None;
+            }
+        } else if rest.starts_with(start) {
+            open = true;
+            step = start.len();
+        } else if language.line_comments.iter().any(|m| rest.starts_with(m)) {
+            break;
+        } else if quotes.contains(&c) {
+            quote = Some(c);
+        }
+        index += step;
+    }
+    open
+}
+
+/// Classify each line as code, comment or blank. A line that starts with a
+/// comment marker is a comment even if the marker is really inside a
+/// multi-line string.
+fn count_lines(content: &str, language: &Language) -> LineCounts {
+    let mut counts = LineCounts::default();
+    let mut in_block: Option<&str> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(end) = in_block {
+            if trimmed.contains(end) {
+                in_block = None;
+                // Code after the closing marker makes it a code line
+                let rest = trimmed.split_once(end).map_or("", |(_, rest)| rest).trim();
+                if !rest.is_empty() && !language.line_comments.iter().any(|c| rest.starts_with(c)) {
+                    counts.code += 1;
+                    continue;
+                }
+            }
+            counts.comments += 1;
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            counts.blanks += 1;
+        } else if language.line_comments.iter().any(|c| trimmed.starts_with(c)) {
+            counts.comments += 1;
+        } else if let Some((start, end)) = language.block_comment.filter(|(start, _)| trimmed.starts_with(start)) {
+            let after = &trimmed[start.len()..];
+            match after.split_once(end) {
+                Some((_, rest)) if !rest.trim().is_empty() => counts.code += 1,
+                Some(_) => counts.comments += 1,
+                None => {
+                    in_block = Some(end);
+                    counts.comments += 1;
+                }
+            }
+        } else {
+            counts.code += 1;
+            if opens_block(trimmed, language) {
+                in_block = language.block_comment.map(|(_, end)| end);
+            }
+        }
+    }
+
+    counts
+}
+
+/// Split off a trailing Rust `#[cfg(test)]` module so it counts as test code
+fn split_inline_tests<'a>(content: &'a str, language: &Language) -> (&'a str, &'a str) {
+    if language.name != "Rust" {
+        return (content, "");
+    }
+    match inline_tests_start(content) {
+        Some(index) => content.split_at(index),
+        None => (content, ""),
+    }
+}
+
+fn language_for(file_name: &str) -> Option<&'static Language> {
+    let extension = file_name.rsplit_once('.')?.1.to_ascii_lowercase();
+    LANGUAGES.iter().find(|l| l.extensions.contains(&extension.as_str()))
+}
+
+fn is_generated(file_name: &str, content: &str) -> bool {
+    GENERATED_FILES.contains(&file_name)
+        || GENERATED_SUFFIXES.iter().any(|s| file_name.ends_with(s))
+        || content
+            .lines()
+            .take(HEADER_LINES)
+            .any(|line| GENERATED_MARKERS.iter().any(|m| line.contains(m)))
+}
+
+/// Count lines per language across the files `walker` visits (so ignore rules
+/// apply), leaving out generated and vendored files
+pub fn collect_code_stats(walker: &ProjectWalker) -> CodeStats {
+    let mut stats = CodeStats::default();
+    let mut by_language: BTreeMap<&str, LanguageStats> = BTreeMap::new();
+    let mut files: Vec<FileLines> = Vec::new();
+
+    for entry in walker.files() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(language) = language_for(&file_name) else {
+            continue;
+        };
+        let relative = walker.relative(entry.path()).replace('\\', "/");
+        if relative.split('/').rev().skip(1).any(|d| VENDOR_DIRS.contains(&d)) {
+            stats.skipped_files += 1;
+            continue;
+        }
+        if entry.metadata().map(|m| m.len() > MAX_FILE_SIZE).unwrap_or(true) {
+            stats.skipped_files += 1;
+            continue;
+        }
+        // Binary or non-UTF-8 files with a source extension
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        if is_generated(&file_name, &content) {
+            stats.skipped_files += 1;
+            continue;
+        }
+
+        let (main, inline_tests) = split_inline_tests(&content, language);
+        let counts = count_lines(main, language);
+        let test_counts = count_lines(inline_tests, language);
+        let entry_stats = by_language.entry(language.name).or_insert_with(|| LanguageStats {
+            language: language.name.to_string(),
+            ..Default::default()
+        });
+        entry_stats.files += 1;
+        entry_stats.code += counts.code + test_counts.code;
+        entry_stats.comments += counts.comments + test_counts.comments;
+        entry_stats.blanks += counts.blanks + test_counts.blanks;
+
+        if language.source {
+            if is_test_path(&relative) {
+                stats.test_code += counts.code + test_counts.code;
+            } else {
+                stats.source_code += counts.code;
+                stats.test_code += test_counts.code;
+            }
+        }
+
+        files.push(FileLines {
+            path: relative,
+            language: language.name.to_string(),
+            code: counts.code + test_counts.code,
+        });
+    }
+
+    stats.languages = by_language.into_values().collect();
+    stats.languages.sort_by_key(|l| Reverse(l.code));
+    stats.code = stats.languages.iter().map(|l| l.code).sum();
+    stats.comments = stats.languages.iter().map(|l| l.comments).sum();
+    stats.blanks = stats.languages.iter().map(|l| l.blanks).sum();
+
+    files.sort_by(|a, b| b.code.cmp(&a.code).then_with(|| a.path.cmp(&b.path)));
+    files.truncate(LARGEST_FILES);
+    stats.largest_files = files;
+
+    stats.test_to_source_ratio = (stats.source_code > 0).then(|| stats.test_code as f64 / stats.source_code as f64);
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(file_name: &str, content: &str) -> (usize, usize, usize) {
+        let counts = count_lines(content, language_for(file_name).unwrap());
+        (counts.code, counts.comments, counts.blanks)
+    }
+
+    #[test]
+    fn lines_are_code_comments_or_blank() {
+        let content = "// header\n\nfn main() {\n    let x = 1; // trailing\n}\n";
+        assert_eq!(count("main.rs", content), (3, 1, 1));
+        assert_eq!(count("app.py", "# comment\nimport os\n\n\n"), (1, 1, 2));
+        assert_eq!(count("notes.md", "# Title\n<!-- hidden -->\ntext\n"), (2, 1, 0));
+    }
+
+    #[test]
+    fn block_comments_span_lines() {
+        let content = "/*\n * docs\n */\nconst a = 1;\n/* one line */\n/* then */ const b = 2;\n";
+        assert_eq!(count("a.ts", content), (2, 4, 0));
+
+        // Opened after code and closed before code
+        let content = "let x = 1; /* note\nstill a note\n*/ let y = 2;\nlet z = 3;\n";
+        assert_eq!(count("a.rs", content), (3, 1, 0));
+
+        // Opened and closed on the same code line
+        assert_eq!(count("a.c", "int x = 1; /* note */\nint y;\n"), (2, 0, 0));
+
+        let content = "=begin\nnot code\n=end\nputs 1\n";
+        assert_eq!(count("a.rb", content), (1, 3, 0));
+    }
+
+    #[test]
+    fn comment_markers_inside_strings_do_not_open_blocks() {
+        let content = "const glob = \"src/**/*.ts\";\nconst a = 1;\nconst b = 2;\n";
+        assert_eq!(count("a.ts", content), (3, 0, 0));
+        let content = "const glob = 'src/*';\nconst t = `/*`;\nconst a = 1;\n";
+        assert_eq!(count("a.js", content), (3, 0, 0));
+        let content = "let s = \"a \\\" /* b\";\nlet t = 1;\n";
+        assert_eq!(count("a.rs", content), (2, 0, 0));
+        // Rust lifetimes aren't quotes
+        let content = "fn f<'a>(s: &'a str) /* note\n*/ {}\n";
+        assert_eq!(count("a.rs", content), (2, 0, 0));
+        // Nor are markers after a line comment
+        let content = "let x = 1; // see /* below\nlet y = 2;\n";
+        assert_eq!(count("a.rs", content), (2, 0, 0));
+        // A real block after a closed string still opens
+        let content = "let s = \"/*\"; /* note\nmore\n*/\n";
+        assert_eq!(count("a.rs", content), (1, 2, 0));
+    }
+
+    #[test]
+    fn inline_test_modules_split_off_rust_only() {
+        let rust = language_for("lib.rs").unwrap();
+        let content = "fn a() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn t() {}\n}\n";
+        let (main, tests) = split_inline_tests(content, rust);
+        assert_eq!(main, "fn a() {}\n\n");
+        assert!(tests.starts_with("#[cfg(test)]"));
+        assert_eq!(count_lines(tests, rust).code, 5);
+
+        assert_eq!(split_inline_tests("fn a() {}\n", rust), ("fn a() {}\n", ""));
+        let ts = language_for("a.ts").unwrap();
+        let content = "x\n#[cfg(test)]\n";
+        assert_eq!(split_inline_tests(content, ts), (content, ""));
+    }
+
+    #[test]
+    fn generated_files_by_name_suffix_or_header() {
+        assert!(is_generated("package-lock.json", "{}"));
+        assert!(is_generated("app.min.js", "x"));
+        assert!(is_generated("api.pb.go", "package api"));
+        assert!(is_generated("schema.ts", "/* eslint-disable */\n// @generated by codegen\n"));
+        assert!(is_generated("types.go", "// Code generated by sqlc. DO NOT EDIT.\npackage db\n"));
+        assert!(!is_generated("app.js", "x"));
+        assert!(!is_generated("minify.js", "x"));
+        // Only the header is searched
+        let late_marker = "a\nb\nc\nd\ne\n// @generated\n";
+        assert!(!is_generated("late.ts", late_marker));
+    }
+}
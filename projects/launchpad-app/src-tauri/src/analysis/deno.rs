@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const CONFIG_FILES: &[&str] = &["deno.json", "deno.jsonc"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DenoConfigInfo {
+    /// deno.json or deno.jsonc
+    pub file: String,
+    pub name: Option<String>,
+    /// Package names from the import map, e.g. `hono` for `npm:hono@^4` or
+    /// `@std/path` for `jsr:@std/path@1`
+    pub imports: Vec<String>,
+    pub tasks: Vec<String>,
+}
+
+/// Drop `//` and `/* */` comments and trailing commas so JSONC parses as JSON.
+/// String contents are left alone.
+pub fn strip_jsonc(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            (',', _) => {
+                // A comma followed only by whitespace and comments before a closing bracket is trailing
+                if !matches!(next_token(chars.clone()), Some('}') | Some(']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// The next character outside whitespace and comments
+fn next_token(mut chars: std::iter::Peekable<std::str::Chars>) -> Option<char> {
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            (c, _) if c.is_whitespace() => {}
+            ('/', Some('/')) => {
+                chars.find(|c| *c == '\n')?;
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    let c = chars.next()?;
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => return Some(c),
+        }
+    }
+    None
+}
+
+/// `npm:hono@^4.0.0` -> `hono`, `jsr:@std/path@^1` -> `@std/path`,
+/// `https://deno.land/x/oak@v12.6.1/mod.ts` -> `oak`
+fn import_package(specifier: &str) -> Option<String> {
+    let bare = specifier
+        .strip_prefix("npm:")
+        .or_else(|| specifier.strip_prefix("jsr:"))
+        .map(|s| s.trim_start_matches('/'));
+    if let Some(bare) = bare {
+        // The version starts at the first `@` that isn't the scope's, and a
+        // subpath may follow the name
+        let version_at = bare.get(1..)?.find('@').map_or(bare.len(), |i| i + 1);
+        let segments = if bare.starts_with('@') { 2 } else { 1 };
+        let name = bare[..version_at].split('/').take(segments).collect::<Vec<_>>().join("/");
+        return (!name.is_empty()).then_some(name);
+    }
+
+    let module = specifier.strip_prefix("https://deno.land/x/")?;
+    let name = module.split(['@', '/']).next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Parse deno.json or deno.jsonc in `dir`; `None` when neither exists
+pub fn parse_deno_config(dir: &Path) -> Option<DenoConfigInfo> {
+    let (file, content) = CONFIG_FILES
+        .iter()
+        .find_map(|file| fs::read_to_string(dir.join(file)).ok().map(|c| (*file, c)))?;
+
+    let json: serde_json::Value = match serde_json::from_str(&strip_jsonc(&content)) {
+        Ok(json) => json,
+        Err(e) => {
+            log::warn!("Failed to parse {} in {}: {}", file, dir.display(), e);
+            return Some(DenoConfigInfo {
+                file: file.to_string(),
+                ..Default::default()
+            });
+        }
+    };
+
+    let mut imports: Vec<String> = json["imports"]
+        .as_object()
+        .map(|map| map.values().filter_map(|v| v.as_str()).filter_map(import_package).collect())
+        .unwrap_or_default();
+    imports.sort();
+    imports.dedup();
+
+    Some(DenoConfigInfo {
+        file: file.to_string(),
+        name: json["name"].as_str().map(|s| s.to_string()),
+        imports,
+        tasks: json["tasks"]
+            .as_object()
+            .map(|o| o.keys().cloned().collect())
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jsonc_comments_and_trailing_commas_are_stripped() {
+        let content = r#"{
+  // The package name
+  "name": "@acme/app",
+  /* multi
+     line */
+  "url": "https://example.com/a//b",
+  "escaped": "quote \" // still a string",
+  "list": [1, 2, /* trailing */ ],
+}"#;
+        let json: serde_json::Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(json["name"], "@acme/app");
+        assert_eq!(json["url"], "https://example.com/a//b");
+        assert_eq!(json["escaped"], "quote \" // still a string");
+        assert_eq!(json["list"], serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn import_specifiers_resolve_to_package_names() {
+        assert_eq!(import_package("npm:hono@^4.0.0").as_deref(), Some("hono"));
+        assert_eq!(import_package("npm:preact/hooks").as_deref(), Some("preact"));
+        assert_eq!(import_package("npm:@types/node@20").as_deref(), Some("@types/node"));
+        assert_eq!(import_package("jsr:@std/path@^1/posix").as_deref(), Some("@std/path"));
+        assert_eq!(import_package("jsr:/@std/assert").as_deref(), Some("@std/assert"));
+        assert_eq!(import_package("https://deno.land/x/oak@v12.6.1/mod.ts").as_deref(), Some("oak"));
+        assert_eq!(import_package("./src/utils.ts"), None);
+        assert_eq!(import_package("npm:"), None);
+    }
+
+    #[test]
+    fn deno_config_reads_imports_and_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("deno.jsonc"),
+            r#"{
+  "name": "@acme/app",
+  "imports": {
+    "hono": "npm:hono@^4",
+    "hono/": "npm:hono@^4/",
+    "@std/path": "jsr:@std/path@1", // path helpers
+  },
+  "tasks": { "dev": "deno run -A main.ts", "test": "deno test" },
+}"#,
+        )
+        .unwrap();
+
+        let info = parse_deno_config(dir.path()).unwrap();
+        assert_eq!(info.file, "deno.jsonc");
+        assert_eq!(info.name.as_deref(), Some("@acme/app"));
+        assert_eq!(info.imports, ["@std/path", "hono"]);
+        assert_eq!(info.tasks, ["dev", "test"]);
+    }
+}
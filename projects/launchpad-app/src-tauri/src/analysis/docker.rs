@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const COMPOSE_FILES: &[&str] = &["compose.yaml", "compose.yml", "docker-compose.yml", "docker-compose.yaml"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DockerInfo {
+    pub has_dockerfile: bool,
+    /// `FROM` images of every build stage, skipping references to earlier stages
+    pub base_images: Vec<String>,
+    pub exposed_ports: Vec<String>,
+    pub compose_file: Option<String>,
+    pub compose_services: Vec<ComposeService>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComposeService {
+    pub name: String,
+    /// `None` for services built from a local Dockerfile
+    pub image: Option<String>,
+    pub ports: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ComposeFile {
+    /// A service written as a bare `name:` has a null body
+    #[serde(default)]
+    services: BTreeMap<String, Option<ComposeServiceEntry>>,
+}
+
+#[derive(Deserialize, Default)]
+struct ComposeServiceEntry {
+    image: Option<String>,
+    #[serde(default)]
+    ports: Vec<serde_yaml::Value>,
+}
+
+/// Base images and exposed ports from a Dockerfile
+fn parse_dockerfile(content: &str) -> (Vec<String>, Vec<String>) {
+    let mut stages: Vec<String> = Vec::new();
+    let mut images = Vec::new();
+    let mut ports = Vec::new();
+
+    // Join `\` continuations so multi-line instructions parse as one
+    let joined = content.replace("\\\r\n", " ").replace("\\\n", " ");
+    for line in joined.lines() {
+        let line = line.trim();
+        let Some((instruction, args)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+
+        if instruction.eq_ignore_ascii_case("FROM") {
+            let mut words = args.split_whitespace().filter(|w| !w.starts_with("--"));
+            let Some(image) = words.next() else {
+                continue;
+            };
+            if !stages.iter().any(|s| s.eq_ignore_ascii_case(image)) && !images.iter().any(|i| i == image) {
+                images.push(image.to_string());
+            }
+            if words.next().is_some_and(|w| w.eq_ignore_ascii_case("AS")) {
+                stages.extend(words.next().map(|s| s.to_string()));
+            }
+        } else if instruction.eq_ignore_ascii_case("EXPOSE") {
+            ports.extend(args.split_whitespace().map(|p| p.to_string()));
+        }
+    }
+
+    (images, ports)
+}
+
+fn port_text(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        // Long syntax: `{ target: 80, published: 8080 }`
+        serde_yaml::Value::Mapping(map) => {
+            let field = |key: &str| map.get(key).and_then(port_text);
+            let target = field("target")?;
+            Some(match field("published") {
+                Some(published) => format!("{}:{}", published, target),
+                None => target,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Parse the Dockerfile and compose file in `dir`; `None` when there are neither
+pub fn parse_docker(dir: &Path) -> Option<DockerInfo> {
+    let mut info = DockerInfo::default();
+
+    if let Ok(content) = fs::read_to_string(dir.join("Dockerfile")) {
+        info.has_dockerfile = true;
+        (info.base_images, info.exposed_ports) = parse_dockerfile(&content);
+    }
+
+    if let Some((file, content)) = COMPOSE_FILES
+        .iter()
+        .find_map(|file| fs::read_to_string(dir.join(file)).ok().map(|c| (*file, c)))
+    {
+        info.compose_file = Some(file.to_string());
+        match serde_yaml::from_str::<ComposeFile>(&content) {
+            Ok(compose) => {
+                info.compose_services = compose
+                    .services
+                    .into_iter()
+                    .map(|(name, service)| (name, service.unwrap_or_default()))
+                    .map(|(name, service)| ComposeService {
+                        name,
+                        image: service.image,
+                        ports: service.ports.iter().filter_map(port_text).collect(),
+                    })
+                    .collect();
+            }
+            Err(e) => log::warn!("Failed to parse {} in {}: {}", file, dir.display(), e),
+        }
+    }
+
+    (info.has_dockerfile || info.compose_file.is_some()).then_some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_stage_dockerfiles_skip_stage_references() {
+        let content = "\
+# syntax=docker/dockerfile:1
+FROM --platform=$BUILDPLATFORM node:20-alpine AS deps
+RUN npm ci
+
+from node:20-alpine as build
+COPY --from=deps /app/node_modules ./node_modules
+
+FROM build AS test
+FROM nginx:1.25 \\
+    AS runtime
+EXPOSE 80 443/tcp
+expose 8080
+";
+        let (images, ports) = parse_dockerfile(content);
+        assert_eq!(images, ["node:20-alpine", "nginx:1.25"]);
+        assert_eq!(ports, ["80", "443/tcp", "8080"]);
+    }
+
+    #[test]
+    fn compose_services_with_short_long_and_null_bodies() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("docker-compose.yml"),
+            "\
+services:
+  web:
+    build: .
+    ports:
+      - \"3000:3000\"
+      - 9229
+      - target: 80
+        published: 8080
+      - target: 443
+  db:
+    image: postgres:16
+  worker:
+",
+        )
+        .unwrap();
+
+        let info = parse_docker(dir.path()).unwrap();
+        assert!(!info.has_dockerfile);
+        assert_eq!(info.compose_file.as_deref(), Some("docker-compose.yml"));
+        let services: Vec<(&str, Option<&str>, &[String])> = info
+            .compose_services
+            .iter()
+            .map(|s| (s.name.as_str(), s.image.as_deref(), s.ports.as_slice()))
+            .collect();
+        assert_eq!(
+            services,
+            [
+                ("db", Some("postgres:16"), &[][..]),
+                ("web", None, &["3000:3000".to_string(), "9229".to_string(), "8080:80".to_string(), "443".to_string()][..]),
+                ("worker", None, &[][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn nothing_to_parse() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(parse_docker(dir.path()).is_none());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::analysis::deno::{parse_deno_config, DenoConfigInfo};
+use crate::analysis::docker::{parse_docker, DockerInfo};
+use crate::analysis::go_mod::{parse_go_mod, GoModInfo};
+use crate::analysis::python::{parse_python_project, PythonProjectInfo};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Python distributions (normalized names) that imply a framework
+const PYTHON_FRAMEWORKS: &[(&str, &str)] = &[
+    ("fastapi", "FastAPI"),
+    ("django", "Django"),
+    ("flask", "Flask"),
+    ("starlette", "Starlette"),
+    ("streamlit", "Streamlit"),
+    ("sqlalchemy", "SQLAlchemy"),
+    ("celery", "Celery"),
+];
+
+const PYTHON_SERVICES: &[(&str, &str)] = &[
+    ("psycopg", "PostgreSQL"),
+    ("psycopg2", "PostgreSQL"),
+    ("psycopg2-binary", "PostgreSQL"),
+    ("asyncpg", "PostgreSQL"),
+    ("redis", "Redis"),
+    ("pymongo", "MongoDB"),
+    ("motor", "MongoDB"),
+    ("stripe", "Stripe (Payments)"),
+    ("sentry-sdk", "Sentry (Error Tracking)"),
+    ("anthropic", "Anthropic (AI)"),
+    ("openai", "OpenAI"),
+    ("supabase", "Supabase"),
+    ("boto3", "AWS"),
+];
+
+/// Go module path prefixes (major version suffixes like `/v4` still match)
+const GO_FRAMEWORKS: &[(&str, &str)] = &[
+    ("github.com/gin-gonic/gin", "Gin"),
+    ("github.com/labstack/echo", "Echo"),
+    ("github.com/gofiber/fiber", "Fiber"),
+    ("github.com/go-chi/chi", "Chi"),
+    ("gorm.io/gorm", "GORM"),
+    ("github.com/spf13/cobra", "Cobra"),
+];
+
+const GO_SERVICES: &[(&str, &str)] = &[
+    ("github.com/jackc/pgx", "PostgreSQL"),
+    ("github.com/lib/pq", "PostgreSQL"),
+    ("github.com/redis/go-redis", "Redis"),
+    ("github.com/go-redis/redis", "Redis"),
+    ("go.mongodb.org/mongo-driver", "MongoDB"),
+    ("github.com/stripe/stripe-go", "Stripe (Payments)"),
+    ("github.com/getsentry/sentry-go", "Sentry (Error Tracking)"),
+    ("github.com/aws/aws-sdk-go", "AWS"),
+];
+
+/// Deno import map package names
+const DENO_FRAMEWORKS: &[(&str, &str)] = &[
+    ("@fresh/core", "Fresh"),
+    ("$fresh", "Fresh"),
+    ("hono", "Hono"),
+    ("@hono/hono", "Hono"),
+    ("oak", "Oak"),
+    ("@oak/oak", "Oak"),
+];
+
+const DENO_SERVICES: &[(&str, &str)] = &[
+    ("postgres", "PostgreSQL"),
+    ("@db/postgres", "PostgreSQL"),
+    ("@db/redis", "Redis"),
+    ("stripe", "Stripe (Payments)"),
+    ("@sentry/deno", "Sentry (Error Tracking)"),
+    ("@supabase/supabase-js", "Supabase"),
+];
+
+/// Substrings of a container image's name (without registry or tag)
+const IMAGE_SERVICES: &[(&str, &str)] = &[
+    ("postgres", "PostgreSQL"),
+    ("postgis", "PostgreSQL"),
+    ("timescaledb", "PostgreSQL"),
+    ("redis", "Redis"),
+    ("valkey", "Redis"),
+    ("mysql", "MySQL"),
+    ("mariadb", "MySQL"),
+    ("mongo", "MongoDB"),
+    ("rabbitmq", "RabbitMQ"),
+    ("elasticsearch", "Elasticsearch"),
+    ("opensearch", "Elasticsearch"),
+    ("minio", "MinIO (Storage)"),
+    ("kafka", "Kafka"),
+    ("memcached", "Memcached"),
+    ("clickhouse", "ClickHouse"),
+    ("nginx", "Nginx"),
+];
+
+/// Manifests other than package.json and Cargo.toml found in a directory
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EcosystemInfo {
+    pub python: Option<PythonProjectInfo>,
+    pub go: Option<GoModInfo>,
+    pub deno: Option<DenoConfigInfo>,
+    /// bun.lock, bun.lockb or bunfig.toml is present
+    pub bun: bool,
+    pub docker: Option<DockerInfo>,
+}
+
+fn push_unique(target: &mut Vec<String>, item: &str) {
+    if !target.iter().any(|t| t == item) {
+        target.push(item.to_string());
+    }
+}
+
+/// Labels from `table` for every name that `matches` one of its keys
+fn lookup<'a>(table: &[(&str, &str)], names: impl Iterator<Item = &'a str>, matches: fn(&str, &str) -> bool) -> Vec<String> {
+    let mut labels = Vec::new();
+    for name in names {
+        for (key, label) in table {
+            if matches(name, key) {
+                push_unique(&mut labels, label);
+            }
+        }
+    }
+    labels
+}
+
+fn exact(name: &str, key: &str) -> bool {
+    name == key
+}
+
+fn go_module(path: &str, prefix: &str) -> bool {
+    path == prefix || path.starts_with(&format!("{}/", prefix))
+}
+
+/// `ghcr.io/acme/postgres-custom:16-alpine` -> `postgres-custom`
+fn image_contains(image: &str, key: &str) -> bool {
+    let name = image.rsplit('/').next().unwrap_or(image);
+    let name = name.split([':', '@']).next().unwrap_or(name);
+    name.contains(key)
+}
+
+impl EcosystemInfo {
+    pub fn tech_stack(&self) -> Vec<String> {
+        let mut tech = Vec::new();
+        if self.python.is_some() {
+            tech.push("Python".to_string());
+        }
+        if self.go.is_some() {
+            tech.push("Go".to_string());
+        }
+        if self.deno.is_some() {
+            tech.push("Deno".to_string());
+        }
+        if self.bun {
+            tech.push("Bun".to_string());
+        }
+        if self.docker.is_some() {
+            tech.push("Docker".to_string());
+        }
+        tech
+    }
+
+    pub fn frameworks(&self) -> Vec<String> {
+        let mut frameworks = Vec::new();
+        if let Some(ref python) = self.python {
+            frameworks.extend(lookup(PYTHON_FRAMEWORKS, python.dependencies.iter().map(|d| d.as_str()), exact));
+        }
+        if let Some(ref go) = self.go {
+            frameworks.extend(lookup(GO_FRAMEWORKS, go.requires.iter().map(|r| r.path.as_str()), go_module));
+        }
+        if let Some(ref deno) = self.deno {
+            frameworks.extend(lookup(DENO_FRAMEWORKS, deno.imports.iter().map(|i| i.as_str()), exact));
+        }
+        frameworks
+    }
+
+    pub fn services(&self) -> Vec<String> {
+        let mut services = Vec::new();
+        if let Some(ref python) = self.python {
+            services.extend(lookup(PYTHON_SERVICES, python.dependencies.iter().map(|d| d.as_str()), exact));
+        }
+        if let Some(ref go) = self.go {
+            services.extend(lookup(GO_SERVICES, go.requires.iter().map(|r| r.path.as_str()), go_module));
+        }
+        if let Some(ref deno) = self.deno {
+            services.extend(lookup(DENO_SERVICES, deno.imports.iter().map(|i| i.as_str()), exact));
+        }
+        if let Some(ref docker) = self.docker {
+            let images = docker.compose_services.iter().filter_map(|s| s.image.as_deref());
+            services.extend(lookup(IMAGE_SERVICES, images, image_contains));
+        }
+
+        let mut unique = Vec::new();
+        for service in &services {
+            push_unique(&mut unique, service);
+        }
+        unique
+    }
+}
+
+/// Parse the Python, Go, Deno, Bun and Docker manifests in `dir`
+pub fn detect_ecosystems(dir: &Path) -> EcosystemInfo {
+    EcosystemInfo {
+        python: parse_python_project(dir),
+        go: parse_go_mod(dir),
+        deno: parse_deno_config(dir),
+        bun: ["bun.lock", "bun.lockb", "bunfig.toml"].iter().any(|f| dir.join(f).exists()),
+        docker: parse_docker(dir),
+    }
+}
@@ -0,0 +1,287 @@
+use crate::commands::walker::ProjectWalker;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// Documentation files a project declares its variables in
+const EXAMPLE_FILES: &[&str] = &[".env.example", ".env.sample", ".env.template"];
+/// Shared templates, found in `_templates/env` of the Launchpad checkout containing the project
+const TEMPLATE_DIR: &str = "_templates/env";
+
+const SOURCE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "vue", "svelte", "astro", "rs", "py", "go"];
+const MAX_SOURCE_FILE_SIZE: u64 = 1_000_000;
+const MAX_LOCATIONS: usize = 5;
+
+/// Set by the runtime, OS or bundler rather than by the project's env files
+const BUILTIN_VARS: &[&str] = &[
+    "NODE_ENV", "MODE", "DEV", "PROD", "SSR", "BASE_URL", "CI", "HOME", "PATH", "PWD", "USER", "USERPROFILE",
+    "APPDATA", "LOCALAPPDATA", "TMPDIR", "TEMP", "SHELL", "LANG", "TERM", "PATHEXT", "SystemRoot", "COMSPEC",
+    "CARGO_PKG_NAME", "CARGO_PKG_VERSION", "CARGO_MANIFEST_DIR", "OUT_DIR",
+];
+
+/// Patterns whose first capture group is a variable name
+const USAGE_PATTERNS: &[&str] = &[
+    // JS/TS
+    r"process\.env\.([A-Za-z_][A-Za-z0-9_]*)",
+    r#"process\.env\[\s*["'`]([A-Za-z_][A-Za-z0-9_]*)["'`]\s*\]"#,
+    r"import\.meta\.env\.([A-Za-z_][A-Za-z0-9_]*)",
+    r#"Deno\.env\.get\(\s*["']([A-Za-z_][A-Za-z0-9_]*)["']"#,
+    // Rust
+    r#"env::var(?:_os)?\(\s*"([A-Za-z_][A-Za-z0-9_]*)""#,
+    r#"(?:option_)?env!\(\s*"([A-Za-z_][A-Za-z0-9_]*)""#,
+    // Python
+    r#"os\.environ\[\s*["']([A-Za-z_][A-Za-z0-9_]*)["']"#,
+    r#"os\.environ\.get\(\s*["']([A-Za-z_][A-Za-z0-9_]*)["']"#,
+    r#"os\.getenv\(\s*["']([A-Za-z_][A-Za-z0-9_]*)["']"#,
+    // Go
+    r#"os\.(?:Getenv|LookupEnv)\(\s*"([A-Za-z_][A-Za-z0-9_]*)""#,
+];
+
+/// `const { A, B: b, C = "x" } = process.env`
+const DESTRUCTURING_PATTERN: &str = r"\{([^{}]*)\}\s*=\s*(?:process\.env|import\.meta\.env)\b";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnvVariableUsage {
+    pub name: String,
+    /// `file:line`, first few only
+    pub locations: Vec<String>,
+}
+
+/// Variables the code reads compared with what the project documents
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EnvContract {
+    /// The project's own `.env.example` (or similar) files, relative to the root
+    pub example_files: Vec<String>,
+    /// Shared `_templates/env` files that also count as documentation
+    pub template_files: Vec<String>,
+    pub used: Vec<EnvVariableUsage>,
+    /// Read by the code but not documented anywhere
+    pub undocumented: Vec<String>,
+    /// In the project's example files but never read. Template-only keys aren't
+    /// listed since the template covers every supported service.
+    pub unused: Vec<String>,
+    /// In the project's example files with an empty value
+    pub missing_values: Vec<String>,
+}
+
+impl EnvContract {
+    pub fn has_example(&self) -> bool {
+        !self.example_files.is_empty()
+    }
+}
+
+/// Keys from a dotenv-style file, with whether each has a non-empty value
+fn parse_env_file(content: &str) -> Vec<(String, bool)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return None;
+            }
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or(""),
+                // `KEY= # fill me in` is a placeholder comment, not a value
+                Some('#') => "",
+                _ => value
+                    .match_indices('#')
+                    .find(|(i, _)| value[..*i].ends_with(char::is_whitespace))
+                    .map_or(value, |(i, _)| &value[..i])
+                    .trim(),
+            };
+            Some((key.to_string(), !value.is_empty()))
+        })
+        .collect()
+}
+
+/// `_templates/env` in the nearest ancestor that has one
+fn find_template_dir(root: &Path) -> Option<std::path::PathBuf> {
+    root.ancestors().map(|dir| dir.join(TEMPLATE_DIR)).find(|dir| dir.is_dir())
+}
+
+fn extract_usages(walker: &ProjectWalker) -> BTreeMap<String, Vec<String>> {
+    let patterns: Vec<Regex> = USAGE_PATTERNS.iter().filter_map(|p| Regex::new(p).ok()).collect();
+    let destructuring = Regex::new(DESTRUCTURING_PATTERN).ok();
+    let mut used: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for entry in walker.files() {
+        let is_source = entry
+            .path()
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext));
+        if !is_source || entry.metadata().map(|m| m.len() > MAX_SOURCE_FILE_SIZE).unwrap_or(true) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let relative = walker.relative(entry.path()).replace('\\', "/");
+
+        for (index, line) in content.lines().enumerate() {
+            // Examples in comments and docs aren't real usage
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("//") || trimmed.starts_with('*') || trimmed.starts_with('#') {
+                continue;
+            }
+            let mut names: Vec<String> = patterns
+                .iter()
+                .flat_map(|p| p.captures_iter(line).filter_map(|c| c.get(1)).map(|m| m.as_str().to_string()))
+                .collect();
+            if let Some(ref destructuring) = destructuring {
+                for caps in destructuring.captures_iter(line) {
+                    names.extend(caps[1].split(',').filter_map(|part| {
+                        let name = part.split([':', '=']).next()?.trim();
+                        // `...rest` collects everything else rather than naming a variable
+                        let is_identifier = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                        is_identifier.then(|| name.to_string())
+                    }));
+                }
+            }
+
+            for name in names {
+                if BUILTIN_VARS.contains(&name.as_str()) {
+                    continue;
+                }
+                let locations = used.entry(name).or_default();
+                let location = format!("{}:{}", relative, index + 1);
+                if locations.len() < MAX_LOCATIONS && !locations.contains(&location) {
+                    locations.push(location);
+                }
+            }
+        }
+    }
+
+    used
+}
+
+/// Compare env variable usage in source with the project's example files (at
+/// the root and in each package directory) and the shared templates
+pub fn check_env_contract<'a>(walker: &ProjectWalker, package_dirs: impl Iterator<Item = &'a str>) -> EnvContract {
+    let root = walker.root();
+    let mut contract = EnvContract::default();
+
+    // key -> has a value in at least one example file
+    let mut declared: BTreeMap<String, bool> = BTreeMap::new();
+    for dir in std::iter::once("").chain(package_dirs) {
+        for file in EXAMPLE_FILES {
+            let relative = if dir.is_empty() { file.to_string() } else { format!("{}/{}", dir, file) };
+            let Ok(content) = fs::read_to_string(root.join(&relative)) else {
+                continue;
+            };
+            for (key, has_value) in parse_env_file(&content) {
+                *declared.entry(key).or_insert(false) |= has_value;
+            }
+            contract.example_files.push(relative);
+        }
+    }
+
+    let mut template_keys: BTreeSet<String> = BTreeSet::new();
+    if let Some(template_dir) = find_template_dir(root) {
+        if let Ok(entries) = fs::read_dir(&template_dir) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let Ok(content) = fs::read_to_string(entry.path()) else {
+                    continue;
+                };
+                template_keys.extend(parse_env_file(&content).into_iter().map(|(key, _)| key));
+                contract.template_files.push(entry.path().to_string_lossy().to_string());
+            }
+        }
+        contract.template_files.sort();
+    }
+
+    let used = extract_usages(walker);
+    contract.undocumented = used
+        .keys()
+        .filter(|name| !declared.contains_key(*name) && !template_keys.contains(*name))
+        .cloned()
+        .collect();
+    contract.unused = declared.keys().filter(|key| !used.contains_key(*key)).cloned().collect();
+    contract.missing_values = declared
+        .iter()
+        .filter(|(_, has_value)| !**has_value)
+        .map(|(key, _)| key.clone())
+        .collect();
+    contract.used = used
+        .into_iter()
+        .map(|(name, locations)| EnvVariableUsage { name, locations })
+        .collect();
+
+    contract
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_files_parse_keys_and_values() {
+        let content = "\
+# Database
+DATABASE_URL=postgres://localhost/app
+export API_KEY=\"sk-123\"
+EMPTY_QUOTED=''
+QUOTED_HASH=\"abc # not a comment\"
+PLACEHOLDER= # fill me in
+TAB_COMMENT=value\t# trailing
+URL_FRAGMENT=https://example.com/#anchor
+BLANK=
+  # indented comment
+not a line
+bad-key=1
+";
+        assert_eq!(
+            parse_env_file(content),
+            [
+                ("DATABASE_URL".to_string(), true),
+                ("API_KEY".to_string(), true),
+                ("EMPTY_QUOTED".to_string(), false),
+                ("QUOTED_HASH".to_string(), true),
+                ("PLACEHOLDER".to_string(), false),
+                ("TAB_COMMENT".to_string(), true),
+                ("URL_FRAGMENT".to_string(), true),
+                ("BLANK".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn contract_compares_usage_with_examples() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(".env.example"), "DATABASE_URL=postgres://\nSECRET=\nSTALE=1\n").unwrap();
+        fs::create_dir_all(root.join("web/src")).unwrap();
+        fs::write(root.join("web/.env.sample"), "VITE_API=http://localhost\n").unwrap();
+        fs::write(
+            root.join("web/src/config.ts"),
+            "\
+const { DATABASE_URL, SECRET: secret, PORT = \"3000\", ...rest } = process.env;
+const api = import.meta.env.VITE_API;
+const mode = process.env.NODE_ENV;
+// process.env.COMMENTED_OUT
+const token = process.env[\"TOKEN\"];
+",
+        )
+        .unwrap();
+
+        let walker = ProjectWalker::new(root, root, &[]);
+        let contract = check_env_contract(&walker, ["web"].into_iter());
+
+        assert_eq!(contract.example_files, [".env.example", "web/.env.sample"]);
+        let used: Vec<&str> = contract.used.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(used, ["DATABASE_URL", "PORT", "SECRET", "TOKEN", "VITE_API"]);
+        assert_eq!(contract.used[0].locations, ["web/src/config.ts:1"]);
+        assert_eq!(contract.undocumented, ["PORT", "TOKEN"]);
+        assert_eq!(contract.unused, ["STALE"]);
+        assert_eq!(contract.missing_values, ["SECRET"]);
+    }
+}
@@ -0,0 +1,503 @@
+use crate::commands::analyzer::ProjectAnalysis;
+use crate::commands::walker::ProjectWalker;
+use ignore::DirEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+
+/// A machine-checkable condition an SOP declares for a project to have
+/// reached it. Stored as JSON in the `gates` column of the `sops` table.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SopGate {
+    /// At least one of the paths exists, at the root or in any package
+    FileExists { any_of: Vec<String> },
+    /// At least one of the packages is declared by a manifest. A trailing `*`
+    /// matches a prefix, e.g. `@sentry/*`.
+    Dependency { any_of: Vec<String> },
+    /// A package.json script is defined
+    Script { name: String },
+    /// At least one of the services is detected; an empty list accepts any
+    Service { any_of: Vec<String> },
+    /// The project has at least this many files
+    FileCount { min: usize },
+    /// The project has at least this many test files (Rust files with an
+    /// inline test module count)
+    TestFiles { min: usize },
+    /// The most recent test command run in the project exited successfully
+    TestsPassing,
+    /// An example env file documents every variable the code reads
+    EnvDocumented,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GateResult {
+    pub gate: SopGate,
+    pub passed: bool,
+    /// What was found, or what is missing
+    pub detail: String,
+}
+
+/// Gate results for one SOP
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PhaseEvidence {
+    pub sop_number: i32,
+    pub name: String,
+    /// Every gate passed; `None` when the SOP has no checkable gates
+    pub passed: Option<bool>,
+    pub gates: Vec<GateResult>,
+}
+
+/// An SOP and the gates to check for it
+pub struct PhaseGates {
+    pub sop_number: i32,
+    pub name: String,
+    pub gates: Vec<SopGate>,
+}
+
+/// Outcome of the most recent test command, if any has been run
+#[derive(Debug, Clone)]
+pub struct TestRun {
+    pub command: String,
+    pub passed: bool,
+    pub ran_at: String,
+}
+
+/// Everything gates are checked against
+pub struct GateContext<'a> {
+    pub analysis: &'a ProjectAnalysis,
+    pub walker: &'a ProjectWalker,
+    pub last_test_run: Option<TestRun>,
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+/// Gates used for SOPs that don't declare their own. Ideation and design
+/// phases have nothing to check in the repository.
+pub fn default_gates(sop_number: i32) -> Vec<SopGate> {
+    match sop_number {
+        5 => vec![SopGate::FileExists {
+            any_of: strings(&["package.json", "Cargo.toml", "pyproject.toml", "requirements.txt", "setup.py", "go.mod"]),
+        }],
+        6 => vec![SopGate::Service { any_of: Vec::new() }],
+        7 => vec![SopGate::FileCount { min: 20 }],
+        8 => vec![SopGate::TestFiles { min: 1 }],
+        9 => vec![
+            SopGate::FileExists {
+                any_of: strings(&[".github/workflows", ".gitlab-ci.yml", "vercel.json"]),
+            },
+            SopGate::EnvDocumented,
+        ],
+        10 => vec![
+            SopGate::FileExists {
+                any_of: strings(&["vercel.json", "netlify.toml", "fly.toml", "wrangler.toml", "Dockerfile", "render.yaml"]),
+            },
+            SopGate::TestsPassing,
+        ],
+        11 => vec![SopGate::Dependency {
+            any_of: strings(&["@sentry/*", "sentry", "posthog-js", "posthog-node", "@vercel/analytics"]),
+        }],
+        12 => vec![SopGate::FileExists {
+            any_of: strings(&[
+                "public/sitemap.xml",
+                "app/sitemap.ts",
+                "src/app/sitemap.ts",
+                "public/robots.txt",
+                "app/robots.ts",
+                "src/app/robots.ts",
+            ]),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Directories whose contents are all tests
+const TEST_DIRS: &[&str] = &["tests", "test", "__tests__", "spec"];
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+/// Larger Rust files aren't read to look for an inline test module
+const MAX_INLINE_TEST_SCAN: u64 = 1_000_000;
+
+/// `foo.test.ts`, `foo.spec.mjs`, `foo_test.rs`, `test_foo.py`, `foo_test.py` and similar
+fn is_test_file(name: &str) -> bool {
+    let Some((stem, extension)) = name.rsplit_once('.') else {
+        return false;
+    };
+    match extension {
+        "rs" | "go" => stem.ends_with("_test"),
+        "py" => stem.starts_with("test_") || stem.ends_with("_test"),
+        "rb" => stem.ends_with("_spec") || stem.ends_with("_test"),
+        _ if JS_EXTENSIONS.contains(&extension) => stem.ends_with(".test") || stem.ends_with(".spec"),
+        _ => false,
+    }
+}
+
+/// A file named like a test or under a test directory, given its path
+/// relative to the project root with `/` separators
+pub fn is_test_path(relative: &str) -> bool {
+    let mut components = relative.rsplit('/');
+    components.next().is_some_and(is_test_file) || components.any(|dir| TEST_DIRS.contains(&dir))
+}
+
+/// Where a trailing Rust `#[cfg(test)]` module starts, if the file has one
+pub fn inline_tests_start(content: &str) -> Option<usize> {
+    content.find("\n#[cfg(test)]").map(|index| index + 1)
+}
+
+/// A walked file that holds tests: a test file by path, or a Rust source file
+/// with an inline test module
+pub fn is_test_entry(walker: &ProjectWalker, entry: &DirEntry) -> bool {
+    let relative = walker.relative(entry.path()).replace('\\', "/");
+    if is_test_path(&relative) {
+        return true;
+    }
+    relative.ends_with(".rs")
+        && entry.metadata().is_ok_and(|m| m.len() <= MAX_INLINE_TEST_SCAN)
+        && fs::read_to_string(entry.path()).is_ok_and(|content| inline_tests_start(&content).is_some())
+}
+
+/// Names declared by any package.json or Cargo.toml in the project
+pub(crate) fn declared_dependencies(analysis: &ProjectAnalysis) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let package_jsons = analysis
+        .package_json
+        .iter()
+        .chain(analysis.packages.iter().filter_map(|p| p.package_json.as_ref()));
+    for package in package_jsons {
+        names.extend(package.dependencies.iter().cloned());
+        names.extend(package.dev_dependencies.iter().cloned());
+    }
+
+    let cargo_tomls = analysis
+        .cargo_toml
+        .iter()
+        .chain(analysis.cargo_toml.iter().flat_map(|c| c.workspace_members.iter().map(|m| &m.manifest)))
+        .chain(analysis.packages.iter().filter_map(|p| p.cargo_toml.as_ref()));
+    for cargo in cargo_tomls {
+        for dep in cargo.dependencies.iter().chain(&cargo.dev_dependencies) {
+            names.insert(dep.package.clone().unwrap_or_else(|| dep.name.clone()));
+        }
+    }
+
+    let manifests = std::iter::once(&analysis.manifests).chain(analysis.packages.iter().map(|p| &p.manifests));
+    for manifest in manifests {
+        names.extend(manifest.python.iter().flat_map(|p| p.dependencies.iter().cloned()));
+        names.extend(manifest.go.iter().flat_map(|g| g.requires.iter().map(|r| r.path.clone())));
+        names.extend(manifest.deno.iter().flat_map(|d| d.imports.iter().cloned()));
+    }
+
+    names
+}
+
+fn dependency_matches(name: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+fn check(gate: &SopGate, ctx: &GateContext) -> (bool, String) {
+    let analysis = ctx.analysis;
+    match gate {
+        SopGate::FileExists { any_of } => {
+            let root = ctx.walker.root();
+            let dirs = std::iter::once(root.to_path_buf())
+                .chain(analysis.packages.iter().map(|p| root.join(&p.path)));
+            let found = dirs
+                .flat_map(|dir| any_of.iter().map(move |path| (dir.join(path), path)))
+                .find(|(full, _)| full.exists());
+            match found {
+                Some((full, _)) => (true, format!("Found {}", ctx.walker.relative(&full).replace('\\', "/"))),
+                None => (false, format!("None of {} exist", any_of.join(", "))),
+            }
+        }
+        SopGate::Dependency { any_of } => {
+            let declared = declared_dependencies(analysis);
+            let found: Vec<&String> = declared
+                .iter()
+                .filter(|name| any_of.iter().any(|p| dependency_matches(name, p)))
+                .collect();
+            if found.is_empty() {
+                (false, format!("No dependency on {}", any_of.join(", ")))
+            } else {
+                (true, format!("Depends on {}", found.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")))
+            }
+        }
+        SopGate::Script { name } => {
+            let defined = analysis
+                .package_json
+                .iter()
+                .chain(analysis.packages.iter().filter_map(|p| p.package_json.as_ref()))
+                .any(|p| p.scripts.contains(name));
+            if defined {
+                (true, format!("\"{}\" script defined", name))
+            } else {
+                (false, format!("No \"{}\" script in package.json", name))
+            }
+        }
+        SopGate::Service { any_of } => {
+            let found: Vec<&String> = analysis
+                .detected_services
+                .iter()
+                .filter(|s| any_of.is_empty() || any_of.iter().any(|wanted| s.contains(wanted.as_str())))
+                .collect();
+            if found.is_empty() {
+                let wanted = if any_of.is_empty() { "any service".to_string() } else { any_of.join(", ") };
+                (false, format!("No {} detected", wanted))
+            } else {
+                (true, format!("Services configured: {}", found.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")))
+            }
+        }
+        SopGate::FileCount { min } => (
+            analysis.file_count >= *min,
+            format!("{} files in project (needs {})", analysis.file_count, min),
+        ),
+        SopGate::TestFiles { min } => {
+            let count = ctx
+                .walker
+                .files()
+                .filter(|e| is_test_entry(ctx.walker, e))
+                .take(*min)
+                .count();
+            if count >= *min {
+                (true, format!("At least {} test file(s)", min))
+            } else {
+                (false, format!("{} test file(s) found (needs {})", count, min))
+            }
+        }
+        SopGate::TestsPassing => match &ctx.last_test_run {
+            Some(run) if run.passed => (true, format!("`{}` passed at {}", run.command, run.ran_at)),
+            Some(run) => (false, format!("`{}` failed at {}", run.command, run.ran_at)),
+            None => (false, "No test run recorded for this project".to_string()),
+        },
+        SopGate::EnvDocumented => {
+            let contract = &analysis.env_contract;
+            if !contract.has_example() {
+                (false, "No .env.example".to_string())
+            } else if !contract.undocumented.is_empty() {
+                (false, format!("Undocumented variables: {}", contract.undocumented.join(", ")))
+            } else {
+                (true, format!("Env documented in {}", contract.example_files.join(", ")))
+            }
+        }
+    }
+}
+
+/// Check every SOP's gates, in SOP order
+pub fn evaluate_phases(phases: &[PhaseGates], ctx: &GateContext) -> Vec<PhaseEvidence> {
+    phases
+        .iter()
+        .map(|phase| {
+            let gates: Vec<GateResult> = phase
+                .gates
+                .iter()
+                .map(|gate| {
+                    let (passed, detail) = check(gate, ctx);
+                    GateResult {
+                        gate: gate.clone(),
+                        passed,
+                        detail,
+                    }
+                })
+                .collect();
+            PhaseEvidence {
+                sop_number: phase.sop_number,
+                name: phase.name.clone(),
+                passed: (!gates.is_empty()).then(|| gates.iter().all(|g| g.passed)),
+                gates,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::EnvContract;
+    use serde_json::json;
+    use std::path::Path;
+
+    fn analysis() -> ProjectAnalysis {
+        serde_json::from_value(json!({
+            "project_path": "",
+            "tech_stack": [],
+            "frameworks": [],
+            "has_git": false,
+            "has_tests": false,
+            "has_ci": false,
+            "env_contract": EnvContract::default(),
+            "file_count": 0,
+            "directory_structure": [],
+            "detected_services": [],
+            "package_json": null,
+            "cargo_toml": null,
+            "recommendations": [],
+            "sop_progress": { "estimated_phase": 0, "phase_name": "Unknown", "evidence": [] },
+        }))
+        .unwrap()
+    }
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn phase(sop_number: i32, gates: Vec<SopGate>) -> PhaseGates {
+        PhaseGates {
+            sop_number,
+            name: format!("SOP {}", sop_number),
+            gates,
+        }
+    }
+
+    #[test]
+    fn test_paths_by_name_and_directory() {
+        for path in [
+            "src/app.test.ts",
+            "src/app.test.jsx",
+            "src/app.spec.js",
+            "src/app.test.mjs",
+            "src/app.spec.cts",
+            "src/parser_test.rs",
+            "pkg/server_test.go",
+            "test_models.py",
+            "app/models_test.py",
+            "spec/models/user_spec.rb",
+            "tests/integration.rs",
+            "src/__tests__/App.tsx",
+            "crates/core/tests/fixtures/input.json",
+        ] {
+            assert!(is_test_path(path), "{}", path);
+        }
+        for path in [
+            "src/app.ts",
+            "src/testing.ts",
+            "src/latest.rs",
+            "src/contest_test.txt",
+            "src/test.rs",
+            "docs/spec.md",
+            "attest/main.go",
+        ] {
+            assert!(!is_test_path(path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn inline_rust_test_modules_are_found() {
+        let content = "fn add() {}\n\n#[cfg(test)]\nmod tests {}\n";
+        let start = inline_tests_start(content).unwrap();
+        assert!(content[start..].starts_with("#[cfg(test)]"));
+        assert_eq!(inline_tests_start("fn add() {}\n"), None);
+        // Only at the start of a line, not in a string or comment
+        assert_eq!(inline_tests_start("// see #[cfg(test)] below\n"), None);
+    }
+
+    #[test]
+    fn test_files_gate_counts_every_kind_of_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/lib.rs", "pub fn a() {}\n\n#[cfg(test)]\nmod tests {}\n");
+        write(root, "src/main.rs", "fn main() {}\n");
+        write(root, "tests/cli.rs", "#[test]\nfn runs() {}\n");
+        write(root, "web/button.test.jsx", "test('x', () => {});\n");
+        write(root, "web/button.jsx", "export default 1;\n");
+        write(root, "node_modules/dep/index.test.js", "");
+
+        let walker = ProjectWalker::new(root, root, &[]);
+        let analysis = analysis();
+        let ctx = GateContext {
+            analysis: &analysis,
+            walker: &walker,
+            last_test_run: None,
+        };
+        let count = walker.files().filter(|e| is_test_entry(&walker, e)).count();
+        assert_eq!(count, 3);
+
+        let evidence = evaluate_phases(
+            &[phase(8, vec![SopGate::TestFiles { min: 3 }]), phase(9, vec![SopGate::TestFiles { min: 4 }])],
+            &ctx,
+        );
+        assert_eq!(evidence[0].passed, Some(true));
+        assert_eq!(evidence[1].passed, Some(false));
+        assert_eq!(evidence[1].gates[0].detail, "3 test file(s) found (needs 4)");
+    }
+
+    #[test]
+    fn phases_pass_only_when_every_gate_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "package.json", "{}");
+        write(root, "apps/web/vercel.json", "{}");
+
+        let mut analysis = analysis();
+        analysis.file_count = 2;
+        analysis.detected_services = vec!["Supabase (PostgreSQL)".to_string()];
+        analysis.packages = serde_json::from_value(json!([{
+            "path": "apps/web",
+            "name": "web",
+            "ecosystems": [],
+            "source": "pnpm-workspace",
+            "tech_stack": [],
+            "frameworks": [],
+            "detected_services": [],
+            "has_tests": false,
+            "package_json": {
+                "name": "web",
+                "dependencies": ["@sentry/nextjs"],
+                "dev_dependencies": [],
+                "scripts": ["build"],
+            },
+            "cargo_toml": null,
+        }]))
+        .unwrap();
+        let walker = ProjectWalker::new(root, root, &[]);
+        let ctx = GateContext {
+            analysis: &analysis,
+            walker: &walker,
+            last_test_run: Some(TestRun {
+                command: "pnpm test".to_string(),
+                passed: false,
+                ran_at: "2026-01-01T00:00:00Z".to_string(),
+            }),
+        };
+
+        let evidence = evaluate_phases(
+            &[
+                phase(1, Vec::new()),
+                phase(
+                    5,
+                    vec![
+                        SopGate::FileExists { any_of: strings(&["Cargo.toml", "package.json"]) },
+                        SopGate::Service { any_of: strings(&["PostgreSQL"]) },
+                    ],
+                ),
+                phase(
+                    10,
+                    vec![
+                        SopGate::FileExists { any_of: strings(&["vercel.json"]) },
+                        SopGate::TestsPassing,
+                    ],
+                ),
+                phase(
+                    11,
+                    vec![
+                        SopGate::Dependency { any_of: strings(&["@sentry/*"]) },
+                        SopGate::Script { name: "build".to_string() },
+                        SopGate::FileCount { min: 2 },
+                    ],
+                ),
+            ],
+            &ctx,
+        );
+
+        let passed: Vec<Option<bool>> = evidence.iter().map(|e| e.passed).collect();
+        assert_eq!(passed, [None, Some(true), Some(false), Some(true)]);
+        assert_eq!(evidence[1].gates[0].detail, "Found package.json");
+        assert_eq!(evidence[2].gates[0].detail, "Found apps/web/vercel.json");
+        assert!(!evidence[2].gates[1].passed);
+        assert_eq!(evidence[2].gates[1].detail, "`pnpm test` failed at 2026-01-01T00:00:00Z");
+        assert_eq!(evidence[3].gates[0].detail, "Depends on @sentry/nextjs");
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GoModInfo {
+    pub module: Option<String>,
+    pub go_version: Option<String>,
+    pub requires: Vec<GoRequire>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GoRequire {
+    /// Module path, e.g. `github.com/gin-gonic/gin`
+    pub path: String,
+    pub version: String,
+    /// Marked `// indirect`, i.e. only needed by other dependencies
+    pub indirect: bool,
+}
+
+fn parse_require(line: &str) -> Option<GoRequire> {
+    let (spec, comment) = match line.split_once("//") {
+        Some((spec, comment)) => (spec, comment),
+        None => (line, ""),
+    };
+    let mut parts = spec.split_whitespace();
+    let path = parts.next()?;
+    let version = parts.next()?;
+    Some(GoRequire {
+        path: path.trim_matches('"').to_string(),
+        version: version.to_string(),
+        indirect: comment.trim() == "indirect",
+    })
+}
+
+fn parse_go_mod_content(content: &str) -> GoModInfo {
+    let mut info = GoModInfo::default();
+    let mut in_require_block = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if in_require_block {
+            if line.starts_with(')') {
+                in_require_block = false;
+            } else if !line.is_empty() && !line.starts_with("//") {
+                info.requires.extend(parse_require(line));
+            }
+            continue;
+        }
+
+        if let Some(module) = line.strip_prefix("module ") {
+            info.module = Some(module.trim().trim_matches('"').to_string());
+        } else if let Some(version) = line.strip_prefix("go ") {
+            info.go_version = Some(version.trim().to_string());
+        } else if let Some(rest) = line.strip_prefix("require") {
+            let rest = rest.trim();
+            if rest.starts_with('(') {
+                in_require_block = true;
+            } else {
+                info.requires.extend(parse_require(rest));
+            }
+        }
+    }
+
+    info
+}
+
+/// Parse `dir/go.mod`; `None` when there isn't one
+pub fn parse_go_mod(dir: &Path) -> Option<GoModInfo> {
+    let content = fs::read_to_string(dir.join("go.mod")).ok()?;
+    Some(parse_go_mod_content(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_version_and_require_block() {
+        let content = "\
+module github.com/acme/api
+
+go 1.22
+
+require github.com/google/uuid v1.6.0
+
+require (
+	// Web framework
+	github.com/gin-gonic/gin v1.9.1
+	golang.org/x/net v0.24.0 // indirect
+
+	\"example.com/quoted\" v0.1.0
+)
+
+replace github.com/acme/lib => ../lib
+";
+        let info = parse_go_mod_content(content);
+        assert_eq!(info.module.as_deref(), Some("github.com/acme/api"));
+        assert_eq!(info.go_version.as_deref(), Some("1.22"));
+        let requires: Vec<(&str, &str, bool)> = info
+            .requires
+            .iter()
+            .map(|r| (r.path.as_str(), r.version.as_str(), r.indirect))
+            .collect();
+        assert_eq!(
+            requires,
+            [
+                ("github.com/google/uuid", "v1.6.0", false),
+                ("github.com/gin-gonic/gin", "v1.9.1", false),
+                ("golang.org/x/net", "v0.24.0", true),
+                ("example.com/quoted", "v0.1.0", false),
+            ]
+        );
+    }
+}
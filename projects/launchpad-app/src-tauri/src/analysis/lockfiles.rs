@@ -0,0 +1,764 @@
+use crate::analysis::deno::strip_jsonc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// One resolved package from a lockfile
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ResolvedDependency {
+    /// "npm" or "cargo"
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
+    /// Declared by the project itself rather than pulled in transitively
+    pub direct: bool,
+}
+
+/// Every resolved package across a project's lockfiles
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DependencyInventory {
+    /// Lockfiles that were parsed, relative to the project root
+    pub lockfiles: Vec<String>,
+    pub dependencies: Vec<ResolvedDependency>,
+}
+
+impl DependencyInventory {
+    /// Add entries, keeping one per (ecosystem, name, version) and marking it
+    /// direct if any lockfile declares it directly
+    fn merge(&mut self, lockfile: String, entries: Vec<ResolvedDependency>) {
+        let mut by_key: BTreeMap<(String, String, String), bool> = self
+            .dependencies
+            .drain(..)
+            .map(|d| ((d.ecosystem, d.name, d.version), d.direct))
+            .collect();
+        for d in entries {
+            *by_key.entry((d.ecosystem, d.name, d.version)).or_insert(false) |= d.direct;
+        }
+        self.dependencies = by_key
+            .into_iter()
+            .map(|((ecosystem, name, version), direct)| ResolvedDependency {
+                ecosystem,
+                name,
+                version,
+                direct,
+            })
+            .collect();
+        self.lockfiles.push(lockfile);
+    }
+}
+
+fn npm(name: &str, version: &str, direct: bool) -> ResolvedDependency {
+    ResolvedDependency {
+        ecosystem: "npm".to_string(),
+        name: name.to_string(),
+        version: version.to_string(),
+        direct,
+    }
+}
+
+/// Names declared in `<dir>/package.json`, used to tell direct from transitive
+/// for lockfiles that don't record it themselves
+fn declared_npm_names(dir: &Path) -> HashSet<String> {
+    let Some(json) = fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+    else {
+        return HashSet::new();
+    };
+
+    ["dependencies", "devDependencies", "optionalDependencies", "peerDependencies"]
+        .iter()
+        .filter_map(|key| json[key].as_object())
+        .flat_map(|o| o.keys().cloned())
+        .collect()
+}
+
+/// Split `name@version`, where a scoped name starts with its own `@`
+fn split_name_version(spec: &str) -> Option<(&str, &str)> {
+    let (at, _) = spec.char_indices().skip(1).find(|&(_, c)| c == '@')?;
+    Some((&spec[..at], &spec[at + 1..]))
+}
+
+/// package-lock.json: v2/v3 `packages` keyed by install path, or v1 nested `dependencies`
+fn parse_package_lock(content: &str, declared: &HashSet<String>) -> Result<Vec<ResolvedDependency>, String> {
+    let json: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut deps = Vec::new();
+
+    if let Some(packages) = json["packages"].as_object() {
+        for (key, entry) in packages {
+            // The root package is keyed "", workspace links have `link: true`
+            let Some(idx) = key.rfind("node_modules/") else {
+                continue;
+            };
+            if entry["link"].as_bool() == Some(true) {
+                continue;
+            }
+            let Some(version) = entry["version"].as_str() else {
+                continue;
+            };
+            let name = entry["name"].as_str().unwrap_or(&key[idx + "node_modules/".len()..]);
+            let direct = key.as_str() == format!("node_modules/{}", name) && declared.contains(name);
+            deps.push(npm(name, version, direct));
+        }
+        return Ok(deps);
+    }
+
+    fn walk_v1(
+        deps_json: &serde_json::Map<String, serde_json::Value>,
+        top_level: bool,
+        declared: &HashSet<String>,
+        out: &mut Vec<ResolvedDependency>,
+    ) {
+        for (name, entry) in deps_json {
+            if let Some(version) = entry["version"].as_str() {
+                out.push(npm(name, version, top_level && declared.contains(name)));
+            }
+            if let Some(nested) = entry["dependencies"].as_object() {
+                walk_v1(nested, false, declared, out);
+            }
+        }
+    }
+    if let Some(top) = json["dependencies"].as_object() {
+        walk_v1(top, true, declared, &mut deps);
+    }
+    Ok(deps)
+}
+
+/// yarn.lock, both the classic v1 format and Berry's YAML-like format
+fn parse_yarn_lock(content: &str, declared: &HashSet<String>) -> Vec<ResolvedDependency> {
+    let mut deps = Vec::new();
+    let mut current: Option<String> = None;
+
+    for line in content.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            // `"@scope/a@^1.0.0", "@scope/a@^1.1.0":` or `a@npm:^2.0.0:`
+            let first = line.trim_end_matches(':').split(',').next().unwrap_or("").trim().trim_matches('"');
+            current = split_name_version(first).map(|(name, _)| name.to_string());
+            continue;
+        }
+        let Some(name) = current.as_ref() else {
+            continue;
+        };
+        let trimmed = line.trim();
+        let version = trimmed
+            .strip_prefix("version:")
+            .or_else(|| trimmed.strip_prefix("version "))
+            .map(|v| v.trim().trim_matches('"'));
+        if let Some(version) = version {
+            // Berry marks workspace packages with a `0.0.0-use.local` version
+            if !version.contains("use.local") {
+                deps.push(npm(name, version, declared.contains(name)));
+            }
+            current = None;
+        }
+    }
+    deps
+}
+
+/// Drop the peer dependency suffix: `14.2.3(react@18.2.0)` in v6+, `14.2.3_react@18.2.0` in v5
+fn strip_peer_suffix(version: &str, v5: bool) -> &str {
+    let end = if v5 { version.find('_') } else { version.find('(') };
+    &version[..end.unwrap_or(version.len())]
+}
+
+/// pnpm-lock.yaml, lockfile versions 5 through 9
+fn parse_pnpm_lock(content: &str) -> Result<Vec<ResolvedDependency>, String> {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+    // `lockfileVersion: 5.4` is a number, later versions quote it (`'6.0'`)
+    let lockfile_version = match yaml.get("lockfileVersion") {
+        Some(serde_yaml::Value::String(v)) => v.parse::<f64>().unwrap_or(0.0),
+        Some(v) => v.as_f64().unwrap_or(0.0),
+        None => 0.0,
+    };
+    let v5 = lockfile_version < 6.0;
+
+    // Direct dependencies per importer (workspace package); older single-project
+    // lockfiles keep them at the top level
+    let mut direct: HashSet<(String, String)> = HashSet::new();
+    let importers: Vec<&serde_yaml::Value> = match yaml.get("importers").and_then(|i| i.as_mapping()) {
+        Some(importers) => importers.values().collect(),
+        None => vec![&yaml],
+    };
+    for importer in importers {
+        for section in ["dependencies", "devDependencies", "optionalDependencies"] {
+            let Some(entries) = importer.get(section).and_then(|s| s.as_mapping()) else {
+                continue;
+            };
+            for (name, spec) in entries {
+                // `next: 14.2.3` (v5) or `next: { specifier: ^14, version: 14.2.3 }` (v6+)
+                let version = spec.as_str().or_else(|| spec.get("version").and_then(|v| v.as_str()));
+                if let (Some(name), Some(version)) = (name.as_str(), version) {
+                    direct.insert((name.to_string(), strip_peer_suffix(version, v5).to_string()));
+                }
+            }
+        }
+    }
+
+    let mut deps = Vec::new();
+    if let Some(packages) = yaml.get("packages").and_then(|p| p.as_mapping()) {
+        for key in packages.keys().filter_map(|k| k.as_str()) {
+            // `/next/14.2.3` (v5), `/next@14.2.3(react@18)` (v6) or `next@14.2.3` (v9)
+            let key = key.trim_start_matches('/');
+            // v5 names can contain `_` (`/string_decoder/1.3.0`), so only the
+            // version part after the last `/` carries the peer suffix
+            let parsed = if v5 {
+                key.rsplit_once('/').map(|(name, version)| (name, strip_peer_suffix(version, true)))
+            } else {
+                split_name_version(strip_peer_suffix(key, false))
+            };
+            let Some((name, version)) = parsed else {
+                continue;
+            };
+            let is_direct = direct.contains(&(name.to_string(), version.to_string()));
+            deps.push(npm(name, version, is_direct));
+        }
+    }
+    Ok(deps)
+}
+
+#[derive(Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<CargoLockPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoLockPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+/// bun.lock, Bun's text lockfile (JSONC). The older binary bun.lockb isn't readable.
+fn parse_bun_lock(content: &str) -> Result<Vec<ResolvedDependency>, String> {
+    let json: serde_json::Value = serde_json::from_str(&strip_jsonc(content)).map_err(|e| e.to_string())?;
+
+    // Every workspace, the root included, lists what it declares
+    let declared: HashSet<&str> = json["workspaces"]
+        .as_object()
+        .into_iter()
+        .flat_map(|workspaces| workspaces.values())
+        .flat_map(|workspace| {
+            ["dependencies", "devDependencies", "optionalDependencies", "peerDependencies"]
+                .iter()
+                .filter_map(move |key| workspace[key].as_object())
+        })
+        .flat_map(|o| o.keys().map(|k| k.as_str()))
+        .collect();
+
+    let mut deps = Vec::new();
+    for (key, entry) in json["packages"].as_object().into_iter().flatten() {
+        // Entries are `[ "name@version", registry, metadata, integrity ]`; nested
+        // installs are keyed by their parent path, e.g. `parent/name`
+        let Some((name, version)) = entry[0].as_str().and_then(split_name_version) else {
+            continue;
+        };
+        if version.starts_with("workspace:") || version.starts_with("link:") {
+            continue;
+        }
+        deps.push(npm(name, version, key == name && declared.contains(name)));
+    }
+    Ok(deps)
+}
+
+/// Cargo.lock. Local crates (no `source`) are the project itself; whatever they
+/// depend on is direct.
+fn parse_cargo_lock(content: &str) -> Result<Vec<ResolvedDependency>, String> {
+    let lock: CargoLock = toml::from_str(content).map_err(|e| e.to_string())?;
+
+    // Entries are `name`, or `name version` when several versions are locked
+    let direct: HashSet<&str> = lock
+        .package
+        .iter()
+        .filter(|p| p.source.is_none())
+        .flat_map(|p| p.dependencies.iter())
+        .map(|d| d.as_str())
+        .collect();
+
+    Ok(lock
+        .package
+        .iter()
+        .filter(|p| p.source.is_some())
+        .map(|p| ResolvedDependency {
+            ecosystem: "cargo".to_string(),
+            name: p.name.clone(),
+            version: p.version.clone(),
+            direct: direct.contains(p.name.as_str())
+                || direct.contains(format!("{} {}", p.name, p.version).as_str()),
+        })
+        .collect())
+}
+
+/// Parse whichever lockfiles exist in `dir`
+fn parse_dir(dir: &Path) -> Vec<(&'static str, Result<Vec<ResolvedDependency>, String>)> {
+    let read = |name: &str| fs::read_to_string(dir.join(name)).ok();
+    let mut parsed = Vec::new();
+
+    if let Some(content) = read("pnpm-lock.yaml") {
+        parsed.push(("pnpm-lock.yaml", parse_pnpm_lock(&content)));
+    }
+    if let Some(content) = read("package-lock.json") {
+        parsed.push(("package-lock.json", parse_package_lock(&content, &declared_npm_names(dir))));
+    }
+    if let Some(content) = read("yarn.lock") {
+        parsed.push(("yarn.lock", Ok(parse_yarn_lock(&content, &declared_npm_names(dir)))));
+    }
+    if let Some(content) = read("bun.lock") {
+        parsed.push(("bun.lock", parse_bun_lock(&content)));
+    }
+    if let Some(content) = read("Cargo.lock") {
+        parsed.push(("Cargo.lock", parse_cargo_lock(&content)));
+    }
+    parsed
+}
+
+/// Build the inventory from lockfiles at the root and in each package directory
+/// (relative to `root`), so nested apps with their own lockfile are covered
+pub fn collect_inventory<'a>(root: &Path, package_dirs: impl Iterator<Item = &'a str>) -> DependencyInventory {
+    let mut inventory = DependencyInventory::default();
+
+    for dir in std::iter::once("").chain(package_dirs) {
+        for (file, result) in parse_dir(&root.join(dir)) {
+            let relative = if dir.is_empty() { file.to_string() } else { format!("{}/{}", dir, file) };
+            match result {
+                Ok(entries) => inventory.merge(relative, entries),
+                Err(e) => log::warn!("Failed to parse {}: {}", relative, e),
+            }
+        }
+    }
+
+    inventory
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(mut deps: Vec<ResolvedDependency>) -> Vec<(String, String, bool)> {
+        deps.sort();
+        deps.into_iter().map(|d| (d.name, d.version, d.direct)).collect()
+    }
+
+    fn expected(entries: &[(&str, &str, bool)]) -> Vec<(String, String, bool)> {
+        let mut entries: Vec<_> = entries
+            .iter()
+            .map(|&(name, version, direct)| (name.to_string(), version.to_string(), direct))
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    fn declared(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn pnpm_v5_keys_split_on_the_last_slash() {
+        let lock = "\
+lockfileVersion: 5.4
+
+specifiers:
+  next: ^12.3.0
+  string_decoder: ^1.3.0
+
+dependencies:
+  next: 12.3.4_react@18.2.0
+  string_decoder: 1.3.0
+
+devDependencies:
+  '@types/node': 18.19.0
+
+packages:
+
+  /next/12.3.4_react@18.2.0:
+    resolution: {integrity: sha512-a}
+    dev: false
+
+  /pkg/1.0.0_peer@2.0.0:
+    resolution: {integrity: sha512-b}
+    dev: false
+
+  /string_decoder/1.3.0:
+    resolution: {integrity: sha512-c}
+
+  /@types/node/18.19.0:
+    resolution: {integrity: sha512-d}
+    dev: true
+";
+        assert_eq!(
+            summary(parse_pnpm_lock(lock).unwrap()),
+            expected(&[
+                ("next", "12.3.4", true),
+                ("pkg", "1.0.0", false),
+                ("string_decoder", "1.3.0", true),
+                ("@types/node", "18.19.0", true),
+            ])
+        );
+    }
+
+    #[test]
+    fn pnpm_v5_workspaces_list_direct_dependencies_per_importer() {
+        let lock = "\
+lockfileVersion: 5.3
+
+importers:
+
+  .:
+    specifiers:
+      typescript: ^4.9.0
+    devDependencies:
+      typescript: 4.9.5
+
+  apps/web:
+    specifiers:
+      react: ^18.2.0
+    dependencies:
+      react: 18.2.0
+
+packages:
+
+  /loose-envify/1.4.0:
+    resolution: {integrity: sha512-a}
+
+  /react/18.2.0:
+    resolution: {integrity: sha512-b}
+
+  /typescript/4.9.5:
+    resolution: {integrity: sha512-c}
+";
+        assert_eq!(
+            summary(parse_pnpm_lock(lock).unwrap()),
+            expected(&[
+                ("loose-envify", "1.4.0", false),
+                ("react", "18.2.0", true),
+                ("typescript", "4.9.5", true),
+            ])
+        );
+    }
+
+    #[test]
+    fn pnpm_v6_keys_use_at_and_parenthesised_peers() {
+        let lock = "\
+lockfileVersion: '6.0'
+
+settings:
+  autoInstallPeers: true
+
+dependencies:
+  next:
+    specifier: ^14.2.0
+    version: 14.2.3(react@18.2.0)
+
+packages:
+
+  /@swc/helpers@0.5.5:
+    resolution: {integrity: sha512-a}
+    dev: false
+
+  /next@14.2.3(react@18.2.0):
+    resolution: {integrity: sha512-b}
+    dev: false
+
+  /react@18.2.0:
+    resolution: {integrity: sha512-c}
+";
+        assert_eq!(
+            summary(parse_pnpm_lock(lock).unwrap()),
+            expected(&[
+                ("@swc/helpers", "0.5.5", false),
+                ("next", "14.2.3", true),
+                ("react", "18.2.0", false),
+            ])
+        );
+    }
+
+    #[test]
+    fn pnpm_v9_keys_have_no_leading_slash() {
+        let lock = "\
+lockfileVersion: '9.0'
+
+importers:
+
+  .:
+    dependencies:
+      '@tanstack/react-query':
+        specifier: ^5.0.0
+        version: 5.40.0(react@18.3.1)
+      react:
+        specifier: ^18.3.0
+        version: 18.3.1
+
+packages:
+
+  '@tanstack/query-core@5.40.0':
+    resolution: {integrity: sha512-a}
+
+  '@tanstack/react-query@5.40.0':
+    resolution: {integrity: sha512-b}
+    peerDependencies:
+      react: ^18 || ^19
+
+  react@18.3.1:
+    resolution: {integrity: sha512-c}
+
+snapshots:
+
+  '@tanstack/react-query@5.40.0(react@18.3.1)':
+    dependencies:
+      '@tanstack/query-core': 5.40.0
+      react: 18.3.1
+
+  react@18.3.1: {}
+";
+        assert_eq!(
+            summary(parse_pnpm_lock(lock).unwrap()),
+            expected(&[
+                ("@tanstack/query-core", "5.40.0", false),
+                ("@tanstack/react-query", "5.40.0", true),
+                ("react", "18.3.1", true),
+            ])
+        );
+        assert!(parse_pnpm_lock("packages: [").is_err());
+    }
+
+    #[test]
+    fn package_lock_v1_walks_nested_dependencies() {
+        let lock = r#"{
+            "name": "app",
+            "lockfileVersion": 1,
+            "requires": true,
+            "dependencies": {
+                "express": {
+                    "version": "4.18.2",
+                    "requires": { "debug": "2.6.9" },
+                    "dependencies": {
+                        "debug": { "version": "2.6.9" }
+                    }
+                },
+                "debug": { "version": "4.3.4", "dev": true }
+            }
+        }"#;
+        assert_eq!(
+            summary(parse_package_lock(lock, &declared(&["express", "debug"])).unwrap()),
+            expected(&[("express", "4.18.2", true), ("debug", "2.6.9", false), ("debug", "4.3.4", true)])
+        );
+    }
+
+    #[test]
+    fn package_lock_v2_prefers_packages_over_legacy_dependencies() {
+        let lock = r#"{
+            "name": "app",
+            "lockfileVersion": 2,
+            "packages": {
+                "": { "name": "app", "dependencies": { "express": "^4.18.0", "ui": "*" } },
+                "node_modules/express": { "version": "4.18.2" },
+                "node_modules/express/node_modules/debug": { "version": "2.6.9" },
+                "node_modules/@scope/pkg": { "version": "1.0.0" },
+                "node_modules/ui": { "resolved": "packages/ui", "link": true },
+                "packages/ui": { "name": "ui", "version": "0.1.0" }
+            },
+            "dependencies": {
+                "express": { "version": "0.0.0" }
+            }
+        }"#;
+        assert_eq!(
+            summary(parse_package_lock(lock, &declared(&["express", "ui"])).unwrap()),
+            expected(&[("express", "4.18.2", true), ("debug", "2.6.9", false), ("@scope/pkg", "1.0.0", false)])
+        );
+    }
+
+    #[test]
+    fn package_lock_v3_reads_aliased_names() {
+        let lock = r#"{
+            "name": "app",
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "app" },
+                "node_modules/react": { "version": "18.3.1" },
+                "node_modules/legacy-react": { "name": "react", "version": "17.0.2" }
+            }
+        }"#;
+        assert_eq!(
+            summary(parse_package_lock(lock, &declared(&["react", "legacy-react"])).unwrap()),
+            expected(&[("react", "18.3.1", true), ("react", "17.0.2", false)])
+        );
+        assert!(parse_package_lock("{", &HashSet::new()).is_err());
+    }
+
+    #[test]
+    fn yarn_classic_entries() {
+        let lock = r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@babel/code-frame@^7.0.0", "@babel/code-frame@^7.22.13":
+  version "7.22.13"
+  resolved "https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.22.13.tgz"
+  dependencies:
+    "@babel/highlight" "^7.22.13"
+
+lodash@^4.17.21:
+  version "4.17.21"
+  resolved "https://registry.yarnpkg.com/lodash/-/lodash-4.17.21.tgz"
+"#;
+        assert_eq!(
+            summary(parse_yarn_lock(lock, &declared(&["lodash"]))),
+            expected(&[("@babel/code-frame", "7.22.13", false), ("lodash", "4.17.21", true)])
+        );
+    }
+
+    #[test]
+    fn yarn_berry_entries_skip_workspaces() {
+        let lock = r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 8
+  cacheKey: 10c0
+
+"@scope/a@npm:^1.0.0, @scope/a@npm:^1.1.0":
+  version: 1.2.0
+  resolution: "@scope/a@npm:1.2.0"
+  checksum: 10c0/abc
+  languageName: node
+  linkType: hard
+
+"lodash@npm:^4.17.21":
+  version: 4.17.21
+  resolution: "lodash@npm:4.17.21"
+
+"my-app@workspace:.":
+  version: 0.0.0-use.local
+  resolution: "my-app@workspace:."
+"#;
+        assert_eq!(
+            summary(parse_yarn_lock(lock, &declared(&["@scope/a"]))),
+            expected(&[("@scope/a", "1.2.0", true), ("lodash", "4.17.21", false)])
+        );
+    }
+
+    #[test]
+    fn bun_lock_with_workspaces_and_nested_installs() {
+        let lock = r#"{
+  "lockfileVersion": 1,
+  "workspaces": {
+    "": {
+      "name": "app",
+      "dependencies": { "hono": "^4.4.0", },
+    },
+    "packages/ui": {
+      "name": "ui",
+      "devDependencies": { "react": "^18.3.0" },
+    },
+  },
+  // Trailing commas and comments are allowed
+  "packages": {
+    "hono": ["hono@4.4.0", "", {}, "sha512-a"],
+    "react": ["react@18.3.1", "", { "dependencies": { "loose-envify": "^1.1.0" } }, "sha512-b"],
+    "loose-envify": ["loose-envify@1.4.0", "", {}, "sha512-c"],
+    "ui": ["ui@workspace:packages/ui"],
+    "hono/@types/node": ["@types/node@20.14.0", "", {}, "sha512-d"],
+  }
+}"#;
+        assert_eq!(
+            summary(parse_bun_lock(lock).unwrap()),
+            expected(&[
+                ("hono", "4.4.0", true),
+                ("react", "18.3.1", true),
+                ("loose-envify", "1.4.0", false),
+                ("@types/node", "20.14.0", false),
+            ])
+        );
+    }
+
+    #[test]
+    fn cargo_lock_marks_what_local_crates_depend_on() {
+        let lock = r#"# This file is automatically @generated by Cargo.
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "serde",
+ "syn 2.0.48",
+ "util",
+]
+
+[[package]]
+name = "util"
+version = "0.1.0"
+
+[[package]]
+name = "serde"
+version = "1.0.195"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "serde_derive",
+]
+
+[[package]]
+name = "serde_derive"
+version = "1.0.195"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "syn 1.0.109",
+]
+
+[[package]]
+name = "syn"
+version = "1.0.109"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "syn"
+version = "2.0.48"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+        let deps = parse_cargo_lock(lock).unwrap();
+        assert!(deps.iter().all(|d| d.ecosystem == "cargo"));
+        assert_eq!(
+            summary(deps),
+            expected(&[
+                ("serde", "1.0.195", true),
+                ("serde_derive", "1.0.195", false),
+                ("syn", "1.0.109", false),
+                ("syn", "2.0.48", true),
+            ])
+        );
+    }
+
+    #[test]
+    fn inventory_merges_lockfiles_from_every_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("package.json"), r#"{ "dependencies": { "react": "^18" } }"#).unwrap();
+        fs::write(
+            root.join("package-lock.json"),
+            r#"{ "lockfileVersion": 3, "packages": {
+                "node_modules/react": { "version": "18.3.1" },
+                "node_modules/loose-envify": { "version": "1.4.0" } } }"#,
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("apps/docs")).unwrap();
+        fs::write(root.join("apps/docs/package.json"), r#"{ "dependencies": { "loose-envify": "1" } }"#).unwrap();
+        fs::write(
+            root.join("apps/docs/yarn.lock"),
+            "loose-envify@^1.1.0:\n  version \"1.4.0\"\n\nreact@^18:\n  version \"18.3.1\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("apps/docs/pnpm-lock.yaml"), "packages: [").unwrap();
+
+        let inventory = collect_inventory(root, ["apps/docs"].into_iter());
+        assert_eq!(inventory.lockfiles, ["package-lock.json", "apps/docs/yarn.lock"]);
+        assert_eq!(
+            summary(inventory.dependencies),
+            expected(&[("loose-envify", "1.4.0", true), ("react", "18.3.1", true)])
+        );
+    }
+}
@@ -0,0 +1,27 @@
+pub mod advisories;
+pub mod cargo;
+pub mod code_stats;
+pub mod deno;
+pub mod docker;
+pub mod ecosystems;
+pub mod env_contract;
+pub mod gates;
+pub mod git;
+pub mod go_mod;
+pub mod lockfiles;
+pub mod packages;
+pub mod python;
+pub mod rules;
+pub mod secrets;
+
+pub use advisories::{audit_dependencies, VulnerabilityAudit};
+pub use cargo::{parse_cargo_toml, CargoTomlInfo};
+pub use code_stats::{collect_code_stats, CodeStats};
+pub use ecosystems::{detect_ecosystems, EcosystemInfo};
+pub use env_contract::{check_env_contract, EnvContract};
+pub use gates::{evaluate_phases, GateContext, PhaseEvidence};
+pub use git::{inspect_git, GitInsight};
+pub use lockfiles::{collect_inventory, DependencyInventory, ResolvedDependency};
+pub use packages::{detect_packages, DetectedPackage};
+pub use rules::{apply_rules, Recommendation};
+pub use secrets::{scan_secrets, SecretScan};
@@ -0,0 +1,134 @@
+use crate::commands::walker::ProjectWalker;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Marker files that make a directory a package, and the ecosystem they imply
+const PACKAGE_MARKERS: &[(&str, &str)] = &[
+    ("package.json", "node"),
+    ("Cargo.toml", "rust"),
+    ("pyproject.toml", "python"),
+    ("setup.py", "python"),
+    ("requirements.txt", "python"),
+    ("go.mod", "go"),
+    ("deno.json", "deno"),
+    ("deno.jsonc", "deno"),
+];
+
+/// How deep below the root to look for nested packages without a workspace file
+const NESTED_SCAN_DEPTH: usize = 5;
+const MAX_PACKAGES: usize = 50;
+
+/// A package found below the project root
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedPackage {
+    /// Directory relative to the project root, with `/` separators
+    pub path: String,
+    pub ecosystems: Vec<String>,
+    /// What declared it: pnpm-workspace, npm-workspaces, cargo-workspace or nested
+    pub source: String,
+}
+
+#[derive(Deserialize)]
+struct PnpmWorkspace {
+    #[serde(default)]
+    packages: Vec<String>,
+}
+
+/// Workspace globs from pnpm-workspace.yaml or package.json `workspaces`
+fn node_workspace_globs(root: &Path) -> Option<(Vec<String>, &'static str)> {
+    if let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        if let Ok(workspace) = serde_yaml::from_str::<PnpmWorkspace>(&content) {
+            return Some((workspace.packages, "pnpm-workspace"));
+        }
+    }
+
+    let content = fs::read_to_string(root.join("package.json")).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    // Either `"workspaces": [...]` (npm, yarn) or `"workspaces": { "packages": [...] }` (yarn classic)
+    let globs = json["workspaces"]
+        .as_array()
+        .or_else(|| json["workspaces"]["packages"].as_array())?
+        .iter()
+        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+        .collect();
+    Some((globs, "npm-workspaces"))
+}
+
+/// Expand workspace globs (with `!pattern` exclusions) into directories holding `marker`
+fn expand_globs(root: &Path, globs: &[String], marker: &str) -> Vec<String> {
+    let (excludes, includes): (Vec<&String>, Vec<&String>) = globs.iter().partition(|g| g.starts_with('!'));
+    let excludes: Vec<glob::Pattern> = excludes
+        .iter()
+        .filter_map(|g| glob::Pattern::new(g.trim_start_matches('!').trim_end_matches('/')).ok())
+        .collect();
+
+    let mut dirs = Vec::new();
+    for include in includes {
+        let pattern = root.join(include.trim_end_matches('/')).to_string_lossy().to_string();
+        let Ok(paths) = glob::glob(&pattern) else {
+            continue;
+        };
+        for dir in paths.filter_map(|p| p.ok()) {
+            if !dir.join(marker).is_file() {
+                continue;
+            }
+            let relative = relative_dir(root, &dir);
+            if !relative.is_empty() && !excludes.iter().any(|e| e.matches(&relative)) {
+                dirs.push(relative);
+            }
+        }
+    }
+    dirs
+}
+
+fn relative_dir(root: &Path, dir: &Path) -> String {
+    dir.strip_prefix(root)
+        .unwrap_or(dir)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Find packages below the project root: declared pnpm/npm/yarn and Cargo
+/// workspace members first, then any other directory with a package marker
+/// (so apps nested a few levels deep without a workspace file still show up).
+pub fn detect_packages(root: &Path, walker: &ProjectWalker, cargo_members: &[String]) -> Vec<DetectedPackage> {
+    let mut found: BTreeMap<String, DetectedPackage> = BTreeMap::new();
+    let mut add = |path: String, ecosystem: &str, source: &str| {
+        let package = found.entry(path.clone()).or_insert_with(|| DetectedPackage {
+            path,
+            ecosystems: Vec::new(),
+            source: source.to_string(),
+        });
+        if !package.ecosystems.iter().any(|e| e == ecosystem) {
+            package.ecosystems.push(ecosystem.to_string());
+        }
+    };
+
+    if let Some((globs, source)) = node_workspace_globs(root) {
+        for dir in expand_globs(root, &globs, "package.json") {
+            add(dir, "node", source);
+        }
+    }
+
+    for member in cargo_members {
+        add(member.clone(), "rust", "cargo-workspace");
+    }
+
+    for entry in walker.clone().max_depth(NESTED_SCAN_DEPTH).files() {
+        let file_name = entry.file_name().to_string_lossy();
+        let Some((_, ecosystem)) = PACKAGE_MARKERS.iter().find(|(marker, _)| *marker == file_name) else {
+            continue;
+        };
+        let Some(dir) = entry.path().parent() else {
+            continue;
+        };
+        let relative = relative_dir(root, dir);
+        if !relative.is_empty() {
+            add(relative, ecosystem, "nested");
+        }
+    }
+
+    found.into_values().take(MAX_PACKAGES).collect()
+}
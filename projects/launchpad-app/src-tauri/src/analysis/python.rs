@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use toml::{Table, Value};
+
+/// Requirement files read besides pyproject.toml
+const REQUIREMENTS_FILES: &[&str] = &["requirements.txt", "requirements-dev.txt", "requirements/base.txt", "requirements/dev.txt"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PythonProjectInfo {
+    pub name: Option<String>,
+    pub requires_python: Option<String>,
+    /// Normalized distribution names (lowercase, `-` separators)
+    pub dependencies: Vec<String>,
+    /// Files the dependencies were read from
+    pub sources: Vec<String>,
+}
+
+/// PEP 503 normalization, so `Django`, `django_filters` and `django.filters` compare equal
+fn normalize(name: &str) -> String {
+    name.trim()
+        .to_ascii_lowercase()
+        .replace(['_', '.'], "-")
+}
+
+/// The distribution name of a PEP 508 requirement: `fastapi[all]>=0.100; python_version>"3.8"` -> `fastapi`
+fn requirement_name(spec: &str) -> Option<String> {
+    // Local paths like `.` or `./vendor/pkg` have no name
+    if !spec.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let end = spec
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(spec.len());
+    let name = &spec[..end];
+    (!name.is_empty()).then(|| normalize(name))
+}
+
+fn parse_requirements(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.split(" #").next().unwrap_or("").trim();
+            // Options (-r, -e, --index-url) and bare URLs aren't package names, but
+            // `name @ https://...` direct references are
+            let is_url = line.split_whitespace().next().is_some_and(|word| word.contains("://"));
+            if line.is_empty() || line.starts_with('#') || line.starts_with('-') || is_url {
+                return None;
+            }
+            requirement_name(line)
+        })
+        .collect()
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default()
+}
+
+/// Dependencies from PEP 621 `[project]`, PEP 735 `[dependency-groups]` and Poetry
+fn pyproject_dependencies(manifest: &Table) -> Vec<String> {
+    let mut specs = Vec::new();
+
+    if let Some(project) = manifest.get("project") {
+        specs.extend(string_list(project.get("dependencies")));
+        if let Some(optional) = project.get("optional-dependencies").and_then(|o| o.as_table()) {
+            for group in optional.values() {
+                specs.extend(string_list(Some(group)));
+            }
+        }
+    }
+    if let Some(groups) = manifest.get("dependency-groups").and_then(|g| g.as_table()) {
+        for group in groups.values() {
+            specs.extend(string_list(Some(group)));
+        }
+    }
+
+    let mut names: Vec<String> = specs.iter().filter_map(|s| requirement_name(s)).collect();
+
+    // Poetry lists names as table keys, with `python` for the interpreter
+    if let Some(poetry) = manifest.get("tool").and_then(|t| t.get("poetry")) {
+        let mut tables: Vec<&Table> = ["dependencies", "dev-dependencies"]
+            .iter()
+            .filter_map(|key| poetry.get(*key).and_then(|d| d.as_table()))
+            .collect();
+        if let Some(groups) = poetry.get("group").and_then(|g| g.as_table()) {
+            tables.extend(
+                groups
+                    .values()
+                    .filter_map(|g| g.get("dependencies").and_then(|d| d.as_table())),
+            );
+        }
+        for table in tables {
+            names.extend(table.keys().filter(|k| *k != "python").map(|k| normalize(k)));
+        }
+    }
+
+    names
+}
+
+/// Parse pyproject.toml and requirements files in `dir`; `None` when there are none
+pub fn parse_python_project(dir: &Path) -> Option<PythonProjectInfo> {
+    let mut info = PythonProjectInfo::default();
+    let mut found = false;
+
+    if let Ok(content) = fs::read_to_string(dir.join("pyproject.toml")) {
+        found = true;
+        match content.parse::<Table>() {
+            Ok(manifest) => {
+                let project = manifest
+                    .get("project")
+                    .or_else(|| manifest.get("tool").and_then(|t| t.get("poetry")));
+                info.name = project
+                    .and_then(|p| p.get("name"))
+                    .and_then(|n| n.as_str())
+                    .map(|s| s.to_string());
+                info.requires_python = manifest
+                    .get("project")
+                    .and_then(|p| p.get("requires-python"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+                info.dependencies.extend(pyproject_dependencies(&manifest));
+                info.sources.push("pyproject.toml".to_string());
+            }
+            Err(e) => log::warn!("Failed to parse pyproject.toml in {}: {}", dir.display(), e),
+        }
+    }
+
+    for file in REQUIREMENTS_FILES {
+        if let Ok(content) = fs::read_to_string(dir.join(file)) {
+            found = true;
+            info.dependencies.extend(parse_requirements(&content));
+            info.sources.push(file.to_string());
+        }
+    }
+
+    info.dependencies.sort();
+    info.dependencies.dedup();
+    found.then_some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirement_names_are_normalized() {
+        assert_eq!(requirement_name("fastapi[all]>=0.100; python_version>\"3.8\"").as_deref(), Some("fastapi"));
+        assert_eq!(requirement_name("Django_Filters==23.1").as_deref(), Some("django-filters"));
+        assert_eq!(requirement_name("zope.interface").as_deref(), Some("zope-interface"));
+        assert_eq!(requirement_name("requests @ https://example.com/requests.whl").as_deref(), Some("requests"));
+        assert_eq!(requirement_name("./vendor/pkg"), None);
+        assert_eq!(requirement_name(""), None);
+    }
+
+    #[test]
+    fn requirements_files_skip_options_and_urls() {
+        let content = "\
+# Runtime
+flask>=3  # web framework
+-r requirements-dev.txt
+--index-url https://pypi.example.com/simple
+-e .
+git+https://github.com/acme/tool.git#egg=tool
+pillow @ https://example.com/pillow.whl
+.
+";
+        assert_eq!(parse_requirements(content), ["flask", "pillow"]);
+    }
+
+    #[test]
+    fn pyproject_and_requirements_are_merged() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            r#"
+[project]
+name = "acme"
+requires-python = ">=3.10"
+dependencies = ["httpx>=0.27", "Pydantic"]
+
+[project.optional-dependencies]
+cli = ["typer"]
+
+[dependency-groups]
+dev = ["pytest"]
+
+[tool.poetry.dependencies]
+python = "^3.10"
+rich = "*"
+
+[tool.poetry.group.docs.dependencies]
+mkdocs = "*"
+"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("requirements.txt"), "httpx\nuvicorn\n").unwrap();
+
+        let info = parse_python_project(dir.path()).unwrap();
+        assert_eq!(info.name.as_deref(), Some("acme"));
+        assert_eq!(info.requires_python.as_deref(), Some(">=3.10"));
+        assert_eq!(info.dependencies, ["httpx", "mkdocs", "pydantic", "pytest", "rich", "typer", "uvicorn"]);
+        assert_eq!(info.sources, ["pyproject.toml", "requirements.txt"]);
+    }
+}
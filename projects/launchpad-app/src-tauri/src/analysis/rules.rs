@@ -0,0 +1,308 @@
+use crate::analysis::advisories::Severity;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A test on `ProjectAnalysis`. Fields are dotted paths into the serialized
+/// analysis; arrays are walked element by element and may be filtered, e.g.
+/// `secret_scan.findings[severity=critical].file`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RuleCondition {
+    /// Any value at the path is set: true, a non-empty string or list, non-zero
+    Present { field: String },
+    /// A string at the path contains the text
+    Contains { field: String, value: String },
+    /// A number at the path is at least the value
+    AtLeast { field: String, value: f64 },
+    All { conditions: Vec<RuleCondition> },
+    Any { conditions: Vec<RuleCondition> },
+    Not { condition: Box<RuleCondition> },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecommendationRule {
+    pub id: String,
+    pub condition: RuleCondition,
+    pub severity: Severity,
+    /// `{field}` placeholders are replaced with the values at that path
+    pub message: String,
+    /// The SOP that covers the recommendation
+    pub sop_number: Option<i32>,
+    /// Project types the rule applies to; empty for every type
+    pub project_types: Vec<String>,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Recommendation {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    pub sop_number: Option<i32>,
+}
+
+/// `findings[severity=critical]` -> (`findings`, Some((`severity`, `critical`))).
+/// `None` for a malformed filter, which matches nothing rather than everything.
+fn parse_segment(segment: &str) -> Option<(&str, Option<(&str, &str)>)> {
+    let Some((name, rest)) = segment.split_once('[') else {
+        return Some((segment, None));
+    };
+    let filter = rest.strip_suffix(']')?.split_once('=')?;
+    Some((name, Some(filter)))
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Every value at a path, with arrays flattened along the way
+fn resolve<'a>(root: &'a Value, path: &str) -> Vec<&'a Value> {
+    let mut current = vec![root];
+    for segment in path.split('.') {
+        let Some((name, filter)) = parse_segment(segment) else {
+            return Vec::new();
+        };
+        let matches = |value: &Value| match filter {
+            Some((key, expected)) => value.get(key).and_then(scalar_text).as_deref() == Some(expected),
+            None => true,
+        };
+
+        let mut next = Vec::new();
+        for value in current {
+            match value.get(name) {
+                Some(Value::Array(items)) => next.extend(items.iter().filter(|item| matches(item))),
+                Some(child) if matches(child) => next.push(child),
+                _ => {}
+            }
+        }
+        current = next;
+    }
+    current
+}
+
+fn is_set(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+pub fn evaluate(condition: &RuleCondition, analysis: &Value) -> bool {
+    match condition {
+        RuleCondition::Present { field } => resolve(analysis, field).into_iter().any(is_set),
+        RuleCondition::Contains { field, value } => resolve(analysis, field)
+            .into_iter()
+            .any(|v| v.as_str().is_some_and(|s| s.contains(value.as_str()))),
+        RuleCondition::AtLeast { field, value } => resolve(analysis, field)
+            .into_iter()
+            .any(|v| v.as_f64().is_some_and(|n| n >= *value)),
+        RuleCondition::All { conditions } => conditions.iter().all(|c| evaluate(c, analysis)),
+        RuleCondition::Any { conditions } => conditions.iter().any(|c| evaluate(c, analysis)),
+        RuleCondition::Not { condition } => !evaluate(condition, analysis),
+    }
+}
+
+/// Fill `{field}` placeholders with the distinct values found at each path
+fn render_message(message: &str, analysis: &Value) -> String {
+    let Ok(placeholder) = Regex::new(r"\{([A-Za-z0-9_.\[\]=\-]+)\}") else {
+        return message.to_string();
+    };
+    placeholder
+        .replace_all(message, |caps: &regex::Captures| {
+            let mut values: Vec<String> = Vec::new();
+            for text in resolve(analysis, &caps[1]).into_iter().filter_map(scalar_text) {
+                if !values.contains(&text) {
+                    values.push(text);
+                }
+            }
+            values.join(", ")
+        })
+        .to_string()
+}
+
+/// Recommendations from every enabled rule that applies to the project type,
+/// isn't silenced and whose condition holds, most severe first
+pub fn apply_rules(
+    rules: &[RecommendationRule],
+    analysis: &Value,
+    project_type: &str,
+    silenced: &[String],
+) -> Vec<Recommendation> {
+    let mut recommendations: Vec<Recommendation> = rules
+        .iter()
+        .filter(|rule| rule.enabled && !silenced.contains(&rule.id))
+        .filter(|rule| rule.project_types.is_empty() || rule.project_types.iter().any(|t| t == project_type))
+        .filter(|rule| evaluate(&rule.condition, analysis))
+        .map(|rule| Recommendation {
+            rule_id: rule.id.clone(),
+            severity: rule.severity,
+            message: render_message(&rule.message, analysis),
+            sop_number: rule.sop_number,
+        })
+        .collect();
+
+    recommendations.sort_by_key(|r| std::cmp::Reverse(r.severity));
+    recommendations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn analysis() -> Value {
+        json!({
+            "project_type": "web",
+            "has_tests": false,
+            "file_count": 42,
+            "tech_stack": ["Node.js", "TypeScript"],
+            "git": null,
+            "env_contract": { "undocumented": [], "example_files": [".env.example"] },
+            "secret_scan": {
+                "findings": [
+                    { "severity": "critical", "file": "src/config.ts", "line": 3 },
+                    { "severity": "critical", "file": "src/config.ts", "line": 9 },
+                    { "severity": "medium", "file": "scripts/seed.ts", "line": 1 },
+                ],
+            },
+        })
+    }
+
+    fn present(field: &str) -> RuleCondition {
+        RuleCondition::Present { field: field.to_string() }
+    }
+
+    fn rule(id: &str, condition: RuleCondition, severity: Severity, message: &str) -> RecommendationRule {
+        RecommendationRule {
+            id: id.to_string(),
+            condition,
+            severity,
+            message: message.to_string(),
+            sop_number: None,
+            project_types: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn paths_walk_objects_and_flatten_arrays() {
+        let analysis = analysis();
+        assert_eq!(resolve(&analysis, "file_count"), [&json!(42)]);
+        assert_eq!(resolve(&analysis, "tech_stack"), [&json!("Node.js"), &json!("TypeScript")]);
+        assert_eq!(resolve(&analysis, "secret_scan.findings.line"), [&json!(3), &json!(9), &json!(1)]);
+        assert_eq!(
+            resolve(&analysis, "secret_scan.findings[severity=critical].line"),
+            [&json!(3), &json!(9)]
+        );
+        assert_eq!(resolve(&analysis, "secret_scan.findings[line=1].file"), [&json!("scripts/seed.ts")]);
+        assert!(resolve(&analysis, "secret_scan.findings[severity=low]").is_empty());
+    }
+
+    #[test]
+    fn missing_paths_resolve_to_nothing() {
+        let analysis = analysis();
+        assert!(resolve(&analysis, "no_such_field").is_empty());
+        assert!(resolve(&analysis, "git.branch").is_empty());
+        assert!(resolve(&analysis, "file_count.nested").is_empty());
+        assert!(resolve(&analysis, "secret_scan.findings.missing").is_empty());
+        // A malformed filter matches nothing rather than everything
+        assert!(resolve(&analysis, "secret_scan.findings[severity].file").is_empty());
+        assert!(resolve(&analysis, "secret_scan.findings[severity=critical.file").is_empty());
+    }
+
+    #[test]
+    fn conditions_evaluate_against_the_analysis() {
+        let analysis = analysis();
+        assert!(evaluate(&present("secret_scan.findings[severity=critical]"), &analysis));
+        assert!(evaluate(&present("env_contract.example_files"), &analysis));
+        assert!(!evaluate(&present("env_contract.undocumented"), &analysis));
+        assert!(!evaluate(&present("has_tests"), &analysis));
+        assert!(!evaluate(&present("git"), &analysis));
+        assert!(!evaluate(&present("missing"), &analysis));
+
+        let contains = |field: &str, value: &str| RuleCondition::Contains {
+            field: field.to_string(),
+            value: value.to_string(),
+        };
+        assert!(evaluate(&contains("tech_stack", "Script"), &analysis));
+        assert!(!evaluate(&contains("tech_stack", "Rust"), &analysis));
+        assert!(!evaluate(&contains("file_count", "4"), &analysis));
+
+        let at_least = |field: &str, value: f64| RuleCondition::AtLeast {
+            field: field.to_string(),
+            value,
+        };
+        assert!(evaluate(&at_least("file_count", 42.0), &analysis));
+        assert!(!evaluate(&at_least("file_count", 42.5), &analysis));
+        assert!(evaluate(&at_least("secret_scan.findings.line", 9.0), &analysis));
+        assert!(!evaluate(&at_least("missing", 0.0), &analysis));
+
+        let all = RuleCondition::All {
+            conditions: vec![present("file_count"), RuleCondition::Not { condition: Box::new(present("has_tests")) }],
+        };
+        assert!(evaluate(&all, &analysis));
+        let any = RuleCondition::Any {
+            conditions: vec![present("has_tests"), present("missing")],
+        };
+        assert!(!evaluate(&any, &analysis));
+        assert!(evaluate(&RuleCondition::All { conditions: Vec::new() }, &analysis));
+        assert!(!evaluate(&RuleCondition::Any { conditions: Vec::new() }, &analysis));
+    }
+
+    #[test]
+    fn conditions_round_trip_through_json() {
+        let stored = r#"{"op":"not","condition":{"op":"present","field":"has_tests"}}"#;
+        let condition: RuleCondition = serde_json::from_str(stored).unwrap();
+        assert_eq!(condition, RuleCondition::Not { condition: Box::new(present("has_tests")) });
+        assert_eq!(serde_json::to_string(&condition).unwrap(), stored);
+    }
+
+    #[test]
+    fn messages_fill_placeholders_with_distinct_values() {
+        let analysis = analysis();
+        assert_eq!(
+            render_message("Secrets in {secret_scan.findings[severity=critical].file}", &analysis),
+            "Secrets in src/config.ts"
+        );
+        assert_eq!(render_message("{secret_scan.findings.file}", &analysis), "src/config.ts, scripts/seed.ts");
+        assert_eq!(render_message("{file_count} files, {has_tests}", &analysis), "42 files, false");
+        assert_eq!(render_message("Missing: [{no_such_field}]", &analysis), "Missing: []");
+        assert_eq!(render_message("No placeholders {}", &analysis), "No placeholders {}");
+    }
+
+    #[test]
+    fn apply_rules_filters_and_orders_by_severity() {
+        let analysis = analysis();
+        let mut desktop_only = rule("desktop", present("file_count"), Severity::High, "desktop");
+        desktop_only.project_types = vec!["desktop".to_string()];
+        let mut disabled = rule("disabled", present("file_count"), Severity::Critical, "disabled");
+        disabled.enabled = false;
+        let rules = [
+            rule("tests", RuleCondition::Not { condition: Box::new(present("has_tests")) }, Severity::Medium, "Add tests"),
+            rule("secrets", present("secret_scan.findings[severity=critical]"), Severity::Critical, "{secret_scan.findings[severity=critical].file}"),
+            rule("silenced", present("file_count"), Severity::High, "silenced"),
+            rule("not-matching", present("has_tests"), Severity::High, "never"),
+            desktop_only,
+            disabled,
+        ];
+
+        let recommendations = apply_rules(&rules, &analysis, "web", &["silenced".to_string()]);
+        let ids: Vec<&str> = recommendations.iter().map(|r| r.rule_id.as_str()).collect();
+        assert_eq!(ids, ["secrets", "tests"]);
+        assert_eq!(recommendations[0].message, "src/config.ts");
+
+        let ids: Vec<String> = apply_rules(&rules, &analysis, "desktop", &[]).into_iter().map(|r| r.rule_id).collect();
+        // Equal severities keep the rules' order
+        assert_eq!(ids, ["secrets", "silenced", "desktop", "tests"]);
+    }
+}
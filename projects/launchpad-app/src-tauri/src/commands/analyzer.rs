@@ -1,413 +1,592 @@
-use crate::db::get_db;
-use chrono::Utc;
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::Path;
-use tauri::AppHandle;
-use walkdir::WalkDir;
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ProjectAnalysis {
-    pub project_path: String,
-    pub tech_stack: Vec<String>,
-    pub frameworks: Vec<String>,
-    pub has_git: bool,
-    pub has_tests: bool,
-    pub has_ci: bool,
-    pub has_env_example: bool,
-    pub file_count: usize,
-    pub directory_structure: Vec<String>,
-    pub detected_services: Vec<String>,
-    pub package_json: Option<PackageJsonInfo>,
-    pub cargo_toml: Option<CargoTomlInfo>,
-    pub recommendations: Vec<String>,
-    pub sop_progress: SopProgress,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct PackageJsonInfo {
-    pub name: Option<String>,
-    pub dependencies: Vec<String>,
-    pub dev_dependencies: Vec<String>,
-    pub scripts: Vec<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CargoTomlInfo {
-    pub name: Option<String>,
-    pub dependencies: Vec<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SopProgress {
-    pub estimated_phase: i32,
-    pub phase_name: String,
-    pub evidence: Vec<String>,
-}
-
-fn detect_tech_stack(path: &Path) -> (Vec<String>, Vec<String>) {
-    let mut tech = Vec::new();
-    let mut frameworks = Vec::new();
-
-    // Check for common files
-    if path.join("package.json").exists() {
-        tech.push("Node.js".to_string());
-    }
-    if path.join("Cargo.toml").exists() {
-        tech.push("Rust".to_string());
-    }
-    if path.join("requirements.txt").exists() || path.join("pyproject.toml").exists() {
-        tech.push("Python".to_string());
-    }
-    if path.join("go.mod").exists() {
-        tech.push("Go".to_string());
-    }
-
-    // Check for frameworks
-    if path.join("next.config.js").exists()
-        || path.join("next.config.ts").exists()
-        || path.join("next.config.mjs").exists()
-    {
-        frameworks.push("Next.js".to_string());
-    }
-    if path.join("src-tauri").exists() {
-        frameworks.push("Tauri".to_string());
-    }
-    if path.join("tailwind.config.js").exists()
-        || path.join("tailwind.config.ts").exists()
-        || path.join("postcss.config.mjs").exists()
-    {
-        frameworks.push("Tailwind CSS".to_string());
-    }
-    if path.join("drizzle.config.ts").exists() {
-        frameworks.push("Drizzle ORM".to_string());
-    }
-    if path.join("prisma").exists() {
-        frameworks.push("Prisma".to_string());
-    }
-
-    (tech, frameworks)
-}
-
-fn detect_services(path: &Path) -> Vec<String> {
-    let mut services = Vec::new();
-
-    // Check for common service integrations
-    if let Ok(content) = fs::read_to_string(path.join("package.json")) {
-        if content.contains("@clerk") {
-            services.push("Clerk (Auth)".to_string());
-        }
-        if content.contains("stripe") {
-            services.push("Stripe (Payments)".to_string());
-        }
-        if content.contains("@sentry") {
-            services.push("Sentry (Error Tracking)".to_string());
-        }
-        if content.contains("@upstash") {
-            services.push("Upstash".to_string());
-        }
-        if content.contains("@neondatabase") || content.contains("neon") {
-            services.push("Neon (PostgreSQL)".to_string());
-        }
-        if content.contains("@vercel") {
-            services.push("Vercel".to_string());
-        }
-    }
-
-    // Check env files for service hints
-    for env_file in &[".env", ".env.example", ".env.local"] {
-        if let Ok(content) = fs::read_to_string(path.join(env_file)) {
-            if content.contains("CLERK") && !services.contains(&"Clerk (Auth)".to_string()) {
-                services.push("Clerk (Auth)".to_string());
-            }
-            if content.contains("STRIPE") && !services.contains(&"Stripe (Payments)".to_string()) {
-                services.push("Stripe (Payments)".to_string());
-            }
-            if content.contains("DATABASE_URL")
-                && !services.iter().any(|s| s.contains("PostgreSQL"))
-            {
-                services.push("PostgreSQL".to_string());
-            }
-            if content.contains("ANTHROPIC") {
-                services.push("Anthropic (AI)".to_string());
-            }
-            if content.contains("OPENAI") {
-                services.push("OpenAI".to_string());
-            }
-        }
-    }
-
-    services
-}
-
-fn estimate_sop_phase(analysis: &ProjectAnalysis) -> SopProgress {
-    let mut evidence = Vec::new();
-    let mut phase = 0;
-
-    // SOP 05-06: Project Setup & Infrastructure
-    if !analysis.tech_stack.is_empty() {
-        phase = 5;
-        evidence.push("Project scaffolded with tech stack".to_string());
-    }
-
-    if !analysis.detected_services.is_empty() {
-        phase = 6;
-        evidence.push(format!(
-            "Services configured: {}",
-            analysis.detected_services.join(", ")
-        ));
-    }
-
-    // SOP 07: Development
-    if analysis.file_count > 20 {
-        phase = 7;
-        evidence.push(format!("{} files in project", analysis.file_count));
-    }
-
-    // SOP 08: Testing
-    if analysis.has_tests {
-        phase = 8;
-        evidence.push("Test files detected".to_string());
-    }
-
-    // SOP 09-10: Pre-ship / Launch
-    if analysis.has_ci && analysis.has_env_example {
-        phase = 9;
-        evidence.push("CI/CD and env documentation present".to_string());
-    }
-
-    let phase_names = [
-        "Idea Intake",
-        "Quick Validation",
-        "MVP Scope Lock",
-        "Revenue Model Lock",
-        "Design Brief",
-        "Project Setup",
-        "Infrastructure",
-        "Development",
-        "Testing & QA",
-        "Pre-Ship Checklist",
-        "Launch Day",
-        "Post-Launch Monitoring",
-        "Marketing Activation",
-    ];
-
-    SopProgress {
-        estimated_phase: phase,
-        phase_name: phase_names
-            .get(phase as usize)
-            .unwrap_or(&"Unknown")
-            .to_string(),
-        evidence,
-    }
-}
-
-fn generate_recommendations(analysis: &ProjectAnalysis) -> Vec<String> {
-    let mut recs = Vec::new();
-
-    if !analysis.has_env_example {
-        recs.push("Add .env.example file to document required environment variables".to_string());
-    }
-
-    if !analysis.has_tests {
-        recs.push("Add tests to ensure code quality before shipping".to_string());
-    }
-
-    if !analysis.has_ci {
-        recs.push(
-            "Set up CI/CD pipeline (GitHub Actions, Vercel) for automated deployments".to_string(),
-        );
-    }
-
-    if !analysis.detected_services.iter().any(|s| s.contains("Auth")) {
-        recs.push("Consider adding authentication (Clerk recommended)".to_string());
-    }
-
-    if !analysis
-        .detected_services
-        .iter()
-        .any(|s| s.contains("Payments"))
-    {
-        recs.push("Set up payment processing (Stripe) for monetization".to_string());
-    }
-
-    if !analysis
-        .detected_services
-        .iter()
-        .any(|s| s.contains("Error"))
-    {
-        recs.push("Add error tracking (Sentry) for production monitoring".to_string());
-    }
-
-    recs
-}
-
-#[tauri::command]
-pub fn analyze_project(app_handle: AppHandle, path: String) -> Result<ProjectAnalysis, String> {
-    let project_path = Path::new(&path);
-
-    if !project_path.exists() {
-        return Err(format!("Path does not exist: {}", path));
-    }
-
-    if !project_path.is_dir() {
-        return Err(format!("Path is not a directory: {}", path));
-    }
-
-    let (tech_stack, frameworks) = detect_tech_stack(project_path);
-    let detected_services = detect_services(project_path);
-
-    let has_git = project_path.join(".git").exists();
-    let has_env_example = project_path.join(".env.example").exists();
-
-    // Check for tests
-    let has_tests = project_path.join("tests").exists()
-        || project_path.join("__tests__").exists()
-        || project_path.join("test").exists()
-        || WalkDir::new(project_path)
-            .max_depth(3)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .any(|e| {
-                let name = e.file_name().to_string_lossy();
-                name.ends_with(".test.ts")
-                    || name.ends_with(".test.tsx")
-                    || name.ends_with(".spec.ts")
-                    || name.ends_with("_test.rs")
-            });
-
-    // Check for CI
-    let has_ci = project_path.join(".github/workflows").exists()
-        || project_path.join("vercel.json").exists()
-        || project_path.join(".gitlab-ci.yml").exists();
-
-    // Count files (excluding node_modules, .git, target)
-    let file_count = WalkDir::new(project_path)
-        .into_iter()
-        .filter_entry(|e| {
-            let name = e.file_name().to_string_lossy();
-            !name.starts_with("node_modules")
-                && !name.starts_with(".git")
-                && !name.starts_with("target")
-                && !name.starts_with(".next")
-                && !name.starts_with("out")
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .count();
-
-    // Get top-level directory structure
-    let directory_structure: Vec<String> = fs::read_dir(project_path)
-        .map(|entries| {
-            entries
-                .filter_map(|e| e.ok())
-                .map(|e| {
-                    let name = e.file_name().to_string_lossy().to_string();
-                    if e.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-                        format!("{}/", name)
-                    } else {
-                        name
-                    }
-                })
-                .filter(|n| !n.starts_with(".git") && n != "node_modules/")
-                .collect()
-        })
-        .unwrap_or_default();
-
-    // Parse package.json if exists
-    let package_json = if let Ok(content) = fs::read_to_string(project_path.join("package.json")) {
-        serde_json::from_str::<serde_json::Value>(&content)
-            .ok()
-            .map(|json| PackageJsonInfo {
-                name: json["name"].as_str().map(|s| s.to_string()),
-                dependencies: json["dependencies"]
-                    .as_object()
-                    .map(|o| o.keys().cloned().collect())
-                    .unwrap_or_default(),
-                dev_dependencies: json["devDependencies"]
-                    .as_object()
-                    .map(|o| o.keys().cloned().collect())
-                    .unwrap_or_default(),
-                scripts: json["scripts"]
-                    .as_object()
-                    .map(|o| o.keys().cloned().collect())
-                    .unwrap_or_default(),
-            })
-    } else {
-        None
-    };
-
-    // Parse Cargo.toml if exists
-    let cargo_toml = if let Ok(content) = fs::read_to_string(project_path.join("Cargo.toml")) {
-        // Simple parsing - just extract name and dependencies
-        let name = content
-            .lines()
-            .find(|l| l.starts_with("name"))
-            .and_then(|l| l.split('=').nth(1))
-            .map(|s| s.trim().trim_matches('"').to_string());
-
-        let deps: Vec<String> = content
-            .lines()
-            .skip_while(|l| !l.contains("[dependencies]"))
-            .skip(1)
-            .take_while(|l| !l.starts_with('['))
-            .filter(|l| l.contains('='))
-            .map(|l| l.split('=').next().unwrap_or("").trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-
-        Some(CargoTomlInfo {
-            name,
-            dependencies: deps,
-        })
-    } else {
-        None
-    };
-
-    let mut analysis = ProjectAnalysis {
-        project_path: path.clone(),
-        tech_stack,
-        frameworks,
-        has_git,
-        has_tests,
-        has_ci,
-        has_env_example,
-        file_count,
-        directory_structure,
-        detected_services,
-        package_json,
-        cargo_toml,
-        recommendations: Vec::new(),
-        sop_progress: SopProgress {
-            estimated_phase: 0,
-            phase_name: "Unknown".to_string(),
-            evidence: Vec::new(),
-        },
-    };
-
-    analysis.sop_progress = estimate_sop_phase(&analysis);
-    analysis.recommendations = generate_recommendations(&analysis);
-
-    Ok(analysis)
-}
-
-#[tauri::command]
-pub fn save_project_analysis(
-    app_handle: AppHandle,
-    project_id: String,
-    analysis: ProjectAnalysis,
-) -> Result<(), String> {
-    let db = get_db(&app_handle);
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-    let status_report = serde_json::to_string(&analysis).map_err(|e| e.to_string())?;
-    let last_analyzed = Utc::now().to_rfc3339();
-
-    conn.execute(
-        "UPDATE projects SET status_report = ?1, last_analyzed = ?2, current_phase = ?3 WHERE id = ?4",
-        (&status_report, &last_analyzed, analysis.sop_progress.estimated_phase, &project_id),
-    )
-    .map_err(|e| e.to_string())?;
-
-    Ok(())
-}
+use crate::analysis::gates::{declared_dependencies, is_test_entry};
+use crate::analysis::{
+    apply_rules, audit_dependencies, check_env_contract, collect_code_stats, collect_inventory, detect_ecosystems,
+    detect_packages, evaluate_phases, inspect_git, parse_cargo_toml, scan_secrets, CargoTomlInfo, CodeStats,
+    DependencyInventory, DetectedPackage, EcosystemInfo, EnvContract, GateContext, GitInsight, PhaseEvidence,
+    Recommendation, SecretScan, VulnerabilityAudit,
+};
+use crate::commands::command_runner::last_test_run;
+use crate::commands::history::record_analysis;
+use crate::commands::inventory::save_inventory;
+use crate::commands::projects::set_roadmap_block;
+use crate::commands::recommendations::{load_recommendation_rules, silenced_rules};
+use crate::commands::sops::load_phase_gates;
+use crate::commands::walker::ProjectWalker;
+use crate::db::get_db;
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Settings key holding a JSON array of local advisory snapshot directories
+/// (OSV JSON exports or a clone of the RustSec advisory-db)
+pub const ADVISORY_DB_SETTING: &str = "advisory_db_paths";
+
+/// Roadmap item that critical secret findings block
+const PRE_SHIP_SOP: i32 = 9;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectAnalysis {
+    pub project_path: String,
+    pub tech_stack: Vec<String>,
+    pub frameworks: Vec<String>,
+    /// web, api, desktop, cli, library or unknown; recommendation rules are scoped by it
+    #[serde(default)]
+    pub project_type: String,
+    pub has_git: bool,
+    /// Branch, working tree and history of the local repository
+    #[serde(default)]
+    pub git: Option<GitInsight>,
+    pub has_tests: bool,
+    pub has_ci: bool,
+    /// Env variables read by the code vs. those documented in `.env.example`
+    pub env_contract: EnvContract,
+    pub file_count: usize,
+    /// Per-language line counts, excluding generated and vendored files
+    #[serde(default)]
+    pub code_stats: CodeStats,
+    pub directory_structure: Vec<String>,
+    pub detected_services: Vec<String>,
+    pub package_json: Option<PackageJsonInfo>,
+    pub cargo_toml: Option<CargoTomlInfo>,
+    /// Python, Go, Deno, Bun and Docker manifests at the project root
+    #[serde(default)]
+    pub manifests: EcosystemInfo,
+    /// Nested packages (workspace members, apps in a monorepo); their tech
+    /// stack, services and tests are rolled up into the fields above
+    #[serde(default)]
+    pub packages: Vec<PackageAnalysis>,
+    /// Resolved versions from every lockfile in the project
+    #[serde(default)]
+    pub dependency_inventory: DependencyInventory,
+    /// Advisory matches for the inventory; `None` when no snapshot is configured
+    #[serde(default)]
+    pub vulnerability_audit: Option<VulnerabilityAudit>,
+    /// Credentials found in project files, masked
+    #[serde(default)]
+    pub secret_scan: SecretScan,
+    /// From the stored recommendation rules, minus those silenced for the project
+    pub recommendations: Vec<Recommendation>,
+    pub sop_progress: SopProgress,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackageAnalysis {
+    /// Directory relative to the project root
+    pub path: String,
+    pub name: Option<String>,
+    pub ecosystems: Vec<String>,
+    /// pnpm-workspace, npm-workspaces, cargo-workspace or nested
+    pub source: String,
+    pub tech_stack: Vec<String>,
+    pub frameworks: Vec<String>,
+    pub detected_services: Vec<String>,
+    pub has_tests: bool,
+    pub package_json: Option<PackageJsonInfo>,
+    pub cargo_toml: Option<CargoTomlInfo>,
+    #[serde(default)]
+    pub manifests: EcosystemInfo,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackageJsonInfo {
+    pub name: Option<String>,
+    pub dependencies: Vec<String>,
+    pub dev_dependencies: Vec<String>,
+    pub scripts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SopProgress {
+    /// Highest SOP whose gates, and those of every gated SOP before it, pass
+    pub estimated_phase: i32,
+    pub phase_name: String,
+    /// What the passing gates up to the estimated phase found
+    pub evidence: Vec<String>,
+    /// Gate results for every SOP, including the ones not yet reached
+    #[serde(default)]
+    pub phases: Vec<PhaseEvidence>,
+}
+
+fn detect_tech_stack(path: &Path, manifests: &EcosystemInfo) -> (Vec<String>, Vec<String>) {
+    let mut tech = Vec::new();
+    let mut frameworks = Vec::new();
+
+    // Check for common files
+    if path.join("package.json").exists() {
+        tech.push("Node.js".to_string());
+    }
+    if path.join("Cargo.toml").exists() {
+        tech.push("Rust".to_string());
+    }
+    extend_unique(&mut tech, &manifests.tech_stack());
+    // setup.py-only projects have nothing for the Python parser to read
+    if manifests.python.is_none() && path.join("setup.py").exists() {
+        tech.push("Python".to_string());
+    }
+
+    // Check for frameworks
+    if path.join("next.config.js").exists()
+        || path.join("next.config.ts").exists()
+        || path.join("next.config.mjs").exists()
+    {
+        frameworks.push("Next.js".to_string());
+    }
+    if path.join("src-tauri").exists() {
+        frameworks.push("Tauri".to_string());
+    }
+    if path.join("tailwind.config.js").exists()
+        || path.join("tailwind.config.ts").exists()
+        || path.join("postcss.config.mjs").exists()
+    {
+        frameworks.push("Tailwind CSS".to_string());
+    }
+    if path.join("drizzle.config.ts").exists() {
+        frameworks.push("Drizzle ORM".to_string());
+    }
+    if path.join("prisma").exists() {
+        frameworks.push("Prisma".to_string());
+    }
+    extend_unique(&mut frameworks, &manifests.frameworks());
+
+    (tech, frameworks)
+}
+
+fn detect_services(path: &Path, manifests: &EcosystemInfo) -> Vec<String> {
+    let mut services = Vec::new();
+
+    // Check for common service integrations
+    if let Ok(content) = fs::read_to_string(path.join("package.json")) {
+        if content.contains("@clerk") {
+            services.push("Clerk (Auth)".to_string());
+        }
+        if content.contains("stripe") {
+            services.push("Stripe (Payments)".to_string());
+        }
+        if content.contains("@sentry") {
+            services.push("Sentry (Error Tracking)".to_string());
+        }
+        if content.contains("@upstash") {
+            services.push("Upstash".to_string());
+        }
+        if content.contains("@neondatabase") || content.contains("neon") {
+            services.push("Neon (PostgreSQL)".to_string());
+        }
+        if content.contains("@vercel") {
+            services.push("Vercel".to_string());
+        }
+    }
+
+    // Check env files for service hints
+    for env_file in &[".env", ".env.example", ".env.local"] {
+        if let Ok(content) = fs::read_to_string(path.join(env_file)) {
+            if content.contains("CLERK") && !services.contains(&"Clerk (Auth)".to_string()) {
+                services.push("Clerk (Auth)".to_string());
+            }
+            if content.contains("STRIPE") && !services.contains(&"Stripe (Payments)".to_string()) {
+                services.push("Stripe (Payments)".to_string());
+            }
+            if content.contains("DATABASE_URL")
+                && !services.iter().any(|s| s.contains("PostgreSQL"))
+            {
+                services.push("PostgreSQL".to_string());
+            }
+            if content.contains("ANTHROPIC") {
+                services.push("Anthropic (AI)".to_string());
+            }
+            if content.contains("OPENAI") {
+                services.push("OpenAI".to_string());
+            }
+        }
+    }
+
+    // Dependencies and compose images; a hosted Postgres like Neon already covers PostgreSQL
+    for service in manifests.services() {
+        let covered = services.contains(&service)
+            || (service == "PostgreSQL" && services.iter().any(|s| s.contains("PostgreSQL")));
+        if !covered {
+            services.push(service);
+        }
+    }
+
+    services
+}
+
+fn detect_tests(path: &Path, walker: &ProjectWalker) -> bool {
+    path.join("tests").exists()
+        || path.join("__tests__").exists()
+        || path.join("test").exists()
+        || walker
+            .clone()
+            .max_depth(3)
+            .files()
+            .any(|e| is_test_entry(walker, &e))
+}
+
+fn parse_package_json(path: &Path) -> Option<PackageJsonInfo> {
+    let content = fs::read_to_string(path.join("package.json")).ok()?;
+    serde_json::from_str::<serde_json::Value>(&content)
+        .ok()
+        .map(|json| PackageJsonInfo {
+            name: json["name"].as_str().map(|s| s.to_string()),
+            dependencies: json["dependencies"]
+                .as_object()
+                .map(|o| o.keys().cloned().collect())
+                .unwrap_or_default(),
+            dev_dependencies: json["devDependencies"]
+                .as_object()
+                .map(|o| o.keys().cloned().collect())
+                .unwrap_or_default(),
+            scripts: json["scripts"]
+                .as_object()
+                .map(|o| o.keys().cloned().collect())
+                .unwrap_or_default(),
+        })
+}
+
+fn parse_cargo(path: &Path) -> Option<CargoTomlInfo> {
+    if !path.join("Cargo.toml").exists() {
+        return None;
+    }
+    match parse_cargo_toml(path) {
+        Ok(info) => Some(info),
+        Err(e) => {
+            log::warn!("Failed to parse Cargo.toml in {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn analyze_package(root: &Path, walker: &ProjectWalker, package: DetectedPackage) -> PackageAnalysis {
+    let path = root.join(&package.path);
+    let manifests = detect_ecosystems(&path);
+    let (tech_stack, frameworks) = detect_tech_stack(&path, &manifests);
+    let package_json = parse_package_json(&path);
+    let cargo_toml = parse_cargo(&path);
+    let name = package_json
+        .as_ref()
+        .and_then(|p| p.name.clone())
+        .or_else(|| cargo_toml.as_ref().and_then(|c| c.name.clone()))
+        .or_else(|| manifests.python.as_ref().and_then(|p| p.name.clone()))
+        .or_else(|| manifests.deno.as_ref().and_then(|d| d.name.clone()))
+        .or_else(|| manifests.go.as_ref().and_then(|g| g.module.clone()));
+
+    PackageAnalysis {
+        name,
+        ecosystems: package.ecosystems,
+        source: package.source,
+        tech_stack,
+        frameworks,
+        detected_services: detect_services(&path, &manifests),
+        has_tests: detect_tests(&path, &walker.scoped(&path)),
+        package_json,
+        cargo_toml,
+        manifests,
+        path: package.path,
+    }
+}
+
+fn load_advisory_sources(app_handle: &AppHandle) -> Vec<String> {
+    let db = get_db(app_handle);
+    let Ok(conn) = db.conn.lock() else {
+        return Vec::new();
+    };
+
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [ADVISORY_DB_SETTING],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+    .and_then(|value| serde_json::from_str(&value).ok())
+    .unwrap_or_default()
+}
+
+fn project_github_url(conn: &Connection, project_id: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row("SELECT github_url FROM projects WHERE id = ?1", [project_id], |row| row.get(0))
+        .optional()
+        .map(|url| url.flatten())
+}
+
+fn extend_unique(target: &mut Vec<String>, items: &[String]) {
+    for item in items {
+        if !target.contains(item) {
+            target.push(item.clone());
+        }
+    }
+}
+
+fn estimate_sop_phase(phases: Vec<PhaseEvidence>) -> SopProgress {
+    let mut evidence = Vec::new();
+    let mut phase = 0;
+
+    // SOPs without gates (ideation, design) can't be checked and don't stop the walk
+    for sop in &phases {
+        match sop.passed {
+            Some(true) => {
+                phase = sop.sop_number;
+                evidence.extend(sop.gates.iter().map(|g| g.detail.clone()));
+            }
+            Some(false) => break,
+            None => {}
+        }
+    }
+
+    SopProgress {
+        estimated_phase: phase,
+        phase_name: phases
+            .iter()
+            .find(|sop| sop.sop_number == phase)
+            .map(|sop| sop.name.clone())
+            .unwrap_or_else(|| "Unknown".to_string()),
+        evidence,
+        phases,
+    }
+}
+
+/// Frameworks and dependencies that identify the kind of project, checked in order
+const PROJECT_TYPE_MARKERS: &[(&str, &[&str])] = &[
+    ("desktop", &["Tauri", "electron", "tauri"]),
+    ("web", &["Next.js", "next", "react", "vue", "svelte", "@sveltejs/kit", "astro", "nuxt", "solid-js", "vite"]),
+    ("api", &["express", "fastify", "hono", "koa", "@nestjs/core", "axum", "actix-web", "rocket", "warp"]),
+    ("cli", &["clap", "commander", "yargs", "structopt"]),
+];
+
+fn detect_project_type(analysis: &ProjectAnalysis) -> String {
+    let dependencies = declared_dependencies(analysis);
+    let has_marker = |marker: &&str| analysis.frameworks.iter().any(|f| f == marker) || dependencies.contains(*marker);
+
+    for (project_type, markers) in PROJECT_TYPE_MARKERS {
+        if markers.iter().any(has_marker) {
+            return project_type.to_string();
+        }
+    }
+    if analysis.tech_stack.is_empty() {
+        "unknown".to_string()
+    } else {
+        "library".to_string()
+    }
+}
+
+#[tauri::command]
+pub fn analyze_project(
+    app_handle: AppHandle,
+    path: String,
+    project_id: Option<String>,
+) -> Result<ProjectAnalysis, String> {
+    let project_path = Path::new(&path);
+
+    if !project_path.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+
+    if !project_path.is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+
+    // Same ignore rules as the assistant's file tools
+    let canonical_path = project_path.canonicalize().map_err(|e| e.to_string())?;
+    let walker = ProjectWalker::for_path(&app_handle, &canonical_path);
+
+    let manifests = detect_ecosystems(project_path);
+    let (mut tech_stack, mut frameworks) = detect_tech_stack(project_path, &manifests);
+    let mut detected_services = detect_services(project_path, &manifests);
+
+    let mut has_tests = detect_tests(project_path, &walker);
+
+    // Check for CI
+    let has_ci = project_path.join(".github/workflows").exists()
+        || project_path.join("vercel.json").exists()
+        || project_path.join(".gitlab-ci.yml").exists();
+
+    // Count files (excluding ignored paths)
+    let file_count = walker.files().count();
+    let code_stats = collect_code_stats(&walker);
+
+    // Get top-level directory structure
+    let directory_structure: Vec<String> = walker
+        .clone()
+        .max_depth(1)
+        .walk()
+        .map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if e.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                format!("{}/", name)
+            } else {
+                name
+            }
+        })
+        .collect();
+
+    let package_json = parse_package_json(project_path);
+    // Parses any workspace members too
+    let cargo_toml = parse_cargo(project_path);
+
+    // Sub-packages of a monorepo, rolled up into the project-level view
+    let cargo_members: Vec<String> = cargo_toml
+        .as_ref()
+        .map(|c| c.workspace_members.iter().map(|m| m.path.clone()).collect())
+        .unwrap_or_default();
+    let packages: Vec<PackageAnalysis> = detect_packages(&canonical_path, &walker, &cargo_members)
+        .into_iter()
+        .map(|package| analyze_package(&canonical_path, &walker, package))
+        .collect();
+    for package in &packages {
+        extend_unique(&mut tech_stack, &package.tech_stack);
+        extend_unique(&mut frameworks, &package.frameworks);
+        extend_unique(&mut detected_services, &package.detected_services);
+        has_tests |= package.has_tests;
+    }
+    let dependency_inventory = collect_inventory(&canonical_path, packages.iter().map(|p| p.path.as_str()));
+
+    let secret_scan = scan_secrets(&walker);
+    let env_contract = check_env_contract(&walker, packages.iter().map(|p| p.path.as_str()));
+
+    // Offline audit against whatever advisory snapshots are configured
+    let advisory_sources = load_advisory_sources(&app_handle);
+    let vulnerability_audit = (!advisory_sources.is_empty())
+        .then(|| audit_dependencies(&advisory_sources, &dependency_inventory.dependencies));
+
+    // SOP gates and recommendation rules are user-editable, so they live in the database
+    let (phase_gates, last_test_run, rules, silenced, github_url) = {
+        let db = get_db(&app_handle);
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        let rules = load_recommendation_rules(&conn).map_err(|e| e.to_string())?;
+        let (silenced, github_url) = match project_id {
+            Some(ref id) => (
+                silenced_rules(&conn, id).map_err(|e| e.to_string())?,
+                project_github_url(&conn, id).map_err(|e| e.to_string())?,
+            ),
+            None => (Vec::new(), None),
+        };
+        (load_phase_gates(&conn), last_test_run(&conn, &canonical_path), rules, silenced, github_url)
+    };
+
+    // Local repository only; nothing is fetched
+    let git = inspect_git(&canonical_path, github_url.as_deref());
+    let has_git = git.is_some() || project_path.join(".git").exists();
+
+    let mut analysis = ProjectAnalysis {
+        project_path: path.clone(),
+        tech_stack,
+        frameworks,
+        project_type: String::new(),
+        has_git,
+        git,
+        has_tests,
+        has_ci,
+        env_contract,
+        file_count,
+        code_stats,
+        directory_structure,
+        detected_services,
+        package_json,
+        cargo_toml,
+        manifests,
+        packages,
+        dependency_inventory,
+        vulnerability_audit,
+        secret_scan,
+        recommendations: Vec::new(),
+        sop_progress: SopProgress {
+            estimated_phase: 0,
+            phase_name: "Unknown".to_string(),
+            evidence: Vec::new(),
+            phases: Vec::new(),
+        },
+    };
+
+    let ctx = GateContext {
+        analysis: &analysis,
+        walker: &walker,
+        last_test_run,
+    };
+    analysis.sop_progress = estimate_sop_phase(evaluate_phases(&phase_gates, &ctx));
+    analysis.project_type = detect_project_type(&analysis);
+    let analysis_value = serde_json::to_value(&analysis).map_err(|e| e.to_string())?;
+    analysis.recommendations = apply_rules(&rules, &analysis_value, &analysis.project_type, &silenced);
+
+    Ok(analysis)
+}
+
+#[tauri::command]
+pub fn save_project_analysis(
+    app_handle: AppHandle,
+    project_id: String,
+    analysis: ProjectAnalysis,
+) -> Result<(), String> {
+    let db = get_db(&app_handle);
+    let mut conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let status_report = serde_json::to_string(&analysis).map_err(|e| e.to_string())?;
+    let last_analyzed = Utc::now().to_rfc3339();
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE projects SET status_report = ?1, last_analyzed = ?2, current_phase = ?3 WHERE id = ?4",
+        (&status_report, &last_analyzed, analysis.sop_progress.estimated_phase, &project_id),
+    )
+    .map_err(|e| e.to_string())?;
+    let analysis_id = record_analysis(&tx, &project_id, &last_analyzed, &analysis).map_err(|e| e.to_string())?;
+    save_inventory(&tx, &project_id, &last_analyzed, analysis_id, &analysis.dependency_inventory)
+        .map_err(|e| e.to_string())?;
+
+    // Committed credentials block the pre-ship checklist until a clean analysis
+    let critical_secrets = analysis.secret_scan.critical().count();
+    let block_reason = (critical_secrets > 0)
+        .then(|| format!("{} critical secret(s) found in tracked files", critical_secrets));
+    set_roadmap_block(&tx, &project_id, PRE_SHIP_SOP, block_reason.as_deref()).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::gates::{GateResult, SopGate};
+
+    fn phase(sop_number: i32, passed: Option<bool>) -> PhaseEvidence {
+        PhaseEvidence {
+            sop_number,
+            name: format!("SOP {}", sop_number),
+            passed,
+            gates: passed
+                .map(|passed| GateResult {
+                    gate: SopGate::TestsPassing,
+                    passed,
+                    detail: format!("SOP {} evidence", sop_number),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn phase_is_the_last_gated_sop_before_the_first_failure() {
+        let progress = estimate_sop_phase(vec![
+            phase(1, None),
+            phase(5, Some(true)),
+            phase(6, None),
+            phase(7, Some(true)),
+            phase(8, Some(false)),
+            phase(9, Some(true)),
+        ]);
+        assert_eq!(progress.estimated_phase, 7);
+        assert_eq!(progress.phase_name, "SOP 7");
+        assert_eq!(progress.evidence, ["SOP 5 evidence", "SOP 7 evidence"]);
+        assert_eq!(progress.phases.len(), 6);
+    }
+
+    #[test]
+    fn no_passing_gates_means_an_unknown_phase() {
+        let progress = estimate_sop_phase(vec![phase(1, None), phase(5, Some(false)), phase(6, Some(true))]);
+        assert_eq!(progress.estimated_phase, 0);
+        assert_eq!(progress.phase_name, "Unknown");
+        assert!(progress.evidence.is_empty());
+
+        assert_eq!(estimate_sop_phase(Vec::new()).estimated_phase, 0);
+    }
+}
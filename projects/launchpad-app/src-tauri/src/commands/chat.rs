@@ -1,515 +1,1254 @@
-use crate::db::get_db;
-use crate::commands::file_tools::{list_files, read_file, grep_files, get_directory_tree};
-use chrono::Utc;
-use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
-use uuid::Uuid;
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Conversation {
-    pub id: String,
-    pub project_id: Option<String>,
-    pub title: Option<String>,
-    pub created_at: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Message {
-    pub id: String,
-    pub conversation_id: String,
-    pub role: String,
-    pub content: String,
-    pub created_at: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ChatRequest {
-    pub conversation_id: Option<String>,
-    pub project_id: Option<String>,
-    pub message: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ChatResponse {
-    pub conversation_id: String,
-    pub message: Message,
-}
-
-#[tauri::command]
-pub fn list_conversations(
-    app_handle: AppHandle,
-    project_id: Option<String>,
-) -> Result<Vec<Conversation>, String> {
-    let db = get_db(&app_handle);
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-    let mut conversations: Vec<Conversation> = Vec::new();
-
-    if let Some(ref pid) = project_id {
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, project_id, title, created_at FROM conversations
-                 WHERE project_id = ?1 ORDER BY created_at DESC",
-            )
-            .map_err(|e| e.to_string())?;
-
-        let rows = stmt.query_map([pid], |row| {
-            Ok(Conversation {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                title: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
-
-        for row in rows {
-            if let Ok(conv) = row {
-                conversations.push(conv);
-            }
-        }
-    } else {
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, project_id, title, created_at FROM conversations
-                 WHERE project_id IS NULL ORDER BY created_at DESC",
-            )
-            .map_err(|e| e.to_string())?;
-
-        let rows = stmt.query_map([], |row| {
-            Ok(Conversation {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                title: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
-
-        for row in rows {
-            if let Ok(conv) = row {
-                conversations.push(conv);
-            }
-        }
-    }
-
-    Ok(conversations)
-}
-
-#[tauri::command]
-pub fn get_conversation_messages(
-    app_handle: AppHandle,
-    conversation_id: String,
-) -> Result<Vec<Message>, String> {
-    let db = get_db(&app_handle);
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, conversation_id, role, content, created_at
-             FROM messages WHERE conversation_id = ?1 ORDER BY created_at ASC",
-        )
-        .map_err(|e| e.to_string())?;
-
-    let messages = stmt
-        .query_map([&conversation_id], |row| {
-            Ok(Message {
-                id: row.get(0)?,
-                conversation_id: row.get(1)?,
-                role: row.get(2)?,
-                content: row.get(3)?,
-                created_at: row.get(4)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    Ok(messages)
-}
-
-#[tauri::command]
-pub fn create_conversation(
-    app_handle: AppHandle,
-    project_id: Option<String>,
-    title: Option<String>,
-) -> Result<Conversation, String> {
-    let db = get_db(&app_handle);
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-    let id = Uuid::new_v4().to_string();
-    let created_at = Utc::now().to_rfc3339();
-
-    conn.execute(
-        "INSERT INTO conversations (id, project_id, title, created_at)
-         VALUES (?1, ?2, ?3, ?4)",
-        (&id, &project_id, &title, &created_at),
-    )
-    .map_err(|e| e.to_string())?;
-
-    Ok(Conversation {
-        id,
-        project_id,
-        title,
-        created_at,
-    })
-}
-
-#[tauri::command]
-pub fn save_message(
-    app_handle: AppHandle,
-    conversation_id: String,
-    role: String,
-    content: String,
-) -> Result<Message, String> {
-    let db = get_db(&app_handle);
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-    let id = Uuid::new_v4().to_string();
-    let created_at = Utc::now().to_rfc3339();
-
-    conn.execute(
-        "INSERT INTO messages (id, conversation_id, role, content, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        (&id, &conversation_id, &role, &content, &created_at),
-    )
-    .map_err(|e| e.to_string())?;
-
-    // Update conversation title from first user message if not set
-    if role == "user" {
-        let title_exists: bool = conn
-            .query_row(
-                "SELECT title IS NOT NULL FROM conversations WHERE id = ?1",
-                [&conversation_id],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !title_exists {
-            let short_title = content.chars().take(50).collect::<String>();
-            let title = if content.len() > 50 {
-                format!("{}...", short_title)
-            } else {
-                short_title
-            };
-
-            conn.execute(
-                "UPDATE conversations SET title = ?1 WHERE id = ?2",
-                (&title, &conversation_id),
-            )
-            .ok();
-        }
-    }
-
-    Ok(Message {
-        id,
-        conversation_id,
-        role,
-        content,
-        created_at,
-    })
-}
-
-#[tauri::command]
-pub fn delete_conversation(app_handle: AppHandle, id: String) -> Result<(), String> {
-    let db = get_db(&app_handle);
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-    conn.execute("DELETE FROM conversations WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
-
-    Ok(())
-}
-
-/// Define the tools available to Claude
-fn get_tools() -> serde_json::Value {
-    serde_json::json!([
-        {
-            "name": "list_files",
-            "description": "List files in a directory. Use this to explore project structure and find files. Automatically excludes node_modules, .git, target, .next, dist, and out directories.",
-            "input_schema": {
-                "type": "object",
-                "properties": {
-                    "base_path": {
-                        "type": "string",
-                        "description": "The base directory path to search in"
-                    },
-                    "pattern": {
-                        "type": "string",
-                        "description": "Optional glob pattern to filter files (e.g., '*.ts', '*.tsx', 'src/*')"
-                    },
-                    "max_depth": {
-                        "type": "integer",
-                        "description": "Maximum directory depth to search (default: 3)"
-                    }
-                },
-                "required": ["base_path"]
-            }
-        },
-        {
-            "name": "read_file",
-            "description": "Read the contents of a file. Use this to examine code, configuration files, or documentation.",
-            "input_schema": {
-                "type": "object",
-                "properties": {
-                    "file_path": {
-                        "type": "string",
-                        "description": "The full path to the file to read"
-                    },
-                    "max_lines": {
-                        "type": "integer",
-                        "description": "Maximum number of lines to read (default: 500)"
-                    }
-                },
-                "required": ["file_path"]
-            }
-        },
-        {
-            "name": "grep_files",
-            "description": "Search for text patterns in files. Use this to find specific code, function definitions, imports, or any text pattern across the codebase.",
-            "input_schema": {
-                "type": "object",
-                "properties": {
-                    "base_path": {
-                        "type": "string",
-                        "description": "The base directory path to search in"
-                    },
-                    "search_pattern": {
-                        "type": "string",
-                        "description": "The text pattern to search for (case-insensitive)"
-                    },
-                    "file_pattern": {
-                        "type": "string",
-                        "description": "Optional glob pattern to filter which files to search (e.g., '*.ts', '*.tsx')"
-                    },
-                    "max_results": {
-                        "type": "integer",
-                        "description": "Maximum number of results to return (default: 50)"
-                    }
-                },
-                "required": ["base_path", "search_pattern"]
-            }
-        },
-        {
-            "name": "get_directory_tree",
-            "description": "Get a visual tree representation of the directory structure. Useful for understanding project layout.",
-            "input_schema": {
-                "type": "object",
-                "properties": {
-                    "base_path": {
-                        "type": "string",
-                        "description": "The base directory path"
-                    },
-                    "max_depth": {
-                        "type": "integer",
-                        "description": "Maximum depth of the tree (default: 3)"
-                    }
-                },
-                "required": ["base_path"]
-            }
-        }
-    ])
-}
-
-/// Execute a tool call and return the result
-fn execute_tool(name: &str, input: &serde_json::Value) -> Result<String, String> {
-    match name {
-        "list_files" => {
-            let base_path = input["base_path"].as_str().ok_or("Missing base_path")?;
-            let pattern = input["pattern"].as_str().map(|s| s.to_string());
-            let max_depth = input["max_depth"].as_u64().map(|n| n as usize);
-
-            let result = list_files(base_path.to_string(), pattern, max_depth)?;
-            serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
-        }
-        "read_file" => {
-            let file_path = input["file_path"].as_str().ok_or("Missing file_path")?;
-            let max_lines = input["max_lines"].as_u64().map(|n| n as usize);
-
-            let result = read_file(file_path.to_string(), max_lines)?;
-            Ok(format!("File: {}\nLines: {}{}\n\n{}",
-                result.path,
-                result.line_count,
-                if result.truncated { " (truncated)" } else { "" },
-                result.content
-            ))
-        }
-        "grep_files" => {
-            let base_path = input["base_path"].as_str().ok_or("Missing base_path")?;
-            let search_pattern = input["search_pattern"].as_str().ok_or("Missing search_pattern")?;
-            let file_pattern = input["file_pattern"].as_str().map(|s| s.to_string());
-            let max_results = input["max_results"].as_u64().map(|n| n as usize);
-
-            let result = grep_files(base_path.to_string(), search_pattern.to_string(), file_pattern, max_results)?;
-
-            if result.is_empty() {
-                Ok("No matches found.".to_string())
-            } else {
-                let formatted: Vec<String> = result.iter()
-                    .map(|r| format!("{}:{}: {}", r.file, r.line_number, r.line_content))
-                    .collect();
-                Ok(formatted.join("\n"))
-            }
-        }
-        "get_directory_tree" => {
-            let base_path = input["base_path"].as_str().ok_or("Missing base_path")?;
-            let max_depth = input["max_depth"].as_u64().map(|n| n as usize);
-
-            get_directory_tree(base_path.to_string(), max_depth)
-        }
-        _ => Err(format!("Unknown tool: {}", name))
-    }
-}
-
-#[tauri::command]
-pub async fn send_chat_message(
-    _app_handle: AppHandle,
-    api_key: String,
-    messages: Vec<Message>,
-    system_prompt: Option<String>,
-    project_path: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
-
-    let base_system = r#"You are an AI assistant integrated into Launchpad, a Micro-SaaS shipping framework.
-You help developers build and ship products using a structured SOP (Standard Operating Procedure) system.
-
-Your capabilities:
-- Search and read files in linked project directories
-- Analyze codebases to understand architecture and patterns
-- Track progress through the SOP pipeline (13 phases from Idea to Launch)
-- Provide guidance on tech stack decisions (Next.js, Tailwind, Clerk, Neon, Drizzle, Stripe, Vercel)
-- Help with code reviews, architecture decisions, and best practices
-
-You have access to file tools to explore and read project files. When the user asks about code, features, or wants you to find something, USE THE TOOLS to search and read the actual files. Don't just make assumptions - look at the code.
-
-Be concise, technical, and action-oriented. When you identify actionable items, be specific about what needs to be done."#;
-
-    let system = if let Some(ref path) = project_path {
-        format!("{}\n\nCurrent project path: {}", base_system, path)
-    } else if let Some(custom) = system_prompt {
-        custom
-    } else {
-        base_system.to_string()
-    };
-
-    // Only include tools if we have a project path
-    let tools = if project_path.is_some() {
-        Some(get_tools())
-    } else {
-        None
-    };
-
-    // Convert messages to API format
-    let mut api_messages: Vec<serde_json::Value> = messages
-        .iter()
-        .map(|m| {
-            serde_json::json!({
-                "role": m.role,
-                "content": m.content
-            })
-        })
-        .collect();
-
-    // Loop to handle tool use
-    let max_iterations = 10;
-    for _ in 0..max_iterations {
-        let mut body = serde_json::json!({
-            "model": "claude-sonnet-4-20250514",  // Claude Sonnet 4 (2025)
-            "max_tokens": 4096,
-            "system": system,
-            "messages": api_messages
-        });
-
-        if let Some(ref t) = tools {
-            body["tools"] = t.clone();
-        }
-
-        let response = client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("Content-Type", "application/json")
-            .header("x-api-key", &api_key)
-            .header("anthropic-version", "2023-06-01")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(format!("API error: {}", error_text));
-        }
-
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-        let stop_reason = json["stop_reason"].as_str().unwrap_or("");
-
-        // If it's a tool use response, execute tools and continue
-        if stop_reason == "tool_use" {
-            let content = json["content"].as_array().ok_or("No content in response")?;
-
-            // Collect all content blocks (text + tool_use)
-            let mut tool_results = Vec::new();
-            let mut assistant_content = Vec::new();
-
-            for block in content {
-                let block_type = block["type"].as_str().unwrap_or("");
-
-                if block_type == "text" {
-                    assistant_content.push(block.clone());
-                } else if block_type == "tool_use" {
-                    assistant_content.push(block.clone());
-
-                    let tool_name = block["name"].as_str().unwrap_or("");
-                    let tool_id = block["id"].as_str().unwrap_or("");
-                    let tool_input = &block["input"];
-
-                    // Execute the tool
-                    let result = match execute_tool(tool_name, tool_input) {
-                        Ok(r) => r,
-                        Err(e) => format!("Error: {}", e)
-                    };
-
-                    tool_results.push(serde_json::json!({
-                        "type": "tool_result",
-                        "tool_use_id": tool_id,
-                        "content": result
-                    }));
-                }
-            }
-
-            // Add assistant's response with tool calls
-            api_messages.push(serde_json::json!({
-                "role": "assistant",
-                "content": assistant_content
-            }));
-
-            // Add tool results as user message
-            api_messages.push(serde_json::json!({
-                "role": "user",
-                "content": tool_results
-            }));
-
-            continue;
-        }
-
-        // Extract final text response
-        let content = json["content"].as_array().ok_or("No content in response")?;
-        let mut text_parts = Vec::new();
-
-        for block in content {
-            if block["type"].as_str() == Some("text") {
-                if let Some(text) = block["text"].as_str() {
-                    text_parts.push(text.to_string());
-                }
-            }
-        }
-
-        return Ok(text_parts.join("\n"));
-    }
-
-    Err("Max tool iterations reached".to_string())
-}
+use crate::db::get_db;
+use crate::llm::{self, LlmError, LlmRequest, ProviderConfig, ProviderKind, StopReason, StreamUpdate};
+use rusqlite::Connection;
+use crate::commands::credentials;
+use crate::commands::usage::{record_usage, UsageRecord};
+use crate::commands::file_tools::{validate_path, list_files, read_file, grep_files, get_directory_tree, GrepOptions, GrepResults, ReadFileOptions};
+use crate::commands::file_changes::{stage_apply_patch, stage_create_file, stage_write_file};
+use crate::commands::command_runner::{self, CommandOutput};
+use crate::commands::symbols::{find_definition, get_symbols, Symbol};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Tauri event carrying incremental updates for an in-flight chat request
+pub const CHAT_STREAM_EVENT: &str = "chat-stream";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Conversation {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub title: Option<String>,
+    /// Per-conversation override of the `llm_provider` / `llm_model` settings
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Message {
+    pub id: String,
+    pub conversation_id: String,
+    pub role: String,
+    pub content: String,
+    /// Raw API content blocks (tool_use / tool_result), when the message has them
+    #[serde(default)]
+    pub content_blocks: Option<serde_json::Value>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatRequest {
+    pub conversation_id: Option<String>,
+    pub project_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatResponse {
+    pub conversation_id: String,
+    pub message: Message,
+}
+
+/// Incremental update emitted on `CHAT_STREAM_EVENT`, keyed by request id
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatStreamEvent {
+    TextDelta {
+        request_id: String,
+        text: String,
+    },
+    ToolCallStart {
+        request_id: String,
+        tool_use_id: String,
+        name: String,
+    },
+    ToolResult {
+        request_id: String,
+        tool_use_id: String,
+        name: String,
+        content: String,
+        is_error: bool,
+    },
+    Complete {
+        request_id: String,
+        text: String,
+    },
+    Cancelled {
+        request_id: String,
+        partial_text: String,
+    },
+    Error {
+        request_id: String,
+        message: String,
+    },
+}
+
+/// Final result of `send_chat_message`; `text` is the partial reply when cancelled
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChatReply {
+    pub request_id: String,
+    pub text: String,
+    pub cancelled: bool,
+}
+
+/// Partial output returned when an in-flight chat request is cancelled
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancelledChat {
+    pub request_id: String,
+    pub partial_text: String,
+    pub cancelled: bool,
+}
+
+#[derive(Clone)]
+struct ActiveChatRequest {
+    token: CancellationToken,
+    partial_text: Arc<Mutex<String>>,
+}
+
+/// Registry of in-flight chat requests, managed as Tauri state
+#[derive(Default)]
+pub struct ChatRequestRegistry {
+    requests: Mutex<HashMap<String, ActiveChatRequest>>,
+}
+
+impl ChatRequestRegistry {
+    fn register(&self, request_id: &str) -> ActiveChatRequest {
+        let request = ActiveChatRequest {
+            token: CancellationToken::new(),
+            partial_text: Arc::new(Mutex::new(String::new())),
+        };
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.insert(request_id.to_string(), request.clone());
+        }
+        request
+    }
+
+    fn remove(&self, request_id: &str) {
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.remove(request_id);
+        }
+    }
+
+    fn cancel(&self, request_id: &str) -> Option<String> {
+        let requests = self.requests.lock().ok()?;
+        let request = requests.get(request_id)?;
+        request.token.cancel();
+        let partial = request.partial_text.lock().map(|t| t.clone()).unwrap_or_default();
+        Some(partial)
+    }
+}
+
+/// How a chat request finished
+enum ChatOutcome {
+    Completed(String),
+    Cancelled(String),
+}
+
+#[tauri::command]
+pub fn list_conversations(
+    app_handle: AppHandle,
+    project_id: Option<String>,
+) -> Result<Vec<Conversation>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut conversations: Vec<Conversation> = Vec::new();
+
+    if let Some(ref pid) = project_id {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, project_id, title, provider, model, created_at FROM conversations
+                 WHERE project_id = ?1 ORDER BY created_at DESC",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt.query_map([pid], |row| {
+            Ok(Conversation {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                title: row.get(2)?,
+                provider: row.get(3)?,
+                model: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+        for row in rows {
+            if let Ok(conv) = row {
+                conversations.push(conv);
+            }
+        }
+    } else {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, project_id, title, provider, model, created_at FROM conversations
+                 WHERE project_id IS NULL ORDER BY created_at DESC",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(Conversation {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                title: row.get(2)?,
+                provider: row.get(3)?,
+                model: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+        for row in rows {
+            if let Ok(conv) = row {
+                conversations.push(conv);
+            }
+        }
+    }
+
+    Ok(conversations)
+}
+
+#[tauri::command]
+pub fn get_conversation_messages(
+    app_handle: AppHandle,
+    conversation_id: String,
+) -> Result<Vec<Message>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, conversation_id, role, content, content_blocks, created_at
+             FROM messages WHERE conversation_id = ?1 ORDER BY created_at ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let messages = stmt
+        .query_map([&conversation_id], |row| {
+            let content_blocks: Option<String> = row.get(4)?;
+            Ok(Message {
+                id: row.get(0)?,
+                conversation_id: row.get(1)?,
+                role: row.get(2)?,
+                content: row.get(3)?,
+                content_blocks: content_blocks.and_then(|b| serde_json::from_str(&b).ok()),
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(messages)
+}
+
+#[tauri::command]
+pub fn create_conversation(
+    app_handle: AppHandle,
+    project_id: Option<String>,
+    title: Option<String>,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<Conversation, String> {
+    if let Some(ref p) = provider {
+        ProviderKind::parse(p).ok_or_else(|| format!("Unknown provider: {}", p))?;
+    }
+
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let id = Uuid::new_v4().to_string();
+    let created_at = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO conversations (id, project_id, title, provider, model, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (&id, &project_id, &title, &provider, &model, &created_at),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(Conversation {
+        id,
+        project_id,
+        title,
+        provider,
+        model,
+        created_at,
+    })
+}
+
+/// Choose the provider and model for a conversation (None falls back to settings)
+#[tauri::command]
+pub fn set_conversation_model(
+    app_handle: AppHandle,
+    conversation_id: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<(), String> {
+    if let Some(ref p) = provider {
+        ProviderKind::parse(p).ok_or_else(|| format!("Unknown provider: {}", p))?;
+    }
+
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE conversations SET provider = ?1, model = ?2 WHERE id = ?3",
+        (&provider, &model, &conversation_id),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn insert_message(
+    conn: &Connection,
+    conversation_id: &str,
+    role: &str,
+    content: &str,
+    content_blocks: Option<&serde_json::Value>,
+) -> rusqlite::Result<Message> {
+    let id = Uuid::new_v4().to_string();
+    let created_at = Utc::now().to_rfc3339();
+    let blocks_json = content_blocks.map(|b| b.to_string());
+
+    conn.execute(
+        "INSERT INTO messages (id, conversation_id, role, content, content_blocks, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (&id, conversation_id, role, content, &blocks_json, &created_at),
+    )?;
+
+    Ok(Message {
+        id,
+        conversation_id: conversation_id.to_string(),
+        role: role.to_string(),
+        content: content.to_string(),
+        content_blocks: content_blocks.cloned(),
+        created_at,
+    })
+}
+
+#[tauri::command]
+pub fn save_message(
+    app_handle: AppHandle,
+    conversation_id: String,
+    role: String,
+    content: String,
+    content_blocks: Option<serde_json::Value>,
+) -> Result<Message, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let message = insert_message(&conn, &conversation_id, &role, &content, content_blocks.as_ref())
+        .map_err(|e| e.to_string())?;
+
+    // Update conversation title from first user message if not set
+    if role == "user" && !content.is_empty() {
+        let title_exists: bool = conn
+            .query_row(
+                "SELECT title IS NOT NULL FROM conversations WHERE id = ?1",
+                [&conversation_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !title_exists {
+            let short_title = content.chars().take(50).collect::<String>();
+            let title = if content.len() > 50 {
+                format!("{}...", short_title)
+            } else {
+                short_title
+            };
+
+            conn.execute(
+                "UPDATE conversations SET title = ?1 WHERE id = ?2",
+                (&title, &conversation_id),
+            )
+            .ok();
+        }
+    }
+
+    Ok(message)
+}
+
+#[tauri::command]
+pub fn delete_conversation(app_handle: AppHandle, id: String) -> Result<(), String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    conn.execute("DELETE FROM conversations WHERE id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Define the tools available to the assistant (provider-agnostic schema)
+fn get_tools() -> serde_json::Value {
+    serde_json::json!([
+        {
+            "name": "list_files",
+            "description": "List files in a directory. Use this to explore project structure and find files. Automatically excludes node_modules, .git, target, .next, dist, and out directories.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "base_path": {
+                        "type": "string",
+                        "description": "The base directory path to search in"
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "Optional glob pattern to filter files (e.g., '*.ts', '*.tsx', 'src/*')"
+                    },
+                    "max_depth": {
+                        "type": "integer",
+                        "description": "Maximum directory depth to search (default: 3)"
+                    }
+                },
+                "required": ["base_path"]
+            }
+        },
+        {
+            "name": "read_file",
+            "description": "Read the contents of a file with line numbers. Use this to examine code, configuration files, or documentation. For large files, read a line range with start_line/end_line, or keep passing back the returned cursor to page through the file.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "The full path to the file to read"
+                    },
+                    "start_line": {
+                        "type": "integer",
+                        "description": "First line to read, 1-based (default: 1)"
+                    },
+                    "end_line": {
+                        "type": "integer",
+                        "description": "Last line to read, inclusive (default: end of file)"
+                    },
+                    "max_lines": {
+                        "type": "integer",
+                        "description": "Maximum number of lines to read (default: 500)"
+                    },
+                    "cursor": {
+                        "type": "string",
+                        "description": "Continuation cursor returned by a previous read_file call, to read the next page"
+                    }
+                },
+                "required": ["file_path"]
+            }
+        },
+        {
+            "name": "grep_files",
+            "description": "Search for text or regex patterns in files. Use this to find specific code, function definitions, imports, or any text pattern across the codebase. Returns matches with optional context, per-file match counts, and files that were skipped (binary, too large, unreadable).",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "base_path": {
+                        "type": "string",
+                        "description": "The base directory path to search in"
+                    },
+                    "search_pattern": {
+                        "type": "string",
+                        "description": "The text or regular expression to search for"
+                    },
+                    "regex": {
+                        "type": "boolean",
+                        "description": "Treat search_pattern as a regular expression (default: false, literal match)"
+                    },
+                    "case_sensitive": {
+                        "type": "boolean",
+                        "description": "Match case exactly (default: false)"
+                    },
+                    "context_lines": {
+                        "type": "integer",
+                        "description": "Lines of context to show before and after each match (default: 0)"
+                    },
+                    "include": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Only search files matching any of these globs (e.g., ['*.ts', 'src/**/*.tsx'])"
+                    },
+                    "exclude": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Skip files matching any of these globs (e.g., ['*.test.ts'])"
+                    },
+                    "max_results": {
+                        "type": "integer",
+                        "description": "Maximum number of matches to return (default: 50)"
+                    }
+                },
+                "required": ["base_path", "search_pattern"]
+            }
+        },
+        {
+            "name": "get_directory_tree",
+            "description": "Get a visual tree representation of the directory structure. Useful for understanding project layout.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "base_path": {
+                        "type": "string",
+                        "description": "The base directory path"
+                    },
+                    "max_depth": {
+                        "type": "integer",
+                        "description": "Maximum depth of the tree (default: 3)"
+                    }
+                },
+                "required": ["base_path"]
+            }
+        },
+        {
+            "name": "get_symbols",
+            "description": "List the functions, methods, structs, classes, interfaces, exports and React components declared in Rust, TypeScript/TSX, JavaScript or Python files, with their line spans. Use this for an outline of a file before reading it.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "base_path": {
+                        "type": "string",
+                        "description": "The project root directory"
+                    },
+                    "file_path": {
+                        "type": "string",
+                        "description": "Optional file, relative to base_path, to outline (default: whole project)"
+                    },
+                    "kind": {
+                        "type": "string",
+                        "description": "Optional kind filter (function, method, struct, enum, trait, impl, type, interface, class, component, module, macro, const)"
+                    }
+                },
+                "required": ["base_path"]
+            }
+        },
+        {
+            "name": "find_definition",
+            "description": "Find where a function, type, class or component is defined anywhere in the project. Faster and more precise than grep_files for locating definitions.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "base_path": {
+                        "type": "string",
+                        "description": "The project root directory"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "The symbol name to look up"
+                    }
+                },
+                "required": ["base_path", "name"]
+            }
+        },
+        {
+            "name": "write_file",
+            "description": "Replace the full contents of a file in the current project. The change is staged as a diff and only written once the user approves it.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path of the file, relative to the project root"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "The complete new contents of the file"
+                    }
+                },
+                "required": ["file_path", "content"]
+            }
+        },
+        {
+            "name": "create_file",
+            "description": "Create a new file in the current project. Fails if the file already exists. The file is only created once the user approves it.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path of the new file, relative to the project root"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "The contents of the new file"
+                    }
+                },
+                "required": ["file_path", "content"]
+            }
+        },
+        {
+            "name": "apply_patch",
+            "description": "Apply a unified diff to an existing file in the current project. Prefer this over write_file for small edits. The patch is only applied once the user approves it.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path of the file to patch, relative to the project root"
+                    },
+                    "patch": {
+                        "type": "string",
+                        "description": "A unified diff (with ---/+++ headers and @@ hunks) against the current file contents"
+                    }
+                },
+                "required": ["file_path", "patch"]
+            }
+        },
+        {
+            "name": "run_command",
+            "description": "Run an allowlisted command (e.g. 'cargo check', 'pnpm test', 'git status') in the project root and return its exit code, stdout and stderr. No shell is used, so pipes, redirects and globbing are not available. Commands outside the user's allowlist are refused.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "command": {
+                        "type": "string",
+                        "description": "The program to run (e.g. 'cargo', 'pnpm', 'git')"
+                    },
+                    "args": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Arguments passed to the program"
+                    },
+                    "timeout_secs": {
+                        "type": "integer",
+                        "description": "Timeout in seconds (default: 60, max: 300)"
+                    }
+                },
+                "required": ["command"]
+            }
+        }
+    ])
+}
+
+/// What a tool call needs to know about the chat it runs in
+struct ToolContext<'a> {
+    app_handle: &'a AppHandle,
+    project_path: Option<&'a str>,
+    conversation_id: Option<&'a str>,
+    cancel: &'a CancellationToken,
+}
+
+/// Stage a write tool call and describe the pending change to the assistant
+fn stage_write_tool(
+    ctx: &ToolContext,
+    name: &str,
+    input: &serde_json::Value,
+) -> Result<String, String> {
+    let base_path = ctx.project_path.ok_or("Write tools require a project")?;
+    let file_path = input["file_path"].as_str().ok_or("Missing file_path")?;
+
+    let db = get_db(ctx.app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let change = match name {
+        "write_file" => {
+            let content = input["content"].as_str().ok_or("Missing content")?;
+            stage_write_file(&conn, ctx.conversation_id, base_path, file_path, content)?
+        }
+        "create_file" => {
+            let content = input["content"].as_str().ok_or("Missing content")?;
+            stage_create_file(&conn, ctx.conversation_id, base_path, file_path, content)?
+        }
+        _ => {
+            let patch = input["patch"].as_str().ok_or("Missing patch")?;
+            stage_apply_patch(&conn, ctx.conversation_id, base_path, file_path, patch)?
+        }
+    };
+
+    Ok(format!(
+        "Change {} to {} is staged and awaiting user approval. It has NOT been written yet.\n\n{}",
+        change.id, change.file_path, change.diff
+    ))
+}
+
+/// Check the allowlist, run the command in the project root and log the invocation
+async fn run_command_tool(ctx: &ToolContext<'_>, input: &serde_json::Value) -> Result<String, String> {
+    let project_path = ctx.project_path.ok_or("run_command requires a project")?;
+    let working_dir = validate_path(ctx.app_handle, project_path)?;
+    let working_dir = working_dir.to_string_lossy();
+    let program = input["command"].as_str().ok_or("Missing command")?;
+    let args: Vec<String> = input["args"]
+        .as_array()
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+    let timeout_secs = input["timeout_secs"].as_u64();
+    let command_line = std::iter::once(program.to_string())
+        .chain(args.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // A failed audit write shouldn't hide the outcome of a command that already ran
+    let record = |result: &Result<CommandOutput, String>, denied: bool| {
+        let logged = get_db(ctx.app_handle)
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| {
+                command_runner::log_command_run(&conn, ctx.conversation_id, &working_dir, &command_line, result, denied)
+            });
+        if let Err(e) = logged {
+            log::warn!("Failed to log command run `{}`: {}", command_line, e);
+        }
+    };
+
+    let allowlist = {
+        let db = get_db(ctx.app_handle);
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        command_runner::load_allowlist(&conn)
+    };
+    if !command_runner::is_allowed(&allowlist, program, &args) {
+        let reason = format!(
+            "Command not allowed: {}. Allowed commands: {} (options that write files, change config or run other programs are always denied)",
+            command_line,
+            allowlist.join(", ")
+        );
+        record(&Err(reason.clone()), true);
+        return Err(reason);
+    }
+
+    let result = command_runner::run_command(
+        std::path::Path::new(working_dir.as_ref()),
+        program,
+        &args,
+        timeout_secs,
+        ctx.cancel,
+    )
+    .await;
+    record(&result, false);
+    let output = result?;
+
+    let exit = match (output.timed_out, output.exit_code) {
+        (true, _) => "timed out".to_string(),
+        (false, Some(code)) => code.to_string(),
+        (false, None) => "killed by signal".to_string(),
+    };
+    Ok(format!(
+        "$ {}\nExit code: {} ({} ms)\n\nstdout{}:\n{}\n\nstderr{}:\n{}",
+        command_line,
+        exit,
+        output.duration_ms,
+        if output.stdout_truncated { " (truncated)" } else { "" },
+        output.stdout,
+        if output.stderr_truncated { " (truncated)" } else { "" },
+        output.stderr
+    ))
+}
+
+/// Render search results ripgrep-style: `file:line: text` for matches and
+/// `file-line- text` for context, followed by counts and skipped files
+fn format_grep_results(results: &GrepResults) -> String {
+    if results.matches.is_empty() && results.skipped.is_empty() {
+        return "No matches found.".to_string();
+    }
+
+    let mut out = Vec::new();
+    for (i, m) in results.matches.iter().enumerate() {
+        let has_context = !m.before.is_empty() || !m.after.is_empty();
+        if has_context && i > 0 {
+            out.push("--".to_string());
+        }
+        let first_before = m.line_number - m.before.len();
+        for (offset, line) in m.before.iter().enumerate() {
+            out.push(format!("{}-{}- {}", m.file, first_before + offset, line));
+        }
+        out.push(format!("{}:{}: {}", m.file, m.line_number, m.line_content));
+        for (offset, line) in m.after.iter().enumerate() {
+            out.push(format!("{}-{}- {}", m.file, m.line_number + 1 + offset, line));
+        }
+    }
+
+    if results.matches.is_empty() {
+        out.push("No matches found.".to_string());
+    }
+
+    let total: usize = results.file_counts.iter().map(|c| c.count).sum();
+    out.push(String::new());
+    out.push(format!(
+        "{} matches in {} of {} files searched{}",
+        total,
+        results.file_counts.len(),
+        results.files_searched,
+        if results.truncated { " (showing first matches only)" } else { "" }
+    ));
+    if results.truncated {
+        for count in &results.file_counts {
+            out.push(format!("  {}: {}", count.file, count.count));
+        }
+    }
+
+    if !results.skipped.is_empty() {
+        out.push(format!("Skipped {} files:", results.skipped.len()));
+        for skipped in results.skipped.iter().take(20) {
+            out.push(format!("  {} ({})", skipped.file, skipped.reason));
+        }
+        if results.skipped.len() > 20 {
+            out.push(format!("  ... and {} more", results.skipped.len() - 20));
+        }
+    }
+
+    out.join("\n")
+}
+
+fn format_symbols(symbols: &[Symbol]) -> String {
+    if symbols.is_empty() {
+        return "No symbols found.".to_string();
+    }
+
+    symbols
+        .iter()
+        .map(|s| {
+            format!(
+                "{}:{}-{} {}{} {}",
+                s.file_path,
+                s.start_line,
+                s.end_line,
+                if s.exported { "exported " } else { "" },
+                s.kind,
+                s.name
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Execute a tool call and return the result
+async fn execute_tool(ctx: &ToolContext<'_>, name: &str, input: &serde_json::Value) -> Result<String, String> {
+    match name {
+        "list_files" => {
+            let base_path = input["base_path"].as_str().ok_or("Missing base_path")?;
+            let pattern = input["pattern"].as_str().map(|s| s.to_string());
+            let max_depth = input["max_depth"].as_u64().map(|n| n as usize);
+
+            let result = list_files(ctx.app_handle.clone(), base_path.to_string(), pattern, max_depth)?;
+            serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
+        }
+        "read_file" => {
+            let file_path = input["file_path"].as_str().ok_or("Missing file_path")?;
+            let options: ReadFileOptions = serde_json::from_value(input.clone())
+                .map_err(|e| format!("Invalid read options: {}", e))?;
+
+            let result = read_file(ctx.app_handle.clone(), file_path.to_string(), Some(options))?;
+            let total = result.line_count.map(|n| n.to_string()).unwrap_or_else(|| "unknown".to_string());
+            let more = match result.next_cursor {
+                Some(ref cursor) => format!("\n\n(more content; continue with cursor \"{}\")", cursor),
+                None => String::new(),
+            };
+            Ok(format!("File: {}\nLines {}-{} of {}\n\n{}{}",
+                result.path,
+                result.start_line,
+                result.end_line,
+                total,
+                result.numbered_content(),
+                more
+            ))
+        }
+        "grep_files" => {
+            let base_path = input["base_path"].as_str().ok_or("Missing base_path")?;
+            let search_pattern = input["search_pattern"].as_str().ok_or("Missing search_pattern")?;
+            let mut options: GrepOptions = serde_json::from_value(input.clone())
+                .map_err(|e| format!("Invalid grep options: {}", e))?;
+            // Older transcripts used a single file_pattern glob
+            if let Some(file_pattern) = input["file_pattern"].as_str() {
+                options.include.push(file_pattern.to_string());
+            }
+
+            let result = grep_files(ctx.app_handle.clone(), base_path.to_string(), search_pattern.to_string(), Some(options))?;
+            Ok(format_grep_results(&result))
+        }
+        "get_directory_tree" => {
+            let base_path = input["base_path"].as_str().ok_or("Missing base_path")?;
+            let max_depth = input["max_depth"].as_u64().map(|n| n as usize);
+
+            get_directory_tree(ctx.app_handle.clone(), base_path.to_string(), max_depth)
+        }
+        "get_symbols" => {
+            let base_path = input["base_path"].as_str().ok_or("Missing base_path")?;
+            let file_path = input["file_path"].as_str().map(|s| s.to_string());
+            let kind = input["kind"].as_str().map(|s| s.to_string());
+
+            let symbols = get_symbols(ctx.app_handle.clone(), base_path.to_string(), file_path, kind)?;
+            Ok(format_symbols(&symbols))
+        }
+        "find_definition" => {
+            let base_path = input["base_path"].as_str().ok_or("Missing base_path")?;
+            let symbol_name = input["name"].as_str().ok_or("Missing name")?;
+
+            let symbols = find_definition(ctx.app_handle.clone(), base_path.to_string(), symbol_name.to_string())?;
+            Ok(format_symbols(&symbols))
+        }
+        "write_file" | "create_file" | "apply_patch" => stage_write_tool(ctx, name, input),
+        "run_command" => run_command_tool(ctx, input).await,
+        _ => Err(format!("Unknown tool: {}", name))
+    }
+}
+
+fn emit_stream_event(app_handle: &AppHandle, event: ChatStreamEvent) {
+    if let Err(e) = app_handle.emit(CHAT_STREAM_EVENT, event) {
+        log::warn!("Failed to emit chat stream event: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn send_chat_message(
+    app_handle: AppHandle,
+    api_key: String,
+    messages: Vec<Message>,
+    system_prompt: Option<String>,
+    project_path: Option<String>,
+    request_id: Option<String>,
+    conversation_id: Option<String>,
+) -> Result<ChatReply, String> {
+    let request_id = request_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let registry = app_handle.state::<ChatRequestRegistry>();
+    let active = registry.register(&request_id);
+
+    let turn = ChatTurn {
+        api_key,
+        messages,
+        system_prompt,
+        project_path,
+        conversation_id,
+    };
+    let result = run_chat(&app_handle, &request_id, &active, turn).await;
+    registry.remove(&request_id);
+
+    let final_event = match &result {
+        Ok(ChatOutcome::Completed(text)) => ChatStreamEvent::Complete {
+            request_id: request_id.clone(),
+            text: text.clone(),
+        },
+        Ok(ChatOutcome::Cancelled(partial_text)) => ChatStreamEvent::Cancelled {
+            request_id: request_id.clone(),
+            partial_text: partial_text.clone(),
+        },
+        Err(message) => ChatStreamEvent::Error {
+            request_id: request_id.clone(),
+            message: message.clone(),
+        },
+    };
+    emit_stream_event(&app_handle, final_event);
+
+    result.map(|outcome| {
+        let (text, cancelled) = match outcome {
+            ChatOutcome::Completed(text) => (text, false),
+            ChatOutcome::Cancelled(text) => (text, true),
+        };
+        ChatReply { request_id, text, cancelled }
+    })
+}
+
+/// Cancel an in-flight `send_chat_message` call, returning the text produced so far
+#[tauri::command]
+pub fn cancel_chat_request(
+    registry: State<'_, ChatRequestRegistry>,
+    request_id: String,
+) -> Result<Option<CancelledChat>, String> {
+    Ok(registry.cancel(&request_id).map(|partial_text| CancelledChat {
+        request_id,
+        partial_text,
+        cancelled: true,
+    }))
+}
+
+/// Inputs for a single `send_chat_message` call
+struct ChatTurn {
+    api_key: String,
+    messages: Vec<Message>,
+    system_prompt: Option<String>,
+    project_path: Option<String>,
+    /// When set, intermediate tool_use / tool_result exchanges are persisted here
+    conversation_id: Option<String>,
+}
+
+/// Persist one step of the tool loop so the conversation can be replayed exactly
+fn persist_transcript_message(
+    app_handle: &AppHandle,
+    conversation_id: &str,
+    role: &str,
+    content_blocks: &[serde_json::Value],
+) -> Result<(), String> {
+    let text: Vec<&str> = content_blocks
+        .iter()
+        .filter(|b| b["type"].as_str() == Some("text"))
+        .filter_map(|b| b["text"].as_str())
+        .collect();
+    let blocks = serde_json::Value::Array(content_blocks.to_vec());
+
+    let db = get_db(app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    insert_message(&conn, conversation_id, role, &text.join("\n"), Some(&blocks))
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Resolve which provider and model serve a request: conversation override,
+/// then the `llm_provider` / `llm_model` settings, then Anthropic defaults
+fn resolve_provider_config(
+    app_handle: &AppHandle,
+    conversation_id: Option<&str>,
+    anthropic_api_key: String,
+) -> Result<ProviderConfig, String> {
+    let db = get_db(app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let setting = |key: &str| -> Option<String> {
+        conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+            .ok()
+            .filter(|v: &String| !v.is_empty())
+    };
+
+    let (conv_provider, conv_model): (Option<String>, Option<String>) = conversation_id
+        .and_then(|id| {
+            conn.query_row(
+                "SELECT provider, model FROM conversations WHERE id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok()
+        })
+        .unwrap_or((None, None));
+
+    let provider_name = conv_provider
+        .or_else(|| setting("llm_provider"))
+        .unwrap_or_else(|| "anthropic".to_string());
+    let kind = ProviderKind::parse(&provider_name)
+        .ok_or_else(|| format!("Unknown provider: {}", provider_name))?;
+
+    let model = conv_model.or_else(|| setting("llm_model"));
+
+    let config = match kind {
+        ProviderKind::Anthropic => ProviderConfig {
+            kind,
+            model: model.unwrap_or_else(|| llm::DEFAULT_ANTHROPIC_MODEL.to_string()),
+            base_url: None,
+            api_key: Some(anthropic_api_key),
+        },
+        ProviderKind::OpenAi => ProviderConfig {
+            kind,
+            model: model.ok_or("No model configured for the OpenAI-compatible provider")?,
+            base_url: setting("openai_base_url"),
+            api_key: credentials::get_credential("openai_api_key").ok(),
+        },
+    };
+
+    Ok(config)
+}
+
+async fn run_chat(
+    app_handle: &AppHandle,
+    request_id: &str,
+    active: &ActiveChatRequest,
+    turn: ChatTurn,
+) -> Result<ChatOutcome, String> {
+    let ChatTurn {
+        api_key,
+        messages,
+        system_prompt,
+        project_path,
+        conversation_id,
+    } = turn;
+    let token = &active.token;
+    let partial_text = || active.partial_text.lock().map(|t| t.clone()).unwrap_or_default();
+
+    let config = resolve_provider_config(app_handle, conversation_id.as_deref(), api_key)?;
+    let provider = llm::build_provider(&config);
+
+    let base_system = r#"You are an AI assistant integrated into Launchpad, a Micro-SaaS shipping framework.
+You help developers build and ship products using a structured SOP (Standard Operating Procedure) system.
+
+Your capabilities:
+- Search and read files in linked project directories
+- Analyze codebases to understand architecture and patterns
+- Track progress through the SOP pipeline (13 phases from Idea to Launch)
+- Provide guidance on tech stack decisions (Next.js, Tailwind, Clerk, Neon, Drizzle, Stripe, Vercel)
+- Help with code reviews, architecture decisions, and best practices
+
+You have access to file tools to explore and read project files. When the user asks about code, features, or wants you to find something, USE THE TOOLS to search and read the actual files. Don't just make assumptions - look at the code.
+
+You can also propose edits with write_file, create_file and apply_patch. These are staged for the user to review as a diff; nothing is written until they approve, so tell the user what you changed and why.
+
+Use run_command to verify your claims (e.g. 'cargo check', 'pnpm test', 'git status'). Only commands on the user's allowlist can run, without a shell.
+
+Be concise, technical, and action-oriented. When you identify actionable items, be specific about what needs to be done."#;
+
+    let system = if let Some(ref path) = project_path {
+        format!("{}\n\nCurrent project path: {}", base_system, path)
+    } else if let Some(custom) = system_prompt {
+        custom
+    } else {
+        base_system.to_string()
+    };
+
+    // Only include tools if we have a project path
+    let tools = if project_path.is_some() {
+        Some(get_tools())
+    } else {
+        None
+    };
+
+    // Convert messages to API format, replaying stored content blocks verbatim
+    let mut api_messages: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "role": m.role,
+                "content": m.content_blocks.clone().unwrap_or_else(|| m.content.clone().into())
+            })
+        })
+        .collect();
+
+    let tool_ctx = ToolContext {
+        app_handle,
+        project_path: project_path.as_deref(),
+        conversation_id: conversation_id.as_deref(),
+        cancel: token,
+    };
+
+    // Loop to handle tool use
+    let max_iterations = 10;
+    for _ in 0..max_iterations {
+        if token.is_cancelled() {
+            return Ok(ChatOutcome::Cancelled(partial_text()));
+        }
+
+        let request = LlmRequest {
+            model: &config.model,
+            system: &system,
+            messages: &api_messages,
+            tools: tools.as_ref(),
+            max_tokens: 4096,
+        };
+
+        // Forward deltas to the frontend as they arrive
+        let mut on_update = |update: StreamUpdate| {
+            let event = match update {
+                StreamUpdate::Text(text) => {
+                    if let Ok(mut partial) = active.partial_text.lock() {
+                        partial.push_str(&text);
+                    }
+                    ChatStreamEvent::TextDelta {
+                        request_id: request_id.to_string(),
+                        text,
+                    }
+                }
+                StreamUpdate::ToolStart { id, name } => ChatStreamEvent::ToolCallStart {
+                    request_id: request_id.to_string(),
+                    tool_use_id: id,
+                    name,
+                },
+            };
+            emit_stream_event(app_handle, event);
+        };
+
+        let response = match provider.stream_message(&request, token, &mut on_update).await {
+            Ok(response) => response,
+            Err(LlmError::Cancelled) => return Ok(ChatOutcome::Cancelled(partial_text())),
+            Err(LlmError::Request(e)) => return Err(e),
+        };
+
+        // Every round-trip is billed, including tool-loop iterations
+        {
+            let db = get_db(app_handle);
+            let conn = db.conn.lock().map_err(|e| e.to_string())?;
+            let record = UsageRecord {
+                request_id,
+                conversation_id: conversation_id.as_deref(),
+                provider: config.kind.as_str(),
+                model: &config.model,
+                usage: response.usage,
+            };
+            if let Err(e) = record_usage(&conn, &record) {
+                log::warn!("Failed to record token usage: {}", e);
+            }
+        }
+        let content = &response.content;
+
+        // If it's a tool use response, execute tools and continue
+        if response.stop_reason == StopReason::ToolUse {
+            // Collect all content blocks (text + tool_use)
+            let mut tool_results = Vec::new();
+            let mut assistant_content = Vec::new();
+
+            for block in content {
+                let block_type = block["type"].as_str().unwrap_or("");
+
+                if block_type == "text" {
+                    assistant_content.push(block.clone());
+                } else if block_type == "tool_use" {
+                    // Stop between tool calls rather than mid-execution
+                    if token.is_cancelled() {
+                        return Ok(ChatOutcome::Cancelled(partial_text()));
+                    }
+
+                    assistant_content.push(block.clone());
+
+                    let tool_name = block["name"].as_str().unwrap_or("");
+                    let tool_id = block["id"].as_str().unwrap_or("");
+                    let tool_input = &block["input"];
+
+                    // Execute the tool
+                    let (result, is_error) = match execute_tool(&tool_ctx, tool_name, tool_input).await {
+                        Ok(r) => (r, false),
+                        Err(e) => (format!("Error: {}", e), true)
+                    };
+
+                    emit_stream_event(app_handle, ChatStreamEvent::ToolResult {
+                        request_id: request_id.to_string(),
+                        tool_use_id: tool_id.to_string(),
+                        name: tool_name.to_string(),
+                        content: result.clone(),
+                        is_error,
+                    });
+
+                    tool_results.push(serde_json::json!({
+                        "type": "tool_result",
+                        "tool_use_id": tool_id,
+                        "content": result,
+                        "is_error": is_error
+                    }));
+                }
+            }
+
+            if let Some(ref cid) = conversation_id {
+                persist_transcript_message(app_handle, cid, "assistant", &assistant_content)?;
+                persist_transcript_message(app_handle, cid, "user", &tool_results)?;
+            }
+
+            // Add assistant's response with tool calls
+            api_messages.push(serde_json::json!({
+                "role": "assistant",
+                "content": assistant_content
+            }));
+
+            // Add tool results as user message
+            api_messages.push(serde_json::json!({
+                "role": "user",
+                "content": tool_results
+            }));
+
+            continue;
+        }
+
+        // Extract final text response
+        let mut text_parts = Vec::new();
+
+        for block in content {
+            if block["type"].as_str() == Some("text") {
+                if let Some(text) = block["text"].as_str() {
+                    text_parts.push(text.to_string());
+                }
+            }
+        }
+
+        return Ok(ChatOutcome::Completed(text_parts.join("\n")));
+    }
+
+    Err("Max tool iterations reached".to_string())
+}
@@ -0,0 +1,549 @@
+use crate::analysis::gates::TestRun;
+use crate::db::get_db;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+pub const COMMAND_ALLOWLIST_SETTING: &str = "command_allowlist";
+
+/// Commands the agent may run when the user has not configured an allowlist.
+/// Each entry matches when its words are a prefix of the program and arguments.
+pub const DEFAULT_COMMAND_ALLOWLIST: &[&str] = &[
+    "git status",
+    "git diff",
+    "git log",
+    "cargo check",
+    "cargo test",
+    "cargo clippy",
+    "pnpm test",
+    "pnpm lint",
+    "pnpm typecheck",
+    "npm test",
+    "npm run lint",
+];
+
+/// Command prefixes that run a project's test suite
+const TEST_COMMANDS: &[&str] = &[
+    "cargo test",
+    "pnpm test",
+    "npm test",
+    "yarn test",
+    "bun test",
+    "pytest",
+    "go test",
+    "npx vitest",
+    "pnpm vitest",
+];
+
+/// Options that write files, load config, leave the project or run other
+/// programs, so an allowlisted prefix like `git diff` can't be extended with
+/// them. Long options also match with `=value` attached, short ones with any
+/// attached value (`-cuser.name=x`).
+const DENIED_ARGS: &[&str] = &[
+    // git
+    "-c",
+    "-C",
+    "-O",
+    "--output",
+    "--output-directory",
+    "--config-env",
+    "--exec",
+    "--exec-path",
+    "--upload-pack",
+    "--receive-pack",
+    "--git-dir",
+    "--work-tree",
+    "--ext-diff",
+    "--no-index",
+    "--open-files-in-pager",
+    // cargo
+    "-Z",
+    "--config",
+    "--manifest-path",
+    "--target-dir",
+    "--artifact-dir",
+    "--out-dir",
+    // npm / pnpm
+    "--prefix",
+    "--dir",
+    "--userconfig",
+    "--globalconfig",
+    "--script-shell",
+    "--node-options",
+    // Linters, formatters and test runners that rewrite project files, which
+    // must go through the staged-change approval flow instead
+    "--fix",
+    "--write",
+    "-u",
+    "--update-snapshots",
+    "--updateSnapshot",
+];
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+const MAX_TIMEOUT_SECS: u64 = 300;
+/// Bytes kept per stream; anything past this is read and discarded
+const MAX_OUTPUT_BYTES: usize = 32 * 1024;
+/// How long to keep reading output once the process has exited or been
+/// killed; a backgrounded grandchild can hold the pipes open indefinitely
+const DRAIN_GRACE: Duration = Duration::from_secs(2);
+
+/// Environment variables passed through to child processes; everything else is dropped
+const PASSTHROUGH_ENV: &[&str] = &[
+    "PATH", "HOME", "USERPROFILE", "SYSTEMROOT", "SystemRoot", "WINDIR", "COMSPEC", "PATHEXT",
+    "TEMP", "TMP", "TMPDIR", "LANG", "APPDATA", "LOCALAPPDATA", "CARGO_HOME", "RUSTUP_HOME",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommandOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Bytes written to each stream, including any past the cap
+    pub stdout_bytes: usize,
+    pub stderr_bytes: usize,
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommandRun {
+    pub id: String,
+    pub conversation_id: Option<String>,
+    pub working_dir: String,
+    pub command: String,
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub duration_ms: i64,
+    pub stdout_bytes: i64,
+    pub stderr_bytes: i64,
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+/// Load the allowlist from settings, falling back to the defaults
+pub fn load_allowlist(conn: &Connection) -> Vec<String> {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [COMMAND_ALLOWLIST_SETTING],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+    .and_then(|value| serde_json::from_str(&value).ok())
+    .unwrap_or_else(|| DEFAULT_COMMAND_ALLOWLIST.iter().map(|s| s.to_string()).collect())
+}
+
+fn is_denied_arg(arg: &str) -> bool {
+    DENIED_ARGS.iter().any(|flag| {
+        if flag.starts_with("--") {
+            arg.strip_prefix(flag).is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+        } else {
+            arg.starts_with(flag)
+        }
+    })
+}
+
+/// Check a program and its arguments against the allowlist
+pub fn is_allowed(allowlist: &[String], program: &str, args: &[String]) -> bool {
+    // Only bare program names are resolved from PATH; never a path into the project
+    if program.contains('/') || program.contains('\\') {
+        return false;
+    }
+    if args.iter().any(|a| is_denied_arg(a)) {
+        return false;
+    }
+
+    let argv: Vec<&str> = std::iter::once(program)
+        .chain(args.iter().map(|a| a.as_str()))
+        .collect();
+
+    allowlist.iter().any(|entry| {
+        let words: Vec<&str> = entry.split_whitespace().collect();
+        !words.is_empty() && words.len() <= argv.len() && words.iter().zip(&argv).all(|(w, a)| w == a)
+    })
+}
+
+/// Read a stream to the end or until `stop` fires, keeping at most `cap` bytes
+async fn read_capped<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    cap: usize,
+    stop: CancellationToken,
+) -> (Vec<u8>, usize) {
+    let mut kept = Vec::new();
+    let mut total = 0;
+    let Some(mut reader) = reader else {
+        return (kept, total);
+    };
+
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = tokio::select! {
+            read = reader.read(&mut buf) => match read {
+                Ok(n) if n > 0 => n,
+                _ => break,
+            },
+            _ = stop.cancelled() => break,
+        };
+        total += n;
+        if kept.len() < cap {
+            let take = n.min(cap - kept.len());
+            kept.extend_from_slice(&buf[..take]);
+        }
+    }
+
+    (kept, total)
+}
+
+/// Resolve a bare program name on Windows, where `pnpm` is really `pnpm.cmd`
+#[cfg(windows)]
+fn resolve_program(program: &str) -> String {
+    if Path::new(program).extension().is_some() {
+        return program.to_string();
+    }
+    let exts = std::env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string());
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    for dir in std::env::split_paths(&path) {
+        for ext in exts.split(';').filter(|e| !e.is_empty()) {
+            let candidate = dir.join(format!("{}{}", program, ext.to_lowercase()));
+            if candidate.is_file() {
+                return candidate.to_string_lossy().to_string();
+            }
+        }
+    }
+    program.to_string()
+}
+
+#[cfg(not(windows))]
+fn resolve_program(program: &str) -> String {
+    program.to_string()
+}
+
+/// Run a program without a shell in `working_dir`, with a minimal environment,
+/// a timeout and capped output. The child is killed on timeout or cancellation.
+pub async fn run_command(
+    working_dir: &Path,
+    program: &str,
+    args: &[String],
+    timeout_secs: Option<u64>,
+    cancel: &CancellationToken,
+) -> Result<CommandOutput, String> {
+    let timeout = Duration::from_secs(
+        timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, MAX_TIMEOUT_SECS),
+    );
+
+    let mut command = Command::new(resolve_program(program));
+    command
+        .args(args)
+        .current_dir(working_dir)
+        .env_clear()
+        .env("CI", "1")
+        .env("NO_COLOR", "1")
+        .env("TERM", "dumb")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    for key in PASSTHROUGH_ENV {
+        if let Some(value) = std::env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+
+    let started = Instant::now();
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+
+    let stop_reading = cancel.child_token();
+    let stdout = tokio::spawn(read_capped(
+        child.stdout.take(),
+        MAX_OUTPUT_BYTES,
+        stop_reading.clone(),
+    ));
+    let stderr = tokio::spawn(read_capped(
+        child.stderr.take(),
+        MAX_OUTPUT_BYTES,
+        stop_reading.clone(),
+    ));
+
+    let (exit_code, timed_out) = tokio::select! {
+        status = child.wait() => {
+            let status = status.map_err(|e| e.to_string())?;
+            (status.code(), false)
+        }
+        _ = tokio::time::sleep(timeout) => {
+            let _ = child.kill().await;
+            (None, true)
+        }
+        _ = cancel.cancelled() => {
+            let _ = child.kill().await;
+            return Err("Command cancelled".to_string());
+        }
+    };
+
+    // Killing the child doesn't reach anything it spawned, and those may still
+    // hold the pipes, so only drain for a grace period (or until cancelled)
+    let drain_deadline = tokio::spawn({
+        let stop_reading = stop_reading.clone();
+        async move {
+            tokio::time::sleep(DRAIN_GRACE).await;
+            stop_reading.cancel();
+        }
+    });
+    let (stdout, stdout_len) = stdout.await.map_err(|e| e.to_string())?;
+    let (stderr, stderr_len) = stderr.await.map_err(|e| e.to_string())?;
+    drain_deadline.abort();
+    if cancel.is_cancelled() {
+        return Err("Command cancelled".to_string());
+    }
+
+    Ok(CommandOutput {
+        exit_code,
+        stdout_truncated: stdout_len > stdout.len(),
+        stderr_truncated: stderr_len > stderr.len(),
+        stdout: String::from_utf8_lossy(&stdout).to_string(),
+        stderr: String::from_utf8_lossy(&stderr).to_string(),
+        stdout_bytes: stdout_len,
+        stderr_bytes: stderr_len,
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Record a command invocation (including denied and failed ones)
+pub fn log_command_run(
+    conn: &Connection,
+    conversation_id: Option<&str>,
+    working_dir: &str,
+    command: &str,
+    result: &Result<CommandOutput, String>,
+    denied: bool,
+) -> Result<(), String> {
+    let (status, exit_code, duration_ms, stdout_bytes, stderr_bytes, error) = match result {
+        _ if denied => ("denied", None, 0, 0, 0, result.as_ref().err().cloned()),
+        Ok(out) => (
+            if out.timed_out { "timed_out" } else { "completed" },
+            out.exit_code,
+            out.duration_ms as i64,
+            out.stdout_bytes as i64,
+            out.stderr_bytes as i64,
+            None,
+        ),
+        Err(e) => ("failed", None, 0, 0, 0, Some(e.clone())),
+    };
+
+    conn.execute(
+        "INSERT INTO command_runs (id, conversation_id, working_dir, command, status, exit_code,
+                duration_ms, stdout_bytes, stderr_bytes, error, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![
+            Uuid::new_v4().to_string(),
+            conversation_id,
+            working_dir,
+            command,
+            status,
+            exit_code,
+            duration_ms,
+            stdout_bytes,
+            stderr_bytes,
+            error,
+            Utc::now().to_rfc3339(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// The most recent test suite run in `project_dir` or one of its subdirectories
+pub fn last_test_run(conn: &Connection, project_dir: &Path) -> Option<TestRun> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT working_dir, command, status, exit_code, created_at
+             FROM command_runs
+             WHERE status != 'denied' AND working_dir LIKE ?1 || '%'
+             ORDER BY created_at DESC",
+        )
+        .ok()?;
+    let mut rows = stmt.query([project_dir.to_string_lossy()]).ok()?;
+
+    while let Ok(Some(row)) = rows.next() {
+        let working_dir: String = row.get(0).ok()?;
+        let command: String = row.get(1).ok()?;
+        let is_test = TEST_COMMANDS.iter().any(|prefix| command.starts_with(prefix));
+        if !is_test || !Path::new(&working_dir).starts_with(project_dir) {
+            continue;
+        }
+        let status: String = row.get(2).ok()?;
+        let exit_code: Option<i32> = row.get(3).ok()?;
+        return Some(TestRun {
+            command,
+            passed: status == "completed" && exit_code == Some(0),
+            ran_at: row.get(4).ok()?,
+        });
+    }
+
+    None
+}
+
+#[tauri::command]
+pub fn list_command_runs(
+    app_handle: AppHandle,
+    conversation_id: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<CommandRun>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, conversation_id, working_dir, command, status, exit_code, duration_ms,
+                    stdout_bytes, stderr_bytes, error, created_at
+             FROM command_runs
+             WHERE ?1 IS NULL OR conversation_id = ?1
+             ORDER BY created_at DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let runs = stmt
+        .query_map(rusqlite::params![conversation_id, limit.unwrap_or(100)], |row| {
+            Ok(CommandRun {
+                id: row.get(0)?,
+                conversation_id: row.get(1)?,
+                working_dir: row.get(2)?,
+                command: row.get(3)?,
+                status: row.get(4)?,
+                exit_code: row.get(5)?,
+                duration_ms: row.get(6)?,
+                stdout_bytes: row.get(7)?,
+                stderr_bytes: row.get(8)?,
+                error: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(entries: &[&str], program: &str, args: &[&str]) -> bool {
+        let allowlist: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        is_allowed(&allowlist, program, &args)
+    }
+
+    #[test]
+    fn entries_match_as_word_prefixes() {
+        assert!(allowed(&["git diff"], "git", &["diff"]));
+        assert!(allowed(&["git diff"], "git", &["diff", "--stat", "HEAD~1"]));
+        assert!(allowed(&["cargo test"], "cargo", &["test", "--", "--nocapture"]));
+        assert!(!allowed(&["git diff"], "git", &["difftool"]));
+        assert!(!allowed(&["git diff"], "git", &["push"]));
+        assert!(!allowed(&["git diff"], "git", &[]));
+        assert!(!allowed(&["npm run lint"], "npm", &["run", "build"]));
+        assert!(!allowed(&[""], "git", &["diff"]));
+        assert!(!allowed(&[], "git", &["diff"]));
+    }
+
+    #[test]
+    fn default_allowlist_allows_its_own_entries() {
+        let defaults: Vec<&str> = DEFAULT_COMMAND_ALLOWLIST.to_vec();
+        for entry in DEFAULT_COMMAND_ALLOWLIST {
+            let mut words = entry.split_whitespace();
+            let program = words.next().unwrap();
+            let args: Vec<&str> = words.collect();
+            assert!(allowed(&defaults, program, &args), "{}", entry);
+        }
+    }
+
+    #[test]
+    fn path_qualified_programs_are_rejected() {
+        assert!(!allowed(&["git diff"], "./git", &["diff"]));
+        assert!(!allowed(&["git diff"], "/usr/bin/git", &["diff"]));
+        assert!(!allowed(&["git diff"], "bin\\git", &["diff"]));
+    }
+
+    #[test]
+    fn long_options_match_bare_or_with_value() {
+        assert!(is_denied_arg("--output"));
+        assert!(is_denied_arg("--output=/tmp/x"));
+        assert!(is_denied_arg("--config=build.rustflags=[]"));
+        assert!(is_denied_arg("--fix"));
+        assert!(is_denied_arg("--fix=true"));
+        assert!(is_denied_arg("--write"));
+        // Only the option itself, not longer options sharing its prefix
+        assert!(!is_denied_arg("--outputs"));
+        assert!(!is_denied_arg("--fixed-strings"));
+        assert!(!is_denied_arg("--dirstat"));
+        assert!(!is_denied_arg("--color=never"));
+    }
+
+    #[test]
+    fn short_options_match_with_attached_values() {
+        assert!(is_denied_arg("-c"));
+        assert!(is_denied_arg("-cuser.name=x"));
+        assert!(is_denied_arg("-C"));
+        assert!(is_denied_arg("-C/tmp"));
+        assert!(is_denied_arg("-Zunstable-options"));
+        assert!(is_denied_arg("-u"));
+        assert!(!is_denied_arg("-q"));
+        assert!(!is_denied_arg("--stat"));
+    }
+
+    #[test]
+    fn source_rewriting_commands_are_denied() {
+        assert!(!allowed(&["cargo clippy"], "cargo", &["clippy", "--fix", "--allow-dirty"]));
+        assert!(!allowed(&["pnpm lint"], "pnpm", &["lint", "--fix"]));
+        assert!(!allowed(&["pnpm lint"], "pnpm", &["lint", "--", "--write", "."]));
+        assert!(!allowed(&["pnpm test"], "pnpm", &["test", "-u"]));
+        assert!(!allowed(&["git diff"], "git", &["diff", "--output=/tmp/x"]));
+        assert!(!allowed(&["git log"], "git", &["log", "--output", "/tmp/x"]));
+        assert!(!allowed(&["git diff"], "git", &["diff", "--no-index", "/etc/passwd", "a"]));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn backgrounded_grandchildren_do_not_block_output() {
+        let started = Instant::now();
+        let args = vec!["-c".to_string(), "echo done; sleep 30 &".to_string()];
+        let output = run_command(
+            Path::new("."),
+            "sh",
+            &args,
+            Some(10),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(output.exit_code, Some(0));
+        assert_eq!(output.stdout.trim(), "done");
+        assert!(!output.timed_out);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn short_option_prefix_rule_over_blocks_some_harmless_flags() {
+        // Known trade-off: `-c`/`-C` also cover git log's combined-diff and
+        // copy-detection flags, which are read-only but denied anyway
+        assert!(!allowed(&["git log"], "git", &["log", "-c"]));
+        assert!(!allowed(&["git log"], "git", &["log", "-C"]));
+        assert!(!allowed(&["git log"], "git", &["log", "-C50%"]));
+        // `-u` (patch output) on git log is read-only too
+        assert!(!allowed(&["git log"], "git", &["log", "-u"]));
+    }
+}
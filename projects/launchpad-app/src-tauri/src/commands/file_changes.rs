@@ -0,0 +1,421 @@
+use crate::commands::file_tools::validate_path_within_base;
+use crate::commands::path_policy;
+use crate::db::get_db;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+/// A file edit proposed by the assistant. Nothing touches disk until the user
+/// approves it; `original_content` is kept as the backup used for reverts.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileChange {
+    pub id: String,
+    pub conversation_id: Option<String>,
+    pub base_path: String,
+    pub file_path: String,
+    pub operation: String,
+    pub original_content: Option<String>,
+    pub new_content: String,
+    pub diff: String,
+    pub status: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
+
+const SELECT_FILE_CHANGE: &str = "SELECT id, conversation_id, base_path, file_path, operation, original_content,
+        new_content, diff, status, created_at, resolved_at FROM file_changes";
+
+fn row_to_file_change(row: &rusqlite::Row) -> rusqlite::Result<FileChange> {
+    Ok(FileChange {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        base_path: row.get(2)?,
+        file_path: row.get(3)?,
+        operation: row.get(4)?,
+        original_content: row.get(5)?,
+        new_content: row.get(6)?,
+        diff: row.get(7)?,
+        status: row.get(8)?,
+        created_at: row.get(9)?,
+        resolved_at: row.get(10)?,
+    })
+}
+
+fn read_existing(path: &Path) -> Result<Option<String>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    if !path.is_file() {
+        return Err("Target is not a file".to_string());
+    }
+    fs::read_to_string(path)
+        .map(Some)
+        .map_err(|e| format!("Failed to read file: {}", e))
+}
+
+/// Validate the target, build a diff preview and store the change as pending
+fn stage_change(
+    conn: &Connection,
+    conversation_id: Option<&str>,
+    base_path: &str,
+    file_path: &str,
+    operation: &str,
+    build_new_content: impl FnOnce(Option<&str>) -> Result<String, String>,
+) -> Result<FileChange, String> {
+    let base = Path::new(base_path)
+        .canonicalize()
+        .map_err(|_| format!("Project path does not exist: {}", base_path))?;
+    let target = validate_path_within_base(&base, file_path)?;
+    path_policy::enforce(conn, &target)?;
+
+    let relative = target
+        .strip_prefix(&base)
+        .map_err(|_| "Path traversal not allowed".to_string())?
+        .to_string_lossy()
+        .to_string();
+    if relative.is_empty() {
+        return Err("Target must be a file inside the project".to_string());
+    }
+
+    let original_content = read_existing(&target)?;
+    let new_content = build_new_content(original_content.as_deref())?;
+    let diff = diffy::create_patch(original_content.as_deref().unwrap_or(""), &new_content).to_string();
+
+    let change = FileChange {
+        id: Uuid::new_v4().to_string(),
+        conversation_id: conversation_id.map(|s| s.to_string()),
+        base_path: base.to_string_lossy().to_string(),
+        file_path: relative,
+        operation: operation.to_string(),
+        original_content,
+        new_content,
+        diff,
+        status: "pending".to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        resolved_at: None,
+    };
+
+    conn.execute(
+        "INSERT INTO file_changes (id, conversation_id, base_path, file_path, operation,
+                original_content, new_content, diff, status, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            &change.id,
+            &change.conversation_id,
+            &change.base_path,
+            &change.file_path,
+            &change.operation,
+            &change.original_content,
+            &change.new_content,
+            &change.diff,
+            &change.status,
+            &change.created_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(change)
+}
+
+/// Stage a full-file overwrite (the file may or may not exist)
+pub fn stage_write_file(
+    conn: &Connection,
+    conversation_id: Option<&str>,
+    base_path: &str,
+    file_path: &str,
+    content: &str,
+) -> Result<FileChange, String> {
+    stage_change(conn, conversation_id, base_path, file_path, "write", |_| Ok(content.to_string()))
+}
+
+/// Stage creation of a new file; fails if the file already exists
+pub fn stage_create_file(
+    conn: &Connection,
+    conversation_id: Option<&str>,
+    base_path: &str,
+    file_path: &str,
+    content: &str,
+) -> Result<FileChange, String> {
+    stage_change(conn, conversation_id, base_path, file_path, "create", |existing| {
+        if existing.is_some() {
+            return Err(format!("File already exists: {}", file_path));
+        }
+        Ok(content.to_string())
+    })
+}
+
+/// Stage a unified diff against the current file contents
+pub fn stage_apply_patch(
+    conn: &Connection,
+    conversation_id: Option<&str>,
+    base_path: &str,
+    file_path: &str,
+    patch: &str,
+) -> Result<FileChange, String> {
+    stage_change(conn, conversation_id, base_path, file_path, "patch", |existing| {
+        let existing = existing.ok_or_else(|| format!("File does not exist: {}", file_path))?;
+        let parsed = diffy::Patch::from_str(patch).map_err(|e| format!("Invalid patch: {}", e))?;
+        diffy::apply(existing, &parsed).map_err(|e| format!("Patch does not apply: {}", e))
+    })
+}
+
+fn get_change(conn: &Connection, id: &str) -> Result<FileChange, String> {
+    conn.query_row(
+        &format!("{} WHERE id = ?1", SELECT_FILE_CHANGE),
+        [id],
+        row_to_file_change,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!("File change not found: {}", id),
+        e => e.to_string(),
+    })
+}
+
+fn set_status(conn: &Connection, id: &str, status: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE file_changes SET status = ?1, resolved_at = ?2 WHERE id = ?3",
+        rusqlite::params![status, Utc::now().to_rfc3339(), id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_file_changes(
+    app_handle: AppHandle,
+    status: Option<String>,
+    conversation_id: Option<String>,
+) -> Result<Vec<FileChange>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR conversation_id = ?2)
+             ORDER BY created_at DESC",
+            SELECT_FILE_CHANGE
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let changes = stmt
+        .query_map(rusqlite::params![status, conversation_id], row_to_file_change)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(changes)
+}
+
+/// Write a pending change to disk
+fn approve_change(conn: &Connection, id: &str) -> Result<FileChange, String> {
+    let change = get_change(conn, id)?;
+    if change.status != "pending" {
+        return Err(format!("Change is already {}", change.status));
+    }
+
+    let target = validate_path_within_base(Path::new(&change.base_path), &change.file_path)?;
+    path_policy::enforce(conn, &target)?;
+
+    // Refuse to clobber edits made after the change was staged
+    if read_existing(&target)? != change.original_content {
+        return Err("File was modified after this change was staged; reject it and ask again".to_string());
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(&target, &change.new_content).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    set_status(conn, id, "applied")?;
+    log::info!("Applied file change {} to {}", id, target.display());
+
+    get_change(conn, id)
+}
+
+#[tauri::command]
+pub fn approve_file_change(app_handle: AppHandle, id: String) -> Result<FileChange, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    approve_change(&conn, &id)
+}
+
+#[tauri::command]
+pub fn reject_file_change(app_handle: AppHandle, id: String) -> Result<(), String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let change = get_change(&conn, &id)?;
+    if change.status != "pending" {
+        return Err(format!("Change is already {}", change.status));
+    }
+
+    set_status(&conn, &id, "rejected")
+}
+
+/// Restore the backup taken when an applied change was staged
+fn revert_change(conn: &Connection, id: &str) -> Result<FileChange, String> {
+    let change = get_change(conn, id)?;
+    if change.status != "applied" {
+        return Err("Only applied changes can be reverted".to_string());
+    }
+
+    let target = validate_path_within_base(Path::new(&change.base_path), &change.file_path)?;
+    path_policy::enforce(conn, &target)?;
+
+    if read_existing(&target)?.as_deref() != Some(change.new_content.as_str()) {
+        return Err("File was modified after this change was applied; revert manually".to_string());
+    }
+
+    match change.original_content {
+        Some(ref original) => {
+            fs::write(&target, original).map_err(|e| format!("Failed to restore file: {}", e))?
+        }
+        // The change created the file, so reverting removes it
+        None => fs::remove_file(&target).map_err(|e| format!("Failed to remove file: {}", e))?,
+    }
+
+    set_status(conn, id, "reverted")?;
+    log::info!("Reverted file change {} on {}", id, target.display());
+
+    get_change(conn, id)
+}
+
+#[tauri::command]
+pub fn revert_file_change(app_handle: AppHandle, id: String) -> Result<FileChange, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    revert_change(&conn, &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_in_memory;
+    use tempfile::TempDir;
+
+    /// A project directory the path policy explicitly allows, so the tests
+    /// don't depend on where the OS puts temp files
+    fn setup() -> (Connection, TempDir, String) {
+        let conn = open_in_memory();
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().canonicalize().unwrap().to_string_lossy().to_string();
+        let policy = serde_json::json!({ "allow": [base] }).to_string();
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)",
+            [path_policy::PATH_POLICY_SETTING, &policy],
+        )
+        .unwrap();
+        (conn, dir, base)
+    }
+
+    #[test]
+    fn staged_writes_touch_disk_only_once_approved_and_revert_cleanly() {
+        let (conn, dir, base) = setup();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "one\n").unwrap();
+
+        let change = stage_write_file(&conn, None, &base, "notes.txt", "one\ntwo\n").unwrap();
+        assert_eq!(change.status, "pending");
+        assert_eq!(change.original_content.as_deref(), Some("one\n"));
+        assert!(change.diff.contains("+two"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "one\n");
+
+        let applied = approve_change(&conn, &change.id).unwrap();
+        assert_eq!(applied.status, "applied");
+        assert!(applied.resolved_at.is_some());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "one\ntwo\n");
+        assert!(approve_change(&conn, &change.id).is_err());
+
+        let reverted = revert_change(&conn, &change.id).unwrap();
+        assert_eq!(reverted.status, "reverted");
+        assert_eq!(fs::read_to_string(&file).unwrap(), "one\n");
+        assert!(revert_change(&conn, &change.id).is_err());
+    }
+
+    #[test]
+    fn reverting_a_created_file_removes_it() {
+        let (conn, dir, base) = setup();
+        let change = stage_create_file(&conn, None, &base, "src/new.rs", "fn main() {}\n").unwrap();
+        approve_change(&conn, &change.id).unwrap();
+        assert!(dir.path().join("src/new.rs").is_file());
+
+        assert!(stage_create_file(&conn, None, &base, "src/new.rs", "").is_err());
+
+        revert_change(&conn, &change.id).unwrap();
+        assert!(!dir.path().join("src/new.rs").exists());
+    }
+
+    #[test]
+    fn edits_made_after_staging_are_not_clobbered() {
+        let (conn, dir, base) = setup();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "one\n").unwrap();
+
+        let change = stage_write_file(&conn, None, &base, "notes.txt", "two\n").unwrap();
+        fs::write(&file, "edited by hand\n").unwrap();
+
+        let err = approve_change(&conn, &change.id).unwrap_err();
+        assert!(err.contains("modified after this change was staged"), "{}", err);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "edited by hand\n");
+        assert_eq!(get_change(&conn, &change.id).unwrap().status, "pending");
+    }
+
+    #[test]
+    fn edits_made_after_applying_block_the_revert() {
+        let (conn, dir, base) = setup();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "one\n").unwrap();
+
+        let change = stage_write_file(&conn, None, &base, "notes.txt", "two\n").unwrap();
+        approve_change(&conn, &change.id).unwrap();
+        fs::write(&file, "three\n").unwrap();
+
+        let err = revert_change(&conn, &change.id).unwrap_err();
+        assert!(err.contains("modified after this change was applied"), "{}", err);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "three\n");
+    }
+
+    #[test]
+    fn patches_apply_against_the_current_contents() {
+        let (conn, dir, base) = setup();
+        fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+
+        let patch = diffy::create_patch("one\ntwo\n", "one\n2\n").to_string();
+        let change = stage_apply_patch(&conn, None, &base, "a.txt", &patch).unwrap();
+        assert_eq!(change.new_content, "one\n2\n");
+
+        let stale = diffy::create_patch("uno\n", "dos\n").to_string();
+        assert!(stage_apply_patch(&conn, None, &base, "a.txt", &stale).is_err());
+        assert!(stage_apply_patch(&conn, None, &base, "missing.txt", &patch).is_err());
+    }
+
+    #[test]
+    fn targets_outside_the_project_are_refused() {
+        let (conn, _dir, base) = setup();
+        assert!(stage_write_file(&conn, None, &base, "../escape.txt", "x").is_err());
+        assert!(stage_write_file(&conn, None, &base, "", "x").is_err());
+    }
+
+    #[test]
+    fn reverts_are_subject_to_the_path_policy() {
+        let (conn, dir, base) = setup();
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        let change = stage_create_file(&conn, None, &base, "vendor/lib.rs", "x").unwrap();
+        approve_change(&conn, &change.id).unwrap();
+
+        let policy = serde_json::json!({ "deny": [format!("{}/vendor", base)] }).to_string();
+        conn.execute(
+            "UPDATE settings SET value = ?1 WHERE key = ?2",
+            [&policy, path_policy::PATH_POLICY_SETTING],
+        )
+        .unwrap();
+
+        let err = revert_change(&conn, &change.id).unwrap_err();
+        assert!(err.contains("not allowed"), "{}", err);
+        assert!(dir.path().join("vendor/lib.rs").is_file());
+    }
+}
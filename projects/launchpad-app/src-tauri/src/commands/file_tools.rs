@@ -1,53 +1,42 @@
+use crate::commands::path_policy;
+use crate::commands::walker::ProjectWalker;
+use crate::db::get_db;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 use glob::Pattern;
+use tauri::AppHandle;
 
-/// Sensitive directories that should never be accessed
-const BLOCKED_PATHS: &[&str] = &[
-    "Windows\\System32",
-    "Windows\\SysWOW64",
-    "Program Files",
-    "ProgramData",
-    "\\AppData\\Local\\Microsoft",
-    "\\AppData\\Roaming\\Microsoft",
-    ".ssh",
-    ".gnupg",
-    ".aws",
-    ".azure",
-    "credentials",
-];
-
-/// Validates and canonicalizes a path, blocking sensitive directories
-fn validate_path(path_str: &str) -> Result<PathBuf, String> {
+/// Validates and canonicalizes a path, enforcing the sensitive-path policy
+pub(crate) fn validate_path(app_handle: &AppHandle, path_str: &str) -> Result<PathBuf, String> {
     let path = Path::new(path_str);
 
     // Canonicalize to resolve .. and symlinks
     let canonical = path.canonicalize()
         .map_err(|_| format!("Path does not exist or is not accessible: {}", path_str))?;
 
-    let canonical_str = canonical.to_string_lossy();
-
-    // Block access to sensitive system directories
-    for blocked in BLOCKED_PATHS {
-        if canonical_str.contains(blocked) {
-            log::warn!("Blocked access to sensitive path: {}", canonical_str);
-            return Err("Access to this directory is not allowed".to_string());
-        }
-    }
+    let db = get_db(app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    path_policy::enforce(&conn, &canonical)?;
 
     Ok(canonical)
 }
 
-/// Validates that a target path is within a base directory (prevents traversal)
-fn validate_path_within_base(base_path: &Path, target_path: &str) -> Result<PathBuf, String> {
+/// Validates that a target path is within a base directory (prevents traversal).
+/// The target may not exist yet, in which case its nearest existing ancestor is checked.
+pub(crate) fn validate_path_within_base(base_path: &Path, target_path: &str) -> Result<PathBuf, String> {
     let base_canonical = base_path.canonicalize()
         .map_err(|_| format!("Base path does not exist: {}", base_path.display()))?;
 
     let target = base_path.join(target_path);
-    let target_canonical = target.canonicalize()
-        .map_err(|_| format!("Target path does not exist: {}", target_path))?;
+    let target_canonical = if target.exists() {
+        target.canonicalize()
+            .map_err(|_| format!("Target path is not accessible: {}", target_path))?
+    } else {
+        canonicalize_missing(&target)
+            .ok_or_else(|| format!("Invalid target path: {}", target_path))?
+    };
 
     // Ensure target is within base directory
     if !target_canonical.starts_with(&base_canonical) {
@@ -59,6 +48,28 @@ fn validate_path_within_base(base_path: &Path, target_path: &str) -> Result<Path
     Ok(target_canonical)
 }
 
+/// Canonicalize a path that does not exist yet by resolving its deepest existing
+/// ancestor. Returns None if the missing part contains `..` or other special components.
+fn canonicalize_missing(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+
+    while !existing.exists() {
+        missing.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+
+    let mut resolved = existing.canonicalize().ok()?;
+    for component in missing.into_iter().rev() {
+        if component == ".." || component == "." {
+            return None;
+        }
+        resolved.push(component);
+    }
+
+    Some(resolved)
+}
+
 /// Result of a file search operation
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileSearchResult {
@@ -73,58 +84,129 @@ pub struct GrepResult {
     pub file: String,
     pub line_number: usize,
     pub line_content: String,
+    /// Lines before the match, oldest first
+    pub before: Vec<String>,
+    /// Lines after the match
+    pub after: Vec<String>,
+}
+
+/// Options for `grep_files`; every field is optional
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct GrepOptions {
+    /// Treat the pattern as a regular expression instead of a literal string
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Lines of context before and after each match
+    pub context_lines: usize,
+    /// Globs a file must match (by relative path or file name); empty means all files
+    pub include: Vec<String>,
+    /// Globs that exclude a file
+    pub exclude: Vec<String>,
+    /// Maximum number of matches returned (default: 50)
+    pub max_results: Option<usize>,
+}
+
+/// Number of matches in a single file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileMatchCount {
+    pub file: String,
+    pub count: usize,
 }
 
+/// A file the search did not look inside
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkippedFile {
+    pub file: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GrepResults {
+    pub matches: Vec<GrepResult>,
+    pub file_counts: Vec<FileMatchCount>,
+    pub skipped: Vec<SkippedFile>,
+    pub files_searched: usize,
+    /// More matches exist than were returned
+    pub truncated: bool,
+}
+
+/// Files larger than this are reported as skipped rather than searched
+const MAX_GREP_FILE_SIZE: u64 = 2_000_000;
+/// Matched and context lines longer than this are cut
+const MAX_GREP_LINE_CHARS: usize = 1000;
+
 /// Result of reading a file
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileContent {
     pub path: String,
     pub content: String,
-    pub line_count: usize,
+    /// First line included in `content` (1-based)
+    pub start_line: usize,
+    /// Last line included in `content`; less than `start_line` when nothing was read
+    pub end_line: usize,
+    /// Total lines in the file, when it is small enough to count
+    pub line_count: Option<usize>,
+    pub size: u64,
+    /// The page stopped before the requested range or end of file
     pub truncated: bool,
+    /// Pass back as `cursor` to continue reading where this page stopped
+    pub next_cursor: Option<String>,
+}
+
+impl FileContent {
+    /// Content prefixed with line numbers, for showing to the assistant
+    pub fn numbered_content(&self) -> String {
+        let width = self.end_line.max(1).to_string().len();
+        self.content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| format!("{:>width$}  {}", self.start_line + i, line, width = width))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Options for `read_file`; every field is optional
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ReadFileOptions {
+    /// First line to read (1-based, default: 1)
+    pub start_line: Option<usize>,
+    /// Last line to read, inclusive
+    pub end_line: Option<usize>,
+    /// Maximum number of lines per page (default: 500)
+    pub max_lines: Option<usize>,
+    /// Maximum bytes of content per page (default: 100 KB)
+    pub max_bytes: Option<usize>,
+    /// Continuation cursor from a previous page's `next_cursor`
+    pub cursor: Option<String>,
 }
 
+/// Files up to this size get an exact `line_count`
+const MAX_COUNTED_FILE_SIZE: u64 = 10_000_000;
+/// Bytes sniffed at the start of a file to detect binary or non-UTF-8 content
+const SNIFF_BYTES: usize = 8192;
+
 /// List files in a directory (with optional glob pattern)
 #[tauri::command]
 pub fn list_files(
+    app_handle: AppHandle,
     base_path: String,
     pattern: Option<String>,
     max_depth: Option<usize>,
 ) -> Result<Vec<FileSearchResult>, String> {
     // Validate and canonicalize the path
-    let path = validate_path(&base_path)?;
+    let path = validate_path(&app_handle, &base_path)?;
 
     let depth = max_depth.unwrap_or(3);
     let mut results = Vec::new();
 
     let glob_pattern = pattern.as_ref().and_then(|p| Pattern::new(p).ok());
 
-    for entry in WalkDir::new(&path)
-        .max_depth(depth)
-        .into_iter()
-        .filter_entry(|e| {
-            let name = e.file_name().to_string_lossy();
-            // Skip common ignored directories
-            !name.starts_with("node_modules")
-                && !name.starts_with(".git")
-                && !name.starts_with("target")
-                && !name.starts_with(".next")
-                && !name.starts_with("out")
-                && !name.starts_with("dist")
-        })
-        .filter_map(|e| e.ok())
-    {
-        let entry_path = entry.path();
-        let relative_path = entry_path
-            .strip_prefix(&path)
-            .unwrap_or(entry_path)
-            .to_string_lossy()
-            .to_string();
-
-        // Skip root directory
-        if relative_path.is_empty() {
-            continue;
-        }
+    let walker = ProjectWalker::for_path(&app_handle, &path).max_depth(depth);
+    for entry in walker.walk() {
+        let relative_path = walker.relative(entry.path());
 
         // Apply glob pattern if provided
         if let Some(ref pat) = glob_pattern {
@@ -136,7 +218,7 @@ pub fn list_files(
         let metadata = entry.metadata().ok();
         results.push(FileSearchResult {
             path: relative_path,
-            is_directory: entry.file_type().is_dir(),
+            is_directory: entry.file_type().map(|t| t.is_dir()).unwrap_or(false),
             size: metadata.and_then(|m| if m.is_file() { Some(m.len()) } else { None }),
         });
     }
@@ -146,203 +228,513 @@ pub fn list_files(
     Ok(results)
 }
 
-/// Read file contents
+/// Read file contents, optionally a line range or one page of a large file
 #[tauri::command]
 pub fn read_file(
+    app_handle: AppHandle,
     file_path: String,
-    max_lines: Option<usize>,
+    options: Option<ReadFileOptions>,
 ) -> Result<FileContent, String> {
     // Validate and canonicalize the path
-    let path = validate_path(&file_path)?;
+    let path = validate_path(&app_handle, &file_path)?;
 
     if !path.is_file() {
         return Err("Path is not a file".to_string());
     }
 
-    // Check file size first (limit to 1MB)
-    let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
-    if metadata.len() > 1_000_000 {
-        return Err("File too large (> 1MB). Use grep_files for large files.".to_string());
-    }
+    read_text_file(&path, file_path, options.unwrap_or_default())
+}
 
-    let content = fs::read_to_string(&path)
+/// One page of a text file; `file_path` is echoed back as given by the caller
+fn read_text_file(path: &Path, file_path: String, options: ReadFileOptions) -> Result<FileContent, String> {
+    let mut file = fs::File::open(path)
         .map_err(|e| format!("Failed to read file: {}", e))?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
 
-    let lines: Vec<&str> = content.lines().collect();
-    let line_count = lines.len();
-    let max = max_lines.unwrap_or(500);
+    check_text_file(&mut file)?;
 
-    let (final_content, truncated) = if line_count > max {
-        (lines[..max].join("\n"), true)
-    } else {
-        (content, false)
+    // A cursor is "<byte offset>:<line number>" of the next unread line, plus
+    // ":<line count>" once the file has been counted so later pages skip it
+    let (offset, mut line_number, known_line_count) = match options.cursor.as_deref() {
+        Some(cursor) => parse_cursor(cursor)?,
+        None => (0, 1, None),
+    };
+    let start_line = options.start_line.unwrap_or(1).max(line_number).max(1);
+    let max_lines = options.max_lines.unwrap_or(500).max(1);
+    let max_bytes = options.max_bytes.unwrap_or(100_000).max(1);
+
+    file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(file);
+    let mut position = offset;
+    let mut buf = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut content_bytes = 0;
+    let mut truncated = false;
+    let mut next_line = None;
+
+    loop {
+        if options.end_line.is_some_and(|end| line_number > end) {
+            break;
+        }
+        if lines.len() >= max_lines || content_bytes >= max_bytes {
+            if position < size {
+                truncated = true;
+                next_line = Some((position, line_number));
+            }
+            break;
+        }
+
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf).map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        position += read as u64;
+
+        if line_number >= start_line {
+            let text = String::from_utf8_lossy(&buf);
+            let mut line = text.trim_end_matches(['\n', '\r']).to_string();
+            // A single enormous line (minified JS, say) must not blow the page budget
+            if line.len() > max_bytes {
+                let mut cut = max_bytes;
+                while !line.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                line.truncate(cut);
+                line.push_str(" [line truncated]");
+            }
+            content_bytes += line.len() + 1;
+            lines.push(line);
+        }
+        line_number += 1;
+    }
+
+    // Finish counting from where the page stopped instead of re-reading the file
+    let line_count = match known_line_count {
+        Some(count) => Some(count),
+        None if size <= MAX_COUNTED_FILE_SIZE => {
+            let remaining = count_remaining_lines(&mut reader).map_err(|e| format!("Failed to read file: {}", e))?;
+            Some(line_number - 1 + remaining)
+        }
+        None => None,
     };
+    let next_cursor = next_line.map(|(position, line)| match line_count {
+        Some(count) => format!("{}:{}:{}", position, line, count),
+        None => format!("{}:{}", position, line),
+    });
 
     Ok(FileContent {
         path: file_path,
-        content: final_content,
+        start_line,
+        end_line: start_line + lines.len() - 1,
+        content: lines.join("\n"),
         line_count,
+        size,
         truncated,
+        next_cursor,
+    })
+}
+
+/// Reject binary and non-UTF-8 files before reading them as text
+fn check_text_file(file: &mut fs::File) -> Result<(), String> {
+    let mut sample = vec![0u8; SNIFF_BYTES];
+    let read = file.read(&mut sample).map_err(|e| format!("Failed to read file: {}", e))?;
+    let sample = &sample[..read];
+
+    if is_binary(sample) {
+        return Err("Binary file; cannot display as text".to_string());
+    }
+    if let Err(e) = std::str::from_utf8(sample) {
+        // error_len() is None when the sample merely ends mid-character
+        if e.error_len().is_some() {
+            return Err(format!(
+                "File is not valid UTF-8 (invalid byte at offset {}); it may use a different encoding",
+                e.valid_up_to()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_cursor(cursor: &str) -> Result<(u64, usize, Option<usize>), String> {
+    let invalid = || format!("Invalid cursor: {}", cursor);
+    let parts: Vec<&str> = cursor.split(':').collect();
+    let (offset, line, count) = match parts.as_slice() {
+        [offset, line] => (offset, line, None),
+        [offset, line, count] => (offset, line, Some(count.parse().map_err(|_| invalid())?)),
+        _ => return Err(invalid()),
+    };
+    // Lines are numbered from 1
+    let line = line.parse().ok().filter(|&line| line > 0).ok_or_else(invalid)?;
+    Ok((offset.parse().map_err(|_| invalid())?, line, count))
+}
+
+/// Lines from the reader's position to the end of the file
+fn count_remaining_lines(reader: &mut impl BufRead) -> std::io::Result<usize> {
+    let mut newlines = 0;
+    let mut last = None;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        newlines += buf.iter().filter(|&&b| b == b'\n').count();
+        last = buf.last().copied();
+        let len = buf.len();
+        reader.consume(len);
+    }
+    Ok(match last {
+        Some(b'\n') | None => newlines,
+        Some(_) => newlines + 1,
     })
 }
 
 /// Search file contents (grep-like)
 #[tauri::command]
 pub fn grep_files(
+    app_handle: AppHandle,
     base_path: String,
     search_pattern: String,
-    file_pattern: Option<String>,
-    max_results: Option<usize>,
-) -> Result<Vec<GrepResult>, String> {
+    options: Option<GrepOptions>,
+) -> Result<GrepResults, String> {
     // Validate and canonicalize the path
-    let path = validate_path(&base_path)?;
+    let path = validate_path(&app_handle, &base_path)?;
+    let options = options.unwrap_or_default();
 
-    let max = max_results.unwrap_or(50);
-    let mut results = Vec::new();
-    let search_lower = search_pattern.to_lowercase();
-
-    let file_glob = file_pattern.as_ref().and_then(|p| Pattern::new(p).ok());
-
-    for entry in WalkDir::new(&path)
-        .max_depth(10)
-        .into_iter()
-        .filter_entry(|e| {
-            let name = e.file_name().to_string_lossy();
-            !name.starts_with("node_modules")
-                && !name.starts_with(".git")
-                && !name.starts_with("target")
-                && !name.starts_with(".next")
-                && !name.starts_with("out")
-                && !name.starts_with("dist")
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        if results.len() >= max {
-            break;
-        }
+    let pattern = if options.regex {
+        search_pattern.clone()
+    } else {
+        regex::escape(&search_pattern)
+    };
+    let matcher = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid regex: {}", e))?;
+
+    let include = compile_globs(&options.include)?;
+    let exclude = compile_globs(&options.exclude)?;
+    let max = options.max_results.unwrap_or(50);
+    let context = options.context_lines;
+
+    let mut results = GrepResults {
+        matches: Vec::new(),
+        file_counts: Vec::new(),
+        skipped: Vec::new(),
+        files_searched: 0,
+        truncated: false,
+    };
 
+    let walker = ProjectWalker::for_path(&app_handle, &path).max_depth(10);
+    for entry in walker.files() {
         let entry_path = entry.path();
+        let relative_path = walker.relative(entry_path);
         let file_name = entry.file_name().to_string_lossy();
 
-        // Apply file pattern filter
-        if let Some(ref pat) = file_glob {
-            if !pat.matches(&file_name) {
+        let glob_matches = |globs: &[Pattern]| {
+            globs.iter().any(|g| g.matches(&relative_path) || g.matches(&file_name))
+        };
+        if (!include.is_empty() && !glob_matches(&include)) || glob_matches(&exclude) {
+            continue;
+        }
+
+        let mut skip = |reason: &str| {
+            results.skipped.push(SkippedFile {
+                file: relative_path.clone(),
+                reason: reason.to_string(),
+            });
+        };
+
+        match entry.metadata() {
+            Ok(metadata) if metadata.len() > MAX_GREP_FILE_SIZE => {
+                skip("too large");
+                continue;
+            }
+            Ok(_) => {}
+            Err(_) => {
+                skip("unreadable");
                 continue;
             }
         }
 
-        // Skip binary files and very large files
-        if let Ok(metadata) = entry.metadata() {
-            if metadata.len() > 500_000 {
+        let bytes = match fs::read(entry_path) {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                skip("unreadable");
                 continue;
             }
+        };
+        if is_binary(&bytes) {
+            skip("binary");
+            continue;
         }
 
-        // Try to read and search the file
-        if let Ok(content) = fs::read_to_string(entry_path) {
-            for (line_num, line) in content.lines().enumerate() {
-                if line.to_lowercase().contains(&search_lower) {
-                    let relative_path = entry_path
-                        .strip_prefix(&path)
-                        .unwrap_or(entry_path)
-                        .to_string_lossy()
-                        .to_string();
-
-                    results.push(GrepResult {
-                        file: relative_path,
-                        line_number: line_num + 1,
-                        line_content: line.chars().take(200).collect(),
-                    });
-
-                    if results.len() >= max {
-                        break;
-                    }
-                }
+        results.files_searched += 1;
+        let content = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = content.lines().collect();
+        let mut count = 0;
+
+        for (index, line) in lines.iter().enumerate() {
+            if !matcher.is_match(line) {
+                continue;
             }
+            count += 1;
+
+            if results.matches.len() >= max {
+                results.truncated = true;
+                continue;
+            }
+
+            let before_start = index.saturating_sub(context);
+            let after_end = (index + 1 + context).min(lines.len());
+            results.matches.push(GrepResult {
+                file: relative_path.clone(),
+                line_number: index + 1,
+                line_content: clip_line(line),
+                before: lines[before_start..index].iter().map(|l| clip_line(l)).collect(),
+                after: lines[index + 1..after_end].iter().map(|l| clip_line(l)).collect(),
+            });
+        }
+
+        if count > 0 {
+            results.file_counts.push(FileMatchCount {
+                file: relative_path,
+                count,
+            });
         }
     }
 
     Ok(results)
 }
 
+fn compile_globs(globs: &[String]) -> Result<Vec<Pattern>, String> {
+    globs
+        .iter()
+        .map(|g| Pattern::new(g).map_err(|e| format!("Invalid glob '{}': {}", g, e)))
+        .collect()
+}
+
+/// Treat a file as binary if it has a NUL byte near the start, like git does
+pub(crate) fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8000).any(|&b| b == 0)
+}
+
+fn clip_line(line: &str) -> String {
+    if line.chars().count() > MAX_GREP_LINE_CHARS {
+        let clipped: String = line.chars().take(MAX_GREP_LINE_CHARS).collect();
+        format!("{}…", clipped)
+    } else {
+        line.to_string()
+    }
+}
+
 /// Get directory tree structure
 #[tauri::command]
 pub fn get_directory_tree(
+    app_handle: AppHandle,
     base_path: String,
     max_depth: Option<usize>,
 ) -> Result<String, String> {
     // Validate and canonicalize the path
-    let path = validate_path(&base_path)?;
+    let path = validate_path(&app_handle, &base_path)?;
 
     let depth = max_depth.unwrap_or(3);
     let mut tree = String::new();
 
-    build_tree(&path, "", depth, &mut tree, true);
+    let walker = ProjectWalker::for_path(&app_handle, &path).max_depth(depth);
+    build_tree(&walker, &mut tree);
 
     Ok(tree)
 }
 
-fn build_tree(path: &Path, prefix: &str, remaining_depth: usize, output: &mut String, is_last: bool) {
-    let name = path.file_name()
+fn build_tree(walker: &ProjectWalker, output: &mut String) {
+    let root = walker.root();
+    let root_name = root.file_name()
         .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| path.to_string_lossy().to_string());
-
-    // Skip ignored directories
-    if name.starts_with("node_modules")
-        || name.starts_with(".git")
-        || name.starts_with("target")
-        || name.starts_with(".next")
-        || name == "out"
-        || name == "dist"
-    {
-        return;
+        .unwrap_or_else(|| root.to_string_lossy().to_string());
+    output.push_str(&format!("└── {}/\n", root_name));
+
+    // The walker yields entries depth-first, directories before files
+    let entries: Vec<(usize, String)> = walker
+        .walk()
+        .map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let is_dir = e.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            (e.depth(), if is_dir { format!("{}/", name) } else { name })
+        })
+        .collect();
+
+    // An entry is the last child if no sibling follows before we climb back up
+    let mut is_last = vec![false; entries.len()];
+    let mut sibling_follows: Vec<bool> = Vec::new();
+    for (i, (depth, _)) in entries.iter().enumerate().rev() {
+        sibling_follows.resize(depth + 1, false);
+        is_last[i] = !sibling_follows[*depth];
+        sibling_follows[*depth] = true;
     }
 
-    let connector = if is_last { "└── " } else { "├── " };
-    let display_name = if path.is_dir() {
-        format!("{}/", name)
-    } else {
-        name
-    };
+    // Whether each ancestor (starting with the root) was the last child
+    let mut ancestors_last = vec![true];
+    for (i, (depth, name)) in entries.iter().enumerate() {
+        ancestors_last.truncate(*depth);
+        let prefix: String = ancestors_last
+            .iter()
+            .map(|&last| if last { "    " } else { "│   " })
+            .collect();
+        let connector = if is_last[i] { "└── " } else { "├── " };
+        output.push_str(&format!("{}{}{}\n", prefix, connector, name));
+        ancestors_last.push(is_last[i]);
+    }
+}
 
-    output.push_str(&format!("{}{}{}\n", prefix, connector, display_name));
-
-    if path.is_dir() && remaining_depth > 0 {
-        if let Ok(entries) = fs::read_dir(path) {
-            let mut entries: Vec<_> = entries
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    let name = e.file_name().to_string_lossy().to_string();
-                    !name.starts_with("node_modules")
-                        && !name.starts_with(".git")
-                        && !name.starts_with("target")
-                        && !name.starts_with(".next")
-                        && name != "out"
-                        && name != "dist"
-                })
-                .collect();
-
-            entries.sort_by(|a, b| {
-                let a_is_dir = a.file_type().map(|t| t.is_dir()).unwrap_or(false);
-                let b_is_dir = b.file_type().map(|t| t.is_dir()).unwrap_or(false);
-                match (a_is_dir, b_is_dir) {
-                    (true, false) => std::cmp::Ordering::Less,
-                    (false, true) => std::cmp::Ordering::Greater,
-                    _ => a.file_name().cmp(&b.file_name()),
-                }
-            });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
 
-            let child_prefix = if is_last {
-                format!("{}    ", prefix)
-            } else {
-                format!("{}│   ", prefix)
-            };
+    fn file_with(content: &[u8]) -> (TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, content).unwrap();
+        (dir, path)
+    }
+
+    fn read(path: &Path, options: ReadFileOptions) -> Result<FileContent, String> {
+        read_text_file(path, "file.txt".to_string(), options)
+    }
+
+    fn numbered_lines(count: usize) -> String {
+        (1..=count).map(|n| format!("line {}\n", n)).collect()
+    }
 
-            let len = entries.len();
-            for (i, entry) in entries.into_iter().enumerate() {
-                build_tree(&entry.path(), &child_prefix, remaining_depth - 1, output, i == len - 1);
+    #[test]
+    fn cursors_page_through_the_whole_file() {
+        let (_dir, path) = file_with(numbered_lines(23).as_bytes());
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = read(
+                &path,
+                ReadFileOptions {
+                    max_lines: Some(5),
+                    cursor: cursor.take(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(page.line_count, Some(23));
+            assert_eq!(page.start_line, pages.len() * 5 + 1);
+            pages.push(page.content);
+            match page.next_cursor {
+                Some(next) => {
+                    assert!(page.truncated);
+                    // The count rides along so later pages skip recounting
+                    assert_eq!(next.split(':').count(), 3);
+                    cursor = Some(next);
+                }
+                None => {
+                    assert!(!page.truncated);
+                    assert_eq!(page.end_line, 23);
+                    break;
+                }
             }
         }
+        assert_eq!(pages.len(), 5);
+        assert_eq!(pages.join("\n") + "\n", numbered_lines(23));
+    }
+
+    #[test]
+    fn line_ranges_are_inclusive() {
+        let (_dir, path) = file_with(b"one\r\ntwo\r\nthree\r\nfour");
+        let page = read(
+            &path,
+            ReadFileOptions {
+                start_line: Some(2),
+                end_line: Some(3),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(page.content, "two\nthree");
+        assert_eq!((page.start_line, page.end_line), (2, 3));
+        assert_eq!(page.line_count, Some(4));
+        assert!(!page.truncated);
+        assert!(page.next_cursor.is_none());
+
+        let whole = read(&path, ReadFileOptions::default()).unwrap();
+        assert_eq!(whole.content, "one\ntwo\nthree\nfour");
+        assert_eq!(whole.line_count, Some(4));
+    }
+
+    #[test]
+    fn line_zero_is_treated_as_the_first_line() {
+        let (_dir, path) = file_with(b"one\ntwo\n");
+        let page = read(
+            &path,
+            ReadFileOptions {
+                start_line: Some(0),
+                end_line: Some(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(page.content, "one");
+        assert_eq!((page.start_line, page.end_line), (1, 1));
+    }
+
+    #[test]
+    fn reading_past_the_end_returns_nothing() {
+        let (_dir, path) = file_with(b"one\ntwo\n");
+        let page = read(
+            &path,
+            ReadFileOptions {
+                start_line: Some(10),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(page.content, "");
+        assert!(page.end_line < page.start_line);
+        assert_eq!(page.line_count, Some(2));
+        assert!(!page.truncated);
+
+        let (_dir, empty) = file_with(b"");
+        let page = read(&empty, ReadFileOptions::default()).unwrap();
+        assert_eq!(page.content, "");
+        assert_eq!(page.line_count, Some(0));
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        let (_dir, path) = file_with(b"one\ntwo\n");
+        for cursor in ["", "4", "x:1", "4:y", "4:0", "0:0:2", "4:2:z", "4:2:3:4"] {
+            let options = ReadFileOptions {
+                cursor: Some(cursor.to_string()),
+                ..Default::default()
+            };
+            assert!(read(&path, options).is_err(), "{:?}", cursor);
+        }
+
+        let page = read(
+            &path,
+            ReadFileOptions {
+                cursor: Some("4:2".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(page.content, "two");
+        assert_eq!(page.start_line, 2);
+    }
+
+    #[test]
+    fn binary_and_non_utf8_files_are_rejected() {
+        let (_dir, binary) = file_with(b"\x7fELF\x02\x01\x01\x00\x00\x00");
+        let err = read(&binary, ReadFileOptions::default()).unwrap_err();
+        assert!(err.starts_with("Binary file"), "{}", err);
+
+        let (_dir, latin1) = file_with(b"caf\xe9 au lait\n");
+        let err = read(&latin1, ReadFileOptions::default()).unwrap_err();
+        assert!(err.contains("not valid UTF-8"), "{}", err);
     }
 }
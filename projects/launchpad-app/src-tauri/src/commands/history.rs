@@ -0,0 +1,285 @@
+use crate::commands::analyzer::ProjectAnalysis;
+use crate::commands::inventory::{diff_inventory, load_snapshot, InventoryDiff};
+use crate::db::get_db;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tauri::AppHandle;
+
+/// Summary of one saved analysis; the full report is kept alongside it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalysisSnapshot {
+    pub id: i64,
+    pub project_id: String,
+    pub analyzed_at: String,
+    pub phase: i32,
+    pub phase_name: String,
+    pub file_count: i64,
+    pub has_tests: bool,
+    pub services: Vec<String>,
+    pub recommendation_count: i64,
+    pub code_lines: i64,
+    pub comment_lines: i64,
+    pub test_to_source_ratio: Option<f64>,
+    /// Code lines in test files; `None` for analyses saved before they were counted
+    pub test_lines: Option<i64>,
+    /// Code lines per language
+    pub languages: BTreeMap<String, i64>,
+}
+
+/// What changed between two saved analyses (usually older first)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalysisDiff {
+    pub from: AnalysisSnapshot,
+    pub to: AnalysisSnapshot,
+    /// Positive when the project moved forward
+    pub phase_change: i32,
+    pub file_count_change: i64,
+    pub code_lines_change: i64,
+    pub services_added: Vec<String>,
+    pub services_removed: Vec<String>,
+    /// `None` when either analysis predates test line counts
+    pub test_lines_change: Option<i64>,
+    /// `None` when either analysis predates dependency snapshots
+    pub dependencies: Option<InventoryDiff>,
+}
+
+const SNAPSHOT_COLUMNS: &str = "id, project_id, analyzed_at, phase, phase_name, file_count, has_tests, services,
+     recommendation_count, code_lines, comment_lines, test_to_source_ratio, test_lines, languages";
+
+fn row_to_snapshot(row: &Row) -> rusqlite::Result<AnalysisSnapshot> {
+    let services: String = row.get(7)?;
+    let languages: String = row.get(13)?;
+    Ok(AnalysisSnapshot {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        analyzed_at: row.get(2)?,
+        phase: row.get(3)?,
+        phase_name: row.get(4)?,
+        file_count: row.get(5)?,
+        has_tests: row.get(6)?,
+        services: serde_json::from_str(&services).unwrap_or_default(),
+        recommendation_count: row.get(8)?,
+        code_lines: row.get(9)?,
+        comment_lines: row.get(10)?,
+        test_to_source_ratio: row.get(11)?,
+        test_lines: row.get(12)?,
+        languages: serde_json::from_str(&languages).unwrap_or_default(),
+    })
+}
+
+/// Append a saved analysis to the project's history, returning its row id
+pub fn record_analysis(
+    conn: &Connection,
+    project_id: &str,
+    analyzed_at: &str,
+    analysis: &ProjectAnalysis,
+) -> rusqlite::Result<i64> {
+    let services = serde_json::to_string(&analysis.detected_services).unwrap_or_else(|_| "[]".to_string());
+    let stats = &analysis.code_stats;
+    let languages: BTreeMap<&str, usize> = stats.languages.iter().map(|l| (l.language.as_str(), l.code)).collect();
+    let languages = serde_json::to_string(&languages).unwrap_or_else(|_| "{}".to_string());
+    let report = serde_json::to_string(analysis).unwrap_or_default();
+
+    conn.execute(
+        "INSERT INTO project_analyses
+         (project_id, analyzed_at, phase, phase_name, file_count, has_tests, services, recommendation_count,
+          code_lines, comment_lines, test_to_source_ratio, test_lines, languages, report)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        rusqlite::params![
+            project_id,
+            analyzed_at,
+            analysis.sop_progress.estimated_phase,
+            &analysis.sop_progress.phase_name,
+            analysis.file_count as i64,
+            analysis.has_tests,
+            services,
+            analysis.recommendations.len() as i64,
+            stats.code as i64,
+            stats.comments as i64,
+            stats.test_to_source_ratio,
+            stats.test_code as i64,
+            languages,
+            report,
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+fn load_analysis(conn: &Connection, analysis_id: i64) -> Result<AnalysisSnapshot, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM project_analyses WHERE id = ?1", SNAPSHOT_COLUMNS),
+        [analysis_id],
+        row_to_snapshot,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Analysis {} not found", analysis_id))
+}
+
+fn dependency_snapshot_for(conn: &Connection, analysis_id: i64) -> Result<Option<i64>, String> {
+    conn.query_row(
+        "SELECT id FROM dependency_snapshots WHERE analysis_id = ?1",
+        [analysis_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Saved analyses for a project, oldest first. `limit` keeps the most recent ones.
+#[tauri::command]
+pub fn get_analysis_history(
+    app_handle: AppHandle,
+    project_id: String,
+    limit: Option<i64>,
+) -> Result<Vec<AnalysisSnapshot>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM project_analyses
+             WHERE project_id = ?1
+             ORDER BY analyzed_at DESC, id DESC
+             LIMIT ?2",
+            SNAPSHOT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut history = stmt
+        .query_map(rusqlite::params![&project_id, limit.unwrap_or(-1)], row_to_snapshot)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    history.reverse();
+
+    Ok(history)
+}
+
+#[tauri::command]
+pub fn diff_analyses(app_handle: AppHandle, from_id: i64, to_id: i64) -> Result<AnalysisDiff, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    diff(&conn, from_id, to_id)
+}
+
+fn diff(conn: &Connection, from_id: i64, to_id: i64) -> Result<AnalysisDiff, String> {
+    let from = load_analysis(conn, from_id)?;
+    let to = load_analysis(conn, to_id)?;
+
+    let before: BTreeSet<&String> = from.services.iter().collect();
+    let after: BTreeSet<&String> = to.services.iter().collect();
+    let services_added = after.difference(&before).map(|s| s.to_string()).collect();
+    let services_removed = before.difference(&after).map(|s| s.to_string()).collect();
+
+    let dependencies = match (dependency_snapshot_for(conn, from_id)?, dependency_snapshot_for(conn, to_id)?) {
+        (Some(a), Some(b)) => Some(diff_inventory(load_snapshot(conn, a)?, load_snapshot(conn, b)?)),
+        _ => None,
+    };
+
+    Ok(AnalysisDiff {
+        phase_change: to.phase - from.phase,
+        file_count_change: to.file_count - from.file_count,
+        code_lines_change: to.code_lines - from.code_lines,
+        services_added,
+        services_removed,
+        test_lines_change: from.test_lines.zip(to.test_lines).map(|(before, after)| after - before),
+        dependencies,
+        from,
+        to,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{CodeStats, DependencyInventory, EnvContract, ResolvedDependency};
+    use crate::commands::inventory::save_inventory;
+    use serde_json::json;
+
+    fn analysis(phase: i32, services: &[&str], code: usize, test_code: usize) -> ProjectAnalysis {
+        let mut analysis: ProjectAnalysis = serde_json::from_value(json!({
+            "project_path": "",
+            "tech_stack": [],
+            "frameworks": [],
+            "has_git": false,
+            "has_tests": test_code > 0,
+            "has_ci": false,
+            "env_contract": EnvContract::default(),
+            "file_count": code / 10,
+            "directory_structure": [],
+            "detected_services": services,
+            "package_json": null,
+            "cargo_toml": null,
+            "recommendations": [],
+            "sop_progress": { "estimated_phase": phase, "phase_name": format!("Phase {}", phase), "evidence": [] },
+        }))
+        .unwrap();
+        analysis.code_stats = CodeStats {
+            code,
+            test_code,
+            ..Default::default()
+        };
+        analysis
+    }
+
+    fn dependency(name: &str, version: &str) -> ResolvedDependency {
+        ResolvedDependency {
+            ecosystem: "npm".to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+            direct: true,
+        }
+    }
+
+    fn setup() -> Connection {
+        let conn = crate::db::open_in_memory();
+        conn.execute("INSERT INTO projects (id, name, slug) VALUES ('p1', 'App', 'app')", [])
+            .unwrap();
+        conn
+    }
+
+    #[test]
+    fn diff_compares_two_saved_analyses() {
+        let conn = setup();
+        let from = record_analysis(&conn, "p1", "2024-01-01T00:00:00Z", &analysis(2, &["Auth", "Database"], 1000, 0))
+            .unwrap();
+        let to = record_analysis(&conn, "p1", "2024-02-01T00:00:00Z", &analysis(4, &["Database", "Payments"], 1500, 300))
+            .unwrap();
+        let inventory = |deps| DependencyInventory { lockfiles: vec!["package-lock.json".to_string()], dependencies: deps };
+        save_inventory(&conn, "p1", "2024-01-01T00:00:00Z", from, &inventory(vec![dependency("react", "18.2.0")])).unwrap();
+        save_inventory(&conn, "p1", "2024-02-01T00:00:00Z", to, &inventory(vec![dependency("react", "18.3.1")])).unwrap();
+
+        let diff = diff(&conn, from, to).unwrap();
+        assert_eq!(diff.phase_change, 2);
+        assert_eq!(diff.file_count_change, 50);
+        assert_eq!(diff.code_lines_change, 500);
+        assert_eq!(diff.services_added, ["Payments"]);
+        assert_eq!(diff.services_removed, ["Auth"]);
+        assert_eq!(diff.test_lines_change, Some(300));
+        assert_eq!(diff.dependencies.unwrap().changed.len(), 1);
+    }
+
+    #[test]
+    fn older_analyses_have_no_test_or_dependency_diff() {
+        let conn = setup();
+        let from = record_analysis(&conn, "p1", "2024-01-01T00:00:00Z", &analysis(2, &[], 100, 40)).unwrap();
+        let to = record_analysis(&conn, "p1", "2024-02-01T00:00:00Z", &analysis(2, &[], 100, 10)).unwrap();
+        assert_eq!(diff(&conn, from, to).unwrap().test_lines_change, Some(-30));
+
+        conn.execute("UPDATE project_analyses SET test_lines = NULL WHERE id = ?1", [from]).unwrap();
+        let diff = diff(&conn, from, to).unwrap();
+        assert_eq!(diff.from.test_lines, None);
+        assert_eq!(diff.test_lines_change, None);
+        assert!(diff.dependencies.is_none());
+    }
+
+    #[test]
+    fn missing_analyses_are_reported() {
+        let conn = setup();
+        let id = record_analysis(&conn, "p1", "2024-01-01T00:00:00Z", &analysis(1, &[], 0, 0)).unwrap();
+        assert_eq!(diff(&conn, id, 99).unwrap_err(), "Analysis 99 not found");
+    }
+}
@@ -0,0 +1,301 @@
+use crate::analysis::{DependencyInventory, ResolvedDependency};
+use crate::db::get_db;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tauri::AppHandle;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencySnapshot {
+    pub id: i64,
+    pub project_id: String,
+    pub analyzed_at: String,
+    pub dependency_count: i64,
+}
+
+/// A project whose latest saved analysis resolves a matching dependency
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencyUsage {
+    pub project_id: String,
+    pub project_name: String,
+    pub snapshot_id: i64,
+    pub analyzed_at: String,
+    pub dependency: ResolvedDependency,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VersionChange {
+    pub ecosystem: String,
+    pub name: String,
+    pub from: Vec<String>,
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InventoryDiff {
+    pub added: Vec<ResolvedDependency>,
+    pub removed: Vec<ResolvedDependency>,
+    pub changed: Vec<VersionChange>,
+}
+
+/// Store the inventory of a saved analysis
+pub fn save_inventory(
+    conn: &Connection,
+    project_id: &str,
+    analyzed_at: &str,
+    analysis_id: i64,
+    inventory: &DependencyInventory,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO dependency_snapshots (project_id, analyzed_at, analysis_id) VALUES (?1, ?2, ?3)",
+        rusqlite::params![project_id, analyzed_at, analysis_id],
+    )?;
+    let snapshot_id = conn.last_insert_rowid();
+
+    let mut stmt = conn.prepare(
+        "INSERT INTO snapshot_dependencies (snapshot_id, ecosystem, name, version, direct)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for dep in &inventory.dependencies {
+        stmt.execute(rusqlite::params![snapshot_id, &dep.ecosystem, &dep.name, &dep.version, dep.direct])?;
+    }
+
+    Ok(())
+}
+
+pub fn load_snapshot(conn: &Connection, snapshot_id: i64) -> Result<Vec<ResolvedDependency>, String> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM dependency_snapshots WHERE id = ?1",
+            [snapshot_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(format!("Dependency snapshot {} not found", snapshot_id));
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT ecosystem, name, version, direct FROM snapshot_dependencies WHERE snapshot_id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    let deps = stmt
+        .query_map([snapshot_id], |row| {
+            Ok(ResolvedDependency {
+                ecosystem: row.get(0)?,
+                name: row.get(1)?,
+                version: row.get(2)?,
+                direct: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(deps)
+}
+
+/// `14` matches `14.2.3` but not `140.0.0`; a full version matches exactly
+fn version_matches(version: &str, query: &str) -> bool {
+    version == query || version.starts_with(&format!("{}.", query))
+}
+
+#[tauri::command]
+pub fn list_dependency_snapshots(app_handle: AppHandle, project_id: String) -> Result<Vec<DependencySnapshot>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.id, s.project_id, s.analyzed_at, COUNT(d.snapshot_id)
+             FROM dependency_snapshots s
+             LEFT JOIN snapshot_dependencies d ON d.snapshot_id = s.id
+             WHERE s.project_id = ?1
+             GROUP BY s.id
+             ORDER BY s.analyzed_at DESC, s.id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let snapshots = stmt
+        .query_map([&project_id], |row| {
+            Ok(DependencySnapshot {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                analyzed_at: row.get(2)?,
+                dependency_count: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(snapshots)
+}
+
+/// Which projects use a dependency, e.g. `next` at version `14`, going by each
+/// project's most recent saved analysis
+#[tauri::command]
+pub fn find_dependency_usage(
+    app_handle: AppHandle,
+    name: String,
+    version: Option<String>,
+) -> Result<Vec<DependencyUsage>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.name, s.id, s.analyzed_at, d.ecosystem, d.name, d.version, d.direct
+             FROM snapshot_dependencies d
+             JOIN dependency_snapshots s ON s.id = d.snapshot_id
+             JOIN projects p ON p.id = s.project_id
+             WHERE d.name = ?1
+               AND s.id = (SELECT id FROM dependency_snapshots
+                           WHERE project_id = s.project_id
+                           ORDER BY analyzed_at DESC, id DESC LIMIT 1)
+             ORDER BY p.name, d.version",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let usages = stmt
+        .query_map([&name], |row| {
+            Ok(DependencyUsage {
+                project_id: row.get(0)?,
+                project_name: row.get(1)?,
+                snapshot_id: row.get(2)?,
+                analyzed_at: row.get(3)?,
+                dependency: ResolvedDependency {
+                    ecosystem: row.get(4)?,
+                    name: row.get(5)?,
+                    version: row.get(6)?,
+                    direct: row.get(7)?,
+                },
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(match version {
+        Some(v) => usages.into_iter().filter(|u| version_matches(&u.dependency.version, &v)).collect(),
+        None => usages,
+    })
+}
+
+/// Compare two inventories (older first). A package whose set of resolved
+/// versions differs is reported as changed rather than as an add plus a remove.
+pub fn diff_inventory(before: Vec<ResolvedDependency>, after: Vec<ResolvedDependency>) -> InventoryDiff {
+    let group = |deps: Vec<ResolvedDependency>| {
+        let mut grouped: BTreeMap<(String, String), Vec<ResolvedDependency>> = BTreeMap::new();
+        for dep in deps {
+            grouped.entry((dep.ecosystem.clone(), dep.name.clone())).or_default().push(dep);
+        }
+        grouped
+    };
+    let before = group(before);
+    let mut after = group(after);
+
+    let mut diff = InventoryDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+
+    for (key, old) in before {
+        let Some(new) = after.remove(&key) else {
+            diff.removed.extend(old);
+            continue;
+        };
+        let versions = |deps: &[ResolvedDependency]| -> BTreeSet<String> {
+            deps.iter().map(|d| d.version.clone()).collect()
+        };
+        let (from, to) = (versions(&old), versions(&new));
+        if from != to {
+            diff.changed.push(VersionChange {
+                ecosystem: key.0,
+                name: key.1,
+                from: from.into_iter().collect(),
+                to: to.into_iter().collect(),
+            });
+        }
+    }
+    diff.added = after.into_values().flatten().collect();
+
+    diff
+}
+
+#[tauri::command]
+pub fn diff_dependency_snapshots(app_handle: AppHandle, from_id: i64, to_id: i64) -> Result<InventoryDiff, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    Ok(diff_inventory(load_snapshot(&conn, from_id)?, load_snapshot(&conn, to_id)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(ecosystem: &str, name: &str, version: &str) -> ResolvedDependency {
+        ResolvedDependency {
+            ecosystem: ecosystem.to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+            direct: false,
+        }
+    }
+
+    #[test]
+    fn inventories_diff_by_ecosystem_and_name() {
+        let before = vec![
+            dependency("npm", "react", "18.2.0"),
+            dependency("npm", "left-pad", "1.3.0"),
+            dependency("npm", "semver", "6.3.1"),
+            dependency("npm", "semver", "7.5.4"),
+            dependency("cargo", "serde", "1.0.190"),
+        ];
+        let after = vec![
+            dependency("npm", "react", "18.3.1"),
+            dependency("npm", "semver", "7.5.4"),
+            dependency("npm", "semver", "6.3.1"),
+            dependency("cargo", "serde", "1.0.190"),
+            // Same name in another ecosystem is a different package
+            dependency("cargo", "react", "0.1.0"),
+        ];
+
+        let diff = diff_inventory(before, after);
+        let names = |deps: &[ResolvedDependency]| -> Vec<String> {
+            deps.iter().map(|d| format!("{}:{}@{}", d.ecosystem, d.name, d.version)).collect()
+        };
+        assert_eq!(names(&diff.added), ["cargo:react@0.1.0"]);
+        assert_eq!(names(&diff.removed), ["npm:left-pad@1.3.0"]);
+        // Versions compare as sets, so reordering `semver` isn't a change
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, "react");
+        assert_eq!(diff.changed[0].from, ["18.2.0"]);
+        assert_eq!(diff.changed[0].to, ["18.3.1"]);
+    }
+
+    #[test]
+    fn snapshots_round_trip() {
+        let conn = crate::db::open_in_memory();
+        conn.execute("INSERT INTO projects (id, name, slug) VALUES ('p1', 'App', 'app')", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO project_analyses (project_id, analyzed_at, phase, phase_name, file_count, report)
+             VALUES ('p1', '2024-01-01T00:00:00Z', 1, 'Idea', 0, '{}')",
+            [],
+        )
+        .unwrap();
+        let inventory = DependencyInventory {
+            lockfiles: vec!["Cargo.lock".to_string()],
+            dependencies: vec![dependency("cargo", "serde", "1.0.190")],
+        };
+        save_inventory(&conn, "p1", "2024-01-01T00:00:00Z", conn.last_insert_rowid(), &inventory).unwrap();
+
+        let loaded = load_snapshot(&conn, 1).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!((loaded[0].name.as_str(), loaded[0].version.as_str()), ("serde", "1.0.190"));
+        assert_eq!(load_snapshot(&conn, 2).unwrap_err(), "Dependency snapshot 2 not found");
+    }
+}
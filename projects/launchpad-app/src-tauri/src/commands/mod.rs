@@ -1,19 +1,36 @@
 pub mod projects;
 pub mod chat;
 pub mod analyzer;
+pub mod history;
+pub mod inventory;
 pub mod settings;
 pub mod file_tools;
+pub mod walker;
+pub mod path_policy;
+pub mod symbols;
+pub mod file_changes;
+pub mod command_runner;
 pub mod ideas;
+pub mod recommendations;
 pub mod sops;
 pub mod shot_clock;
 pub mod credentials;
+pub mod usage;
 
 pub use projects::*;
 pub use chat::*;
 pub use analyzer::*;
+pub use history::*;
+pub use inventory::*;
 pub use settings::*;
 pub use file_tools::*;
+pub use file_changes::*;
+pub use symbols::*;
+pub use path_policy::*;
+pub use command_runner::*;
 pub use ideas::*;
+pub use recommendations::*;
 pub use sops::*;
 pub use shot_clock::*;
 pub use credentials::*;
+pub use usage::*;
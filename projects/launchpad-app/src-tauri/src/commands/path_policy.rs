@@ -0,0 +1,384 @@
+use crate::db::get_db;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+use tauri::AppHandle;
+
+pub const PATH_POLICY_SETTING: &str = "path_policy";
+
+/// Directory names that hold secrets wherever they appear outside a project
+const DEFAULT_DENIED_NAMES: &[&str] = &[".ssh", ".gnupg", ".aws", ".azure", ".kube", ".docker", ".password-store"];
+
+/// Sensitive locations relative to the user's home directory
+#[cfg(target_os = "macos")]
+const DEFAULT_DENIED_HOME_PATHS: &[&str] = &[".config", "Library/Keychains", "Library/Cookies", "Library/Application Support"];
+#[cfg(all(unix, not(target_os = "macos")))]
+const DEFAULT_DENIED_HOME_PATHS: &[&str] = &[".config", ".local/share/keyrings", ".mozilla", ".pki"];
+#[cfg(windows)]
+const DEFAULT_DENIED_HOME_PATHS: &[&str] = &["AppData\\Local\\Microsoft", "AppData\\Roaming\\Microsoft", "NTUSER.DAT"];
+
+/// System locations denied outright
+#[cfg(target_os = "macos")]
+const DEFAULT_DENIED_PATHS: &[&str] = &["/etc", "/private/etc", "/private/var", "/System", "/Library/Keychains", "/dev"];
+#[cfg(all(unix, not(target_os = "macos")))]
+const DEFAULT_DENIED_PATHS: &[&str] = &["/etc", "/proc", "/sys", "/dev", "/boot", "/run", "/var/lib", "/var/log"];
+#[cfg(windows)]
+const DEFAULT_DENIED_PATHS: &[&str] = &[];
+
+/// Windows system folders come from the environment since the drive letter varies
+#[cfg(windows)]
+const DENIED_ENV_PATHS: &[&str] = &["SystemRoot", "ProgramFiles", "ProgramFiles(x86)", "ProgramData"];
+#[cfg(not(windows))]
+const DENIED_ENV_PATHS: &[&str] = &[];
+
+/// User-editable rules stored in settings. A rule containing a path separator
+/// (or starting with `~`) is a path prefix; anything else matches a single path
+/// component by name, except inside a registered project, where folders like
+/// `.docker` are ordinary project content.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PathPolicySettings {
+    /// Only allow paths inside registered projects' `local_path` (plus `allow`)
+    pub confine_to_projects: bool,
+    /// Extra denied paths or names, on top of the per-OS defaults
+    pub deny: Vec<String>,
+    /// Paths that are always allowed, overriding every deny rule
+    pub allow: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PathDenial {
+    pub id: i64,
+    pub path: String,
+    pub reason: String,
+    pub created_at: String,
+}
+
+/// The effective policy: defaults for this OS merged with the user's rules
+pub struct PathPolicy {
+    denied_paths: Vec<PathBuf>,
+    denied_names: Vec<String>,
+    allowed_paths: Vec<PathBuf>,
+    project_roots: Vec<PathBuf>,
+    confine_to_projects: bool,
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from)
+}
+
+fn is_path_rule(rule: &str) -> bool {
+    rule.starts_with('~') || rule.contains('/') || rule.contains('\\')
+}
+
+/// Expand `~` and canonicalize when possible, so rules compare against canonical targets
+fn resolve_rule_path(rule: &str, home: Option<&Path>) -> Option<PathBuf> {
+    let path = match rule.strip_prefix('~') {
+        Some(rest) => home?.join(rest.trim_start_matches(['/', '\\'])),
+        None => PathBuf::from(rule),
+    };
+    Some(path.canonicalize().unwrap_or(path))
+}
+
+fn names_equal(a: &OsStr, b: &str) -> bool {
+    if cfg!(windows) {
+        a.to_string_lossy().eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+fn starts_with(path: &Path, prefix: &Path) -> bool {
+    if cfg!(windows) {
+        let lower = |p: &Path| PathBuf::from(p.to_string_lossy().to_lowercase());
+        lower(path).starts_with(lower(prefix))
+    } else {
+        path.starts_with(prefix)
+    }
+}
+
+impl PathPolicy {
+    pub fn new(settings: &PathPolicySettings, project_roots: Vec<PathBuf>) -> Self {
+        Self::with_home(settings, project_roots, home_dir().as_deref())
+    }
+
+    fn with_home(settings: &PathPolicySettings, project_roots: Vec<PathBuf>, home: Option<&Path>) -> Self {
+        let mut denied_paths: Vec<PathBuf> =
+            DEFAULT_DENIED_PATHS.iter().filter_map(|p| resolve_rule_path(p, home)).collect();
+        denied_paths.extend(
+            DENIED_ENV_PATHS
+                .iter()
+                .filter_map(std::env::var_os)
+                .filter_map(|p| resolve_rule_path(&p.to_string_lossy(), home)),
+        );
+        if let Some(home) = home {
+            denied_paths.extend(DEFAULT_DENIED_HOME_PATHS.iter().map(|p| {
+                let path = home.join(p);
+                path.canonicalize().unwrap_or(path)
+            }));
+        }
+
+        let mut denied_names: Vec<String> = DEFAULT_DENIED_NAMES.iter().map(|s| s.to_string()).collect();
+        for rule in &settings.deny {
+            if is_path_rule(rule) {
+                denied_paths.extend(resolve_rule_path(rule, home));
+            } else {
+                denied_names.push(rule.clone());
+            }
+        }
+
+        // A project linked at (or above) the home directory would otherwise
+        // exempt ~/.ssh and friends from the name rules
+        let project_roots = project_roots
+            .into_iter()
+            .filter(|root| !home.is_some_and(|home| starts_with(home, root)))
+            .collect();
+
+        Self {
+            denied_paths,
+            denied_names,
+            allowed_paths: settings.allow.iter().filter_map(|r| resolve_rule_path(r, home)).collect(),
+            project_roots,
+            confine_to_projects: settings.confine_to_projects,
+        }
+    }
+
+    /// Load the user's rules and the registered project roots from the database
+    pub fn load(conn: &Connection) -> Self {
+        let settings: PathPolicySettings = conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                [PATH_POLICY_SETTING],
+                |row| row.get::<_, String>(0),
+            )
+            .ok()
+            .and_then(|value| serde_json::from_str(&value).ok())
+            .unwrap_or_default();
+
+        let project_roots = conn
+            .prepare("SELECT local_path FROM projects WHERE local_path IS NOT NULL AND local_path != ''")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|p| Path::new(p).canonicalize().ok())
+            .collect();
+
+        Self::new(&settings, project_roots)
+    }
+
+    /// Check a canonical path, returning the reason it is denied
+    pub fn check(&self, path: &Path) -> Result<(), String> {
+        if self.allowed_paths.iter().any(|allowed| starts_with(path, allowed)) {
+            return Ok(());
+        }
+
+        let in_project = self.project_roots.iter().any(|root| starts_with(path, root));
+        if self.confine_to_projects && !in_project {
+            return Err("outside every registered project".to_string());
+        }
+
+        if let Some(denied) = self.denied_paths.iter().find(|denied| starts_with(path, denied)) {
+            return Err(format!("inside protected location {}", denied.display()));
+        }
+
+        if in_project {
+            return Ok(());
+        }
+
+        let denied_name = path.components().find_map(|component| match component {
+            Component::Normal(name) => self.denied_names.iter().find(|denied| names_equal(name, denied)),
+            _ => None,
+        });
+        if let Some(name) = denied_name {
+            return Err(format!("inside protected directory {}", name));
+        }
+
+        Ok(())
+    }
+}
+
+/// Record a denied access in the audit log
+pub fn record_denial(conn: &Connection, path: &Path, reason: &str) {
+    log::warn!("Blocked access to {}: {}", path.display(), reason);
+    if let Err(e) = conn.execute(
+        "INSERT INTO path_denials (path, reason, created_at) VALUES (?1, ?2, ?3)",
+        (path.to_string_lossy().to_string(), reason, Utc::now().to_rfc3339()),
+    ) {
+        log::warn!("Failed to record path denial: {}", e);
+    }
+}
+
+/// Check a canonical path against the policy, auditing any denial
+pub fn enforce(conn: &Connection, path: &Path) -> Result<(), String> {
+    PathPolicy::load(conn).check(path).map_err(|reason| {
+        record_denial(conn, path, &reason);
+        format!("Access to this path is not allowed ({})", reason)
+    })
+}
+
+#[tauri::command]
+pub fn get_path_policy(app_handle: AppHandle) -> Result<PathPolicySettings, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let result: Result<String, _> = conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [PATH_POLICY_SETTING],
+        |row| row.get(0),
+    );
+
+    match result {
+        Ok(value) => serde_json::from_str(&value).map_err(|e| e.to_string()),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(PathPolicySettings::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn set_path_policy(app_handle: AppHandle, policy: PathPolicySettings) -> Result<(), String> {
+    let value = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = ?2",
+        [PATH_POLICY_SETTING, &value],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_path_denials(app_handle: AppHandle, limit: Option<i64>) -> Result<Vec<PathDenial>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, path, reason, created_at FROM path_denials ORDER BY id DESC LIMIT ?1")
+        .map_err(|e| e.to_string())?;
+
+    let denials = stmt
+        .query_map([limit.unwrap_or(100)], |row| {
+            Ok(PathDenial {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                reason: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(denials)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    const HOME: &str = "/home/tester";
+
+    fn policy(settings: PathPolicySettings, roots: &[&str]) -> PathPolicy {
+        let roots = roots.iter().map(PathBuf::from).collect();
+        PathPolicy::with_home(&settings, roots, Some(Path::new(HOME)))
+    }
+
+    fn check(policy: &PathPolicy, path: &str) -> Result<(), String> {
+        policy.check(Path::new(path))
+    }
+
+    #[test]
+    fn default_names_are_denied_anywhere_outside_projects() {
+        let policy = policy(PathPolicySettings::default(), &[]);
+        assert!(check(&policy, "/home/tester/.ssh/id_ed25519").is_err());
+        assert!(check(&policy, "/home/tester/code/old/.aws/credentials").is_err());
+        assert!(check(&policy, "/srv/backup/.gnupg").is_err());
+        assert!(check(&policy, "/home/tester/code/app/src/main.rs").is_ok());
+        // Components are matched whole, not as substrings
+        assert!(check(&policy, "/home/tester/code/.sshrc").is_ok());
+    }
+
+    #[test]
+    fn names_inside_a_project_are_project_content() {
+        let policy = policy(PathPolicySettings::default(), &["/home/tester/code/app", "/home/tester/.docker"]);
+        assert!(check(&policy, "/home/tester/code/app/.docker/Dockerfile").is_ok());
+        assert!(check(&policy, "/home/tester/code/app/.aws/config.example").is_ok());
+        assert!(check(&policy, "/home/tester/.docker/compose.yml").is_ok());
+        assert!(check(&policy, "/home/tester/code/other/.docker/Dockerfile").is_err());
+    }
+
+    #[test]
+    fn a_project_at_home_does_not_exempt_names() {
+        let policy = policy(PathPolicySettings::default(), &[HOME, "/"]);
+        assert!(check(&policy, "/home/tester/.ssh/id_ed25519").is_err());
+        assert!(check(&policy, "/home/tester/code/app/.aws/credentials").is_err());
+    }
+
+    #[test]
+    fn home_relative_rules_expand_tilde() {
+        let settings = PathPolicySettings {
+            deny: vec!["~/private".to_string(), "secrets".to_string()],
+            ..Default::default()
+        };
+        let policy = policy(settings, &["/home/tester/code/app"]);
+        assert!(check(&policy, "/home/tester/private/notes.txt").is_err());
+        assert!(check(&policy, "/home/tester/privateer").is_ok());
+        assert!(check(&policy, "/home/tester/.config/gh/hosts.yml").is_err());
+        assert!(check(&policy, "/mnt/data/secrets/key").is_err());
+        assert!(check(&policy, "/home/tester/code/app/secrets/fixture.json").is_ok());
+    }
+
+    #[test]
+    fn absolute_rules_apply_inside_projects_too() {
+        let settings = PathPolicySettings {
+            deny: vec!["/home/tester/code/app/vendor".to_string()],
+            ..Default::default()
+        };
+        let policy = policy(settings, &["/home/tester/code/app", "/etc/myapp"]);
+        assert!(check(&policy, "/home/tester/code/app/vendor/lib.rs").is_err());
+        assert!(check(&policy, "/home/tester/code/app/src/lib.rs").is_ok());
+        assert!(check(&policy, "/etc/myapp/config.toml").is_err());
+        assert!(check(&policy, "/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn allow_overrides_every_deny_rule() {
+        let settings = PathPolicySettings {
+            confine_to_projects: true,
+            deny: vec!["~/code/app/vendor".to_string()],
+            allow: vec!["~/.ssh/config".to_string(), "~/code/app/vendor/ok".to_string()],
+        };
+        let policy = policy(settings, &["/home/tester/code/app"]);
+        assert!(check(&policy, "/home/tester/.ssh/config").is_ok());
+        assert!(check(&policy, "/home/tester/.ssh/id_ed25519").is_err());
+        assert!(check(&policy, "/home/tester/code/app/vendor/ok/a.rs").is_ok());
+        assert!(check(&policy, "/home/tester/code/app/vendor/b.rs").is_err());
+    }
+
+    #[test]
+    fn confinement_denies_everything_outside_projects() {
+        let settings = PathPolicySettings {
+            confine_to_projects: true,
+            ..Default::default()
+        };
+        let confined = policy(settings, &["/home/tester/code/app"]);
+        assert!(check(&confined, "/home/tester/code/app/README.md").is_ok());
+        assert!(check(&confined, "/home/tester/code/app").is_ok());
+        let err = check(&confined, "/home/tester/code/other/README.md").unwrap_err();
+        assert_eq!(err, "outside every registered project");
+        // Prefixes match whole components
+        assert!(check(&confined, "/home/tester/code/application/README.md").is_err());
+
+        let unconfined = policy(PathPolicySettings::default(), &["/home/tester/code/app"]);
+        assert!(check(&unconfined, "/home/tester/code/other/README.md").is_ok());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::db::get_db;
 use chrono::Utc;
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use uuid::Uuid;
@@ -33,6 +34,8 @@ pub struct RoadmapItem {
     pub status: String,
     pub ai_notes: Option<String>,
     pub completed_at: Option<String>,
+    /// Set by analysis findings (e.g. committed secrets); the item can't be completed until cleared
+    pub blocked_reason: Option<String>,
 }
 
 // Default SOP names for roadmap initialization
@@ -233,7 +236,7 @@ pub fn get_roadmap(app_handle: AppHandle, project_id: String) -> Result<Vec<Road
 
     let mut stmt = conn
         .prepare(
-            "SELECT id, project_id, sop_number, sop_name, status, ai_notes, completed_at
+            "SELECT id, project_id, sop_number, sop_name, status, ai_notes, completed_at, blocked_reason
              FROM roadmap_items WHERE project_id = ?1 ORDER BY sop_number",
         )
         .map_err(|e| e.to_string())?;
@@ -248,6 +251,7 @@ pub fn get_roadmap(app_handle: AppHandle, project_id: String) -> Result<Vec<Road
                 status: row.get(4)?,
                 ai_notes: row.get(5)?,
                 completed_at: row.get(6)?,
+                blocked_reason: row.get(7)?,
             })
         })
         .map_err(|e| e.to_string())?
@@ -267,6 +271,19 @@ pub fn update_roadmap_item(
     let db = get_db(&app_handle);
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
 
+    if status == "complete" {
+        let blocked_reason: Option<String> = conn
+            .query_row(
+                "SELECT blocked_reason FROM roadmap_items WHERE id = ?1",
+                [&id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if let Some(reason) = blocked_reason {
+            return Err(format!("This step is blocked: {}", reason));
+        }
+    }
+
     let completed_at = if status == "complete" {
         Some(Utc::now().to_rfc3339())
     } else {
@@ -305,3 +322,28 @@ pub fn update_roadmap_item(
 
     Ok(())
 }
+
+/// Block (or, with `None`, unblock) a project's roadmap item. A blocked item that
+/// was already complete goes back to in progress.
+pub fn set_roadmap_block(
+    conn: &Connection,
+    project_id: &str,
+    sop_number: i32,
+    reason: Option<&str>,
+) -> rusqlite::Result<()> {
+    match reason {
+        Some(reason) => conn.execute(
+            "UPDATE roadmap_items
+             SET blocked_reason = ?1,
+                 status = CASE WHEN status = 'complete' THEN 'in_progress' ELSE status END,
+                 completed_at = NULL
+             WHERE project_id = ?2 AND sop_number = ?3",
+            (reason, project_id, sop_number),
+        )?,
+        None => conn.execute(
+            "UPDATE roadmap_items SET blocked_reason = NULL WHERE project_id = ?1 AND sop_number = ?2",
+            (project_id, sop_number),
+        )?,
+    };
+    Ok(())
+}
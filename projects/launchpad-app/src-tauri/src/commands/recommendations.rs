@@ -0,0 +1,240 @@
+use crate::analysis::rules::RecommendationRule;
+use crate::db::get_db;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+use tauri::AppHandle;
+
+fn json_column<T: DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let value: String = row.get(index)?;
+    serde_json::from_str(&value)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+fn row_to_rule(row: &Row) -> rusqlite::Result<RecommendationRule> {
+    let severity: String = row.get(2)?;
+
+    Ok(RecommendationRule {
+        id: row.get(0)?,
+        condition: json_column(row, 1)?,
+        severity: serde_json::from_value(serde_json::Value::String(severity))
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e)))?,
+        message: row.get(3)?,
+        sop_number: row.get(4)?,
+        project_types: json_column(row, 5)?,
+        enabled: row.get(6)?,
+    })
+}
+
+/// Every stored rule, oldest first. Rules that no longer parse are skipped.
+pub fn load_recommendation_rules(conn: &Connection) -> rusqlite::Result<Vec<RecommendationRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, condition, severity, message, sop_number, project_types, enabled
+         FROM recommendation_rules
+         ORDER BY rowid",
+    )?;
+
+    let rules = stmt
+        .query_map([], row_to_rule)?
+        .filter_map(|rule| {
+            rule.map_err(|e| log::warn!("Skipping invalid recommendation rule: {}", e))
+                .ok()
+        })
+        .collect();
+
+    Ok(rules)
+}
+
+/// Ids of the rules silenced for a project
+pub fn silenced_rules(conn: &Connection, project_id: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT rule_id FROM silenced_recommendations WHERE project_id = ?1")?;
+    let ids = stmt
+        .query_map([project_id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(ids)
+}
+
+#[tauri::command]
+pub fn list_recommendation_rules(app_handle: AppHandle) -> Result<Vec<RecommendationRule>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    load_recommendation_rules(&conn).map_err(|e| e.to_string())
+}
+
+/// Create a rule, or replace the one with the same id
+#[tauri::command]
+pub fn save_recommendation_rule(app_handle: AppHandle, rule: RecommendationRule) -> Result<(), String> {
+    if rule.id.trim().is_empty() {
+        return Err("Rule id is required".to_string());
+    }
+
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let condition = serde_json::to_string(&rule.condition).map_err(|e| e.to_string())?;
+    let severity = serde_json::to_value(rule.severity).map_err(|e| e.to_string())?;
+    let project_types = serde_json::to_string(&rule.project_types).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO recommendation_rules (id, condition, severity, message, sop_number, project_types, enabled)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET
+            condition = excluded.condition,
+            severity = excluded.severity,
+            message = excluded.message,
+            sop_number = excluded.sop_number,
+            project_types = excluded.project_types,
+            enabled = excluded.enabled",
+        rusqlite::params![
+            &rule.id,
+            &condition,
+            severity.as_str(),
+            &rule.message,
+            rule.sop_number,
+            &project_types,
+            rule.enabled,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_recommendation_rule(app_handle: AppHandle, id: String) -> Result<(), String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    conn.execute("DELETE FROM recommendation_rules WHERE id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_silenced_recommendations(app_handle: AppHandle, project_id: String) -> Result<Vec<String>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    silenced_rules(&conn, &project_id).map_err(|e| e.to_string())
+}
+
+/// Record the silence and drop the rule's recommendation from the project's
+/// saved report, so it stays hidden without re-running the analysis
+fn silence(conn: &Connection, project_id: &str, rule_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO silenced_recommendations (project_id, rule_id) VALUES (?1, ?2)",
+        [project_id, rule_id],
+    )?;
+
+    let report: Option<String> = conn
+        .query_row("SELECT status_report FROM projects WHERE id = ?1", [project_id], |row| row.get(0))
+        .optional()?
+        .flatten();
+    let Some(mut report) = report.and_then(|r| serde_json::from_str::<serde_json::Value>(&r).ok()) else {
+        return Ok(());
+    };
+    let Some(recommendations) = report.get_mut("recommendations").and_then(|r| r.as_array_mut()) else {
+        return Ok(());
+    };
+    recommendations.retain(|r| r["rule_id"].as_str() != Some(rule_id));
+
+    conn.execute(
+        "UPDATE projects SET status_report = ?1 WHERE id = ?2",
+        [report.to_string().as_str(), project_id],
+    )?;
+    Ok(())
+}
+
+/// Stop a rule's recommendation from appearing in the project's analyses
+#[tauri::command]
+pub fn silence_recommendation(app_handle: AppHandle, project_id: String, rule_id: String) -> Result<(), String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    silence(&conn, &project_id, &rule_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn unsilence_recommendation(app_handle: AppHandle, project_id: String, rule_id: String) -> Result<(), String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "DELETE FROM silenced_recommendations WHERE project_id = ?1 AND rule_id = ?2",
+        [&project_id, &rule_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_in_memory;
+    use serde_json::json;
+
+    fn project_with_report(conn: &Connection, report: &serde_json::Value) {
+        conn.execute(
+            "INSERT INTO projects (id, name, slug, status_report) VALUES ('p1', 'App', 'app', ?1)",
+            [report.to_string()],
+        )
+        .unwrap();
+    }
+
+    fn saved_report(conn: &Connection) -> serde_json::Value {
+        let report: String = conn
+            .query_row("SELECT status_report FROM projects WHERE id = 'p1'", [], |row| row.get(0))
+            .unwrap();
+        serde_json::from_str(&report).unwrap()
+    }
+
+    #[test]
+    fn silencing_strips_the_rule_from_the_saved_report() {
+        let conn = open_in_memory();
+        let rule_ids: Vec<String> = load_recommendation_rules(&conn).unwrap().into_iter().map(|r| r.id).collect();
+        let (silenced, kept) = (&rule_ids[0], &rule_ids[1]);
+        project_with_report(
+            &conn,
+            &json!({
+                "project_path": "/code/app",
+                "recommendations": [
+                    { "rule_id": silenced, "severity": "high", "message": "a", "sop_number": null },
+                    { "rule_id": kept, "severity": "low", "message": "b", "sop_number": 8 },
+                ],
+            }),
+        );
+
+        silence(&conn, "p1", silenced).unwrap();
+        // Silencing twice is harmless
+        silence(&conn, "p1", silenced).unwrap();
+
+        assert_eq!(silenced_rules(&conn, "p1").unwrap(), std::slice::from_ref(silenced));
+        let report = saved_report(&conn);
+        assert_eq!(report["project_path"], "/code/app");
+        let remaining: Vec<&str> = report["recommendations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["rule_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(remaining, [kept.as_str()]);
+    }
+
+    #[test]
+    fn legacy_and_missing_reports_are_left_alone() {
+        let conn = open_in_memory();
+        let rule_id = load_recommendation_rules(&conn).unwrap()[0].id.clone();
+        let legacy = json!({ "recommendations": ["Add tests"] });
+        project_with_report(&conn, &legacy);
+        silence(&conn, "p1", &rule_id).unwrap();
+        assert_eq!(saved_report(&conn), legacy);
+
+        conn.execute("UPDATE projects SET status_report = NULL", []).unwrap();
+        silence(&conn, "p1", &rule_id).unwrap();
+
+        // The project must exist
+        assert!(silence(&conn, "missing", &rule_id).is_err());
+    }
+}
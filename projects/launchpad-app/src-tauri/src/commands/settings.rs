@@ -1,6 +1,10 @@
 use crate::db::get_db;
 use crate::commands::credentials;
+use crate::commands::analyzer::ADVISORY_DB_SETTING;
+use crate::commands::command_runner::{COMMAND_ALLOWLIST_SETTING, DEFAULT_COMMAND_ALLOWLIST};
+use crate::commands::walker::IGNORE_OVERRIDES_SETTING;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tauri::AppHandle;
 
 const API_KEY_CREDENTIAL_NAME: &str = "anthropic_api_key";
@@ -10,6 +14,17 @@ pub struct AppSettings {
     pub anthropic_api_key: Option<String>,
     pub theme: String,
     pub auto_analyze: bool,
+    /// Default LLM provider ("anthropic" or "openai" for any OpenAI-compatible server)
+    pub llm_provider: String,
+    pub llm_model: Option<String>,
+    /// Base URL for the OpenAI-compatible provider (e.g. http://localhost:11434/v1 for Ollama)
+    pub openai_base_url: Option<String>,
+    /// Commands the chat agent may run, stored as a JSON array of command prefixes
+    pub command_allowlist: Vec<String>,
+    /// Extra gitignore-style patterns per project path (prefix with `!` to re-include)
+    pub ignore_overrides: HashMap<String, Vec<String>>,
+    /// Local advisory snapshot directories (OSV JSON or RustSec advisory-db) for offline audits
+    pub advisory_db_paths: Vec<String>,
 }
 
 impl Default for AppSettings {
@@ -18,6 +33,12 @@ impl Default for AppSettings {
             anthropic_api_key: None,
             theme: "system".to_string(),
             auto_analyze: true,
+            llm_provider: "anthropic".to_string(),
+            llm_model: None,
+            openai_base_url: None,
+            command_allowlist: DEFAULT_COMMAND_ALLOWLIST.iter().map(|s| s.to_string()).collect(),
+            ignore_overrides: HashMap::new(),
+            advisory_db_paths: Vec::new(),
         }
     }
 }
@@ -133,6 +154,24 @@ pub fn get_all_settings(app_handle: AppHandle) -> Result<AppSettings, String> {
             match key.as_str() {
                 "theme" => settings.theme = value,
                 "auto_analyze" => settings.auto_analyze = value == "true",
+                "llm_provider" => settings.llm_provider = value,
+                "llm_model" => settings.llm_model = Some(value),
+                "openai_base_url" => settings.openai_base_url = Some(value),
+                COMMAND_ALLOWLIST_SETTING => {
+                    if let Ok(list) = serde_json::from_str(&value) {
+                        settings.command_allowlist = list;
+                    }
+                }
+                IGNORE_OVERRIDES_SETTING => {
+                    if let Ok(overrides) = serde_json::from_str(&value) {
+                        settings.ignore_overrides = overrides;
+                    }
+                }
+                ADVISORY_DB_SETTING => {
+                    if let Ok(paths) = serde_json::from_str(&value) {
+                        settings.advisory_db_paths = paths;
+                    }
+                }
                 // Note: anthropic_api_key is now retrieved from secure storage above
                 _ => {}
             }
@@ -1,5 +1,8 @@
+use crate::analysis::gates::{default_gates, PhaseGates, SopGate};
 use crate::db::get_db;
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use tauri::AppHandle;
 use uuid::Uuid;
 
@@ -15,6 +18,8 @@ pub struct SOP {
     pub tags: Option<String>,
     pub created_at: String,
     pub archived_at: Option<String>,
+    /// JSON array of `SopGate`s; `None` uses the built-in gates for the SOP number
+    pub gates: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,6 +30,7 @@ pub struct CreateSOPInput {
     pub phase: String,
     pub content: String,
     pub tags: Option<Vec<String>>,
+    pub gates: Option<Vec<SopGate>>,
 }
 
 // Default SOP definitions
@@ -44,15 +50,58 @@ pub const DEFAULT_SOPS: &[(i32, &str, &str)] = &[
     (12, "Marketing Activation", "post_launch"),
 ];
 
+/// Gates for every SOP, taken from its latest active version. Versions saved
+/// before gates existed use the built-in gates for their number.
+pub fn load_phase_gates(conn: &Connection) -> Vec<PhaseGates> {
+    let mut phases: BTreeMap<i32, PhaseGates> = DEFAULT_SOPS
+        .iter()
+        .map(|(num, name, _)| {
+            let gates = PhaseGates {
+                sop_number: *num,
+                name: name.to_string(),
+                gates: default_gates(*num),
+            };
+            (*num, gates)
+        })
+        .collect();
+
+    let rows = conn
+        .prepare("SELECT sop_number, name, gates FROM sops WHERE is_active = 1 ORDER BY sop_number, created_at, rowid")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| {
+                Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+        });
+    let rows = rows.unwrap_or_else(|e| {
+        log::warn!("Failed to load SOP gates: {}", e);
+        Vec::new()
+    });
+
+    // Later versions replace earlier ones
+    for (num, name, gates) in rows {
+        let gates = match gates {
+            Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("Invalid gates for SOP {}: {}", num, e);
+                default_gates(num)
+            }),
+            None => default_gates(num),
+        };
+        phases.insert(num, PhaseGates { sop_number: num, name, gates });
+    }
+
+    phases.into_values().collect()
+}
+
 #[tauri::command]
 pub fn list_sops(app_handle: AppHandle, active_only: Option<bool>) -> Result<Vec<SOP>, String> {
     let db = get_db(&app_handle);
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
 
     let query = if active_only.unwrap_or(false) {
-        "SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at FROM sops WHERE is_active = 1 ORDER BY sop_number, version DESC"
+        "SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at, gates FROM sops WHERE is_active = 1 ORDER BY sop_number, version DESC"
     } else {
-        "SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at FROM sops ORDER BY sop_number, version DESC"
+        "SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at, gates FROM sops ORDER BY sop_number, version DESC"
     };
 
     let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
@@ -70,6 +119,7 @@ pub fn list_sops(app_handle: AppHandle, active_only: Option<bool>) -> Result<Vec
                 tags: row.get(7)?,
                 created_at: row.get(8)?,
                 archived_at: row.get(9)?,
+                gates: row.get(10)?,
             })
         })
         .map_err(|e| e.to_string())?
@@ -86,7 +136,7 @@ pub fn get_sop(app_handle: AppHandle, sop_number: i32, version: Option<String>)
 
     let sop = if let Some(v) = version {
         let mut stmt = conn
-            .prepare("SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at FROM sops WHERE sop_number = ?1 AND version = ?2")
+            .prepare("SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at, gates FROM sops WHERE sop_number = ?1 AND version = ?2")
             .map_err(|e| e.to_string())?;
 
         stmt.query_row(rusqlite::params![sop_number, v], |row| {
@@ -101,13 +151,14 @@ pub fn get_sop(app_handle: AppHandle, sop_number: i32, version: Option<String>)
                 tags: row.get(7)?,
                 created_at: row.get(8)?,
                 archived_at: row.get(9)?,
+                gates: row.get(10)?,
             })
         })
         .ok()
     } else {
         // Get latest active version
         let mut stmt = conn
-            .prepare("SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at FROM sops WHERE sop_number = ?1 AND is_active = 1 ORDER BY created_at DESC LIMIT 1")
+            .prepare("SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at, gates FROM sops WHERE sop_number = ?1 AND is_active = 1 ORDER BY created_at DESC LIMIT 1")
             .map_err(|e| e.to_string())?;
 
         stmt.query_row([sop_number], |row| {
@@ -122,6 +173,7 @@ pub fn get_sop(app_handle: AppHandle, sop_number: i32, version: Option<String>)
                 tags: row.get(7)?,
                 created_at: row.get(8)?,
                 archived_at: row.get(9)?,
+                gates: row.get(10)?,
             })
         })
         .ok()
@@ -137,9 +189,10 @@ pub fn create_sop_version(app_handle: AppHandle, input: CreateSOPInput) -> Resul
 
     let id = Uuid::new_v4().to_string();
     let tags_json = input.tags.map(|t| serde_json::to_string(&t).unwrap_or_default());
+    let gates_json = input.gates.map(|g| serde_json::to_string(&g).unwrap_or_default());
 
     conn.execute(
-        "INSERT INTO sops (id, sop_number, version, name, phase, content, is_active, tags) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7)",
+        "INSERT INTO sops (id, sop_number, version, name, phase, content, is_active, tags, gates) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7, ?8)",
         rusqlite::params![
             &id,
             input.sop_number,
@@ -148,13 +201,14 @@ pub fn create_sop_version(app_handle: AppHandle, input: CreateSOPInput) -> Resul
             &input.phase,
             &input.content,
             &tags_json,
+            &gates_json,
         ],
     )
     .map_err(|e| e.to_string())?;
 
     // Fetch the created SOP
     let mut stmt = conn
-        .prepare("SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at FROM sops WHERE id = ?1")
+        .prepare("SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at, gates FROM sops WHERE id = ?1")
         .map_err(|e| e.to_string())?;
 
     let sop = stmt
@@ -170,6 +224,7 @@ pub fn create_sop_version(app_handle: AppHandle, input: CreateSOPInput) -> Resul
                 tags: row.get(7)?,
                 created_at: row.get(8)?,
                 archived_at: row.get(9)?,
+                gates: row.get(10)?,
             })
         })
         .map_err(|e| e.to_string())?;
@@ -199,7 +254,7 @@ pub fn get_sop_versions(app_handle: AppHandle, sop_number: i32) -> Result<Vec<SO
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = conn
-        .prepare("SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at FROM sops WHERE sop_number = ?1 ORDER BY created_at DESC")
+        .prepare("SELECT id, sop_number, version, name, phase, content, is_active, tags, created_at, archived_at, gates FROM sops WHERE sop_number = ?1 ORDER BY created_at DESC")
         .map_err(|e| e.to_string())?;
 
     let sops = stmt
@@ -215,6 +270,7 @@ pub fn get_sop_versions(app_handle: AppHandle, sop_number: i32) -> Result<Vec<SO
                 tags: row.get(7)?,
                 created_at: row.get(8)?,
                 archived_at: row.get(9)?,
+                gates: row.get(10)?,
             })
         })
         .map_err(|e| e.to_string())?
@@ -241,9 +297,10 @@ pub fn init_default_sops(app_handle: AppHandle) -> Result<(), String> {
 
         if !exists {
             let id = Uuid::new_v4().to_string();
+            let gates = serde_json::to_string(&default_gates(*num)).map_err(|e| e.to_string())?;
             conn.execute(
-                "INSERT INTO sops (id, sop_number, version, name, phase, content, is_active) VALUES (?1, ?2, '1.0.0', ?3, ?4, '', 1)",
-                rusqlite::params![&id, num, name, phase],
+                "INSERT INTO sops (id, sop_number, version, name, phase, content, is_active, gates) VALUES (?1, ?2, '1.0.0', ?3, ?4, '', 1, ?5)",
+                rusqlite::params![&id, num, name, phase, &gates],
             )
             .map_err(|e| e.to_string())?;
         }
@@ -0,0 +1,623 @@
+use crate::commands::file_tools::validate_path;
+use crate::commands::walker::ProjectWalker;
+use crate::db::get_db;
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::UNIX_EPOCH;
+use tauri::AppHandle;
+
+/// Source files larger than this are not indexed
+const MAX_INDEXED_FILE_SIZE: u64 = 1_000_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Symbol {
+    pub file_path: String,
+    pub name: String,
+    /// function, method, struct, enum, trait, impl, type, interface, class, component, module, macro, const
+    pub kind: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub exported: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    TypeScript,
+    JavaScript,
+    Python,
+}
+
+impl Language {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "ts" | "tsx" | "mts" | "cts" => Some(Self::TypeScript),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+}
+
+struct Rule {
+    regex: Regex,
+    kind: &'static str,
+}
+
+fn rule(pattern: &str, kind: &'static str) -> Rule {
+    Rule {
+        regex: Regex::new(pattern).expect("valid symbol regex"),
+        kind,
+    }
+}
+
+/// Declaration patterns per language. Each regex captures `vis` (optional) and `name`.
+fn rules(language: Language) -> &'static [Rule] {
+    static RUST: OnceLock<Vec<Rule>> = OnceLock::new();
+    static SCRIPT: OnceLock<Vec<Rule>> = OnceLock::new();
+    static PYTHON: OnceLock<Vec<Rule>> = OnceLock::new();
+
+    match language {
+        Language::Rust => RUST.get_or_init(|| {
+            let vis = r"^\s*(?P<vis>pub(?:\([^)]*\))?\s+)?";
+            vec![
+                rule(&format!(r#"{}(?:default\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?(?:extern\s+"[^"]*"\s+)?fn\s+(?P<name>\w+)"#, vis), "function"),
+                rule(&format!(r"{}struct\s+(?P<name>\w+)", vis), "struct"),
+                rule(&format!(r"{}enum\s+(?P<name>\w+)", vis), "enum"),
+                rule(&format!(r"{}(?:unsafe\s+)?trait\s+(?P<name>\w+)", vis), "trait"),
+                rule(&format!(r"{}type\s+(?P<name>\w+)", vis), "type"),
+                rule(&format!(r"{}mod\s+(?P<name>\w+)", vis), "module"),
+                rule(&format!(r"{}(?:const|static)\s+(?:mut\s+)?(?P<name>[A-Z_][A-Z0-9_]*)\s*:", vis), "const"),
+                rule(r"^\s*(?:unsafe\s+)?impl(?:<[^{]*?>)?\s+(?:[\w:<>, ]+\s+for\s+)?(?P<name>\w+)", "impl"),
+                rule(r"^\s*macro_rules!\s*(?P<name>\w+)", "macro"),
+            ]
+        }),
+        Language::TypeScript | Language::JavaScript => SCRIPT.get_or_init(|| {
+            let vis = r"^\s*(?P<vis>export\s+(?:default\s+)?)?(?:declare\s+)?";
+            vec![
+                rule(&format!(r"{}(?:async\s+)?function\s*\*?\s*(?P<name>\w+)", vis), "function"),
+                rule(&format!(r"{}(?:abstract\s+)?class\s+(?P<name>\w+)", vis), "class"),
+                rule(&format!(r"{}interface\s+(?P<name>\w+)", vis), "interface"),
+                rule(&format!(r"{}type\s+(?P<name>\w+)\s*(?:<[^=]*>)?\s*=", vis), "type"),
+                rule(&format!(r"{}(?:const\s+)?enum\s+(?P<name>\w+)", vis), "enum"),
+                rule(&format!(r"{}(?:const|let|var)\s+(?P<name>\w+)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:function\b|(?:<[^>]*>)?\([^)]*\)\s*(?::[^=]*)?=>|\w+\s*=>)", vis), "function"),
+                rule(&format!(r"{}(?:const|let|var)\s+(?P<name>\w+)\s*(?::[^=]+)?=\s*(?:React\.)?(?:memo|forwardRef)\(", vis), "component"),
+                rule(r"^\s*(?P<vis>export\s+)(?:const|let|var)\s+(?P<name>\w+)", "const"),
+                rule(r"^\s+(?:(?:public|private|protected|static|async|override|readonly)\s+)*(?P<name>[a-zA-Z_]\w*)\s*(?:<[^>]*>)?\([^)]*\)\s*(?::\s*[^{]+)?\{\s*$", "method"),
+            ]
+        }),
+        Language::Python => PYTHON.get_or_init(|| {
+            vec![
+                rule(r"^\s*(?:async\s+)?def\s+(?P<name>\w+)", "function"),
+                rule(r"^\s*class\s+(?P<name>\w+)", "class"),
+            ]
+        }),
+    }
+}
+
+/// Keywords the method rule would otherwise pick up (`if (x) {`, `for (...) {`)
+const NOT_METHODS: &[&str] = &["if", "for", "while", "switch", "catch", "function", "return", "with", "else"];
+
+/// Extract the symbols declared in one source file
+fn extract_symbols(language: Language, file_path: &str, content: &str) -> Vec<Symbol> {
+    let lines: Vec<&str> = content.lines().collect();
+    let is_jsx = file_path.ends_with(".tsx") || file_path.ends_with(".jsx");
+    let mut symbols = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        // Comments, doc-comment continuations and Rust attributes
+        if trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*') || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let Some((rule, caps)) = rules(language)
+            .iter()
+            .find_map(|r| r.regex.captures(line).map(|c| (r, c)))
+        else {
+            continue;
+        };
+
+        let name = caps["name"].to_string();
+        let indented = line.len() != trimmed.len();
+        let mut kind = rule.kind;
+
+        match language {
+            Language::Rust if kind == "function" && indented => kind = "method",
+            Language::Python if kind == "function" && indented => kind = "method",
+            Language::TypeScript | Language::JavaScript => {
+                if kind == "method" && NOT_METHODS.contains(&name.as_str()) {
+                    continue;
+                }
+                // PascalCase functions in JSX files are React components
+                if kind == "function" && is_jsx && name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    kind = "component";
+                }
+            }
+            _ => {}
+        }
+
+        let exported = match language {
+            Language::Python => !indented && !name.starts_with('_'),
+            _ => caps.name("vis").is_some(),
+        };
+
+        let end = match language {
+            Language::Python => python_block_end(&lines, index),
+            _ => brace_block_end(&lines, index),
+        };
+
+        symbols.push(Symbol {
+            file_path: file_path.to_string(),
+            name,
+            kind: kind.to_string(),
+            start_line: index + 1,
+            end_line: end + 1,
+            exported,
+        });
+    }
+
+    symbols
+}
+
+/// Find the line closing the block opened at `start`, skipping braces inside
+/// strings, comments and parameter lists (destructured props). Declarations
+/// ending in `;` before any `{` are one statement.
+fn brace_block_end(lines: &[&str], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut parens = 0usize;
+    let mut opened = false;
+    // Strings can span lines (Rust literals, JS template strings)
+    let mut in_string: Option<char> = None;
+
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            match in_string {
+                Some(quote) => {
+                    if c == '\\' {
+                        i += 1;
+                    } else if c == quote {
+                        in_string = None;
+                    }
+                }
+                None => match c {
+                    '"' | '`' => in_string = Some(c),
+                    // Char literals like '"' or '{', but not Rust lifetimes
+                    '\'' if chars.get(i + 2) == Some(&'\'') => i += 2,
+                    '\'' if chars.get(i + 1) == Some(&'\\') && chars.get(i + 3) == Some(&'\'') => i += 3,
+                    '/' if chars.get(i + 1) == Some(&'/') => break,
+                    '(' => parens += 1,
+                    ')' => parens = parens.saturating_sub(1),
+                    '{' if parens == 0 => {
+                        depth += 1;
+                        opened = true;
+                    }
+                    '}' if parens == 0 => {
+                        depth = depth.saturating_sub(1);
+                        if opened && depth == 0 {
+                            return index;
+                        }
+                    }
+                    ';' if !opened && parens == 0 => return index,
+                    _ => {}
+                },
+            }
+            i += 1;
+        }
+
+        // Give up on runaway blocks rather than spanning the rest of the file
+        if index - start > 5000 {
+            break;
+        }
+    }
+
+    start
+}
+
+/// A Python block ends before the next non-blank line indented no deeper than its header
+fn python_block_end(lines: &[&str], start: usize) -> usize {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let header_indent = indent(lines[start]);
+    let mut end = start;
+
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indent(line) <= header_indent {
+            break;
+        }
+        end = index;
+    }
+
+    end
+}
+
+fn file_mtime(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as i64)
+}
+
+/// Bring the cached index for a project up to date, re-parsing only files whose
+/// mtime changed and dropping files that no longer exist
+fn refresh_index(app_handle: &AppHandle, project_path: &Path) -> Result<(), String> {
+    let key = project_path.to_string_lossy().to_string();
+
+    let indexed: HashMap<String, i64> = {
+        let db = get_db(app_handle);
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT file_path, mtime FROM symbol_files WHERE project_path = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([&key], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    // Parse outside the database lock; the first index of a big project takes a while
+    let walker = ProjectWalker::for_path(app_handle, project_path);
+    let mut seen = HashSet::new();
+    let mut changed = Vec::new();
+    for entry in walker.files() {
+        let Some(language) = Language::from_path(entry.path()) else {
+            continue;
+        };
+        if entry.metadata().map(|m| m.len() > MAX_INDEXED_FILE_SIZE).unwrap_or(true) {
+            continue;
+        }
+        let Some(mtime) = file_mtime(entry.path()) else {
+            continue;
+        };
+
+        let relative = walker.relative(entry.path());
+        seen.insert(relative.clone());
+        if indexed.get(&relative) == Some(&mtime) {
+            continue;
+        }
+
+        let symbols = fs::read_to_string(entry.path())
+            .map(|content| extract_symbols(language, &relative, &content))
+            .unwrap_or_default();
+        changed.push((relative, mtime, symbols));
+    }
+
+    let removed: Vec<&String> = indexed.keys().filter(|f| !seen.contains(*f)).collect();
+    if changed.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+
+    let db = get_db(app_handle);
+    let mut conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    for file in removed {
+        tx.execute(
+            "DELETE FROM symbol_files WHERE project_path = ?1 AND file_path = ?2",
+            [&key, file],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for (file, mtime, symbols) in &changed {
+        tx.execute(
+            "INSERT INTO symbol_files (project_path, file_path, mtime) VALUES (?1, ?2, ?3)
+             ON CONFLICT(project_path, file_path) DO UPDATE SET mtime = ?3",
+            rusqlite::params![&key, file, mtime],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM symbols WHERE project_path = ?1 AND file_path = ?2",
+            [&key, file],
+        )
+        .map_err(|e| e.to_string())?;
+        for symbol in symbols {
+            tx.execute(
+                "INSERT INTO symbols (project_path, file_path, name, kind, start_line, end_line, exported)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![
+                    &key,
+                    file,
+                    &symbol.name,
+                    &symbol.kind,
+                    symbol.start_line as i64,
+                    symbol.end_line as i64,
+                    symbol.exported,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    log::info!("Indexed {} changed files for {}", changed.len(), key);
+
+    Ok(())
+}
+
+fn query_symbols(
+    conn: &Connection,
+    sql: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<Symbol>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let symbols = stmt
+        .query_map(params, |row| {
+            Ok(Symbol {
+                file_path: row.get(0)?,
+                name: row.get(1)?,
+                kind: row.get(2)?,
+                start_line: row.get::<_, i64>(3)? as usize,
+                end_line: row.get::<_, i64>(4)? as usize,
+                exported: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(symbols)
+}
+
+/// List symbols in a project, optionally limited to one file or kind
+#[tauri::command]
+pub fn get_symbols(
+    app_handle: AppHandle,
+    project_path: String,
+    file_path: Option<String>,
+    kind: Option<String>,
+) -> Result<Vec<Symbol>, String> {
+    let root = validate_path(&app_handle, &project_path)?;
+    refresh_index(&app_handle, &root)?;
+
+    let key = root.to_string_lossy().to_string();
+    let file_path = file_path.map(|f| f.replace('\\', "/").trim_start_matches("./").to_string());
+
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    query_symbols(
+        &conn,
+        "SELECT file_path, name, kind, start_line, end_line, exported FROM symbols
+         WHERE project_path = ?1
+           AND (?2 IS NULL OR replace(file_path, '\\', '/') = ?2)
+           AND (?3 IS NULL OR kind = ?3)
+         ORDER BY file_path, start_line
+         LIMIT 2000",
+        &[&key, &file_path, &kind],
+    )
+}
+
+/// Find where a symbol is defined across the project. Exact name matches come
+/// first; without any, falls back to a case-insensitive match.
+#[tauri::command]
+pub fn find_definition(
+    app_handle: AppHandle,
+    project_path: String,
+    name: String,
+) -> Result<Vec<Symbol>, String> {
+    let root = validate_path(&app_handle, &project_path)?;
+    refresh_index(&app_handle, &root)?;
+
+    let key = root.to_string_lossy().to_string();
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let select = "SELECT file_path, name, kind, start_line, end_line, exported FROM symbols
+         WHERE project_path = ?1 AND kind != 'impl' AND";
+    let exact = query_symbols(
+        &conn,
+        &format!("{} name = ?2 ORDER BY exported DESC, file_path LIMIT 50", select),
+        &[&key, &name],
+    )?;
+    if !exact.is_empty() {
+        return Ok(exact);
+    }
+
+    query_symbols(
+        &conn,
+        &format!("{} name = ?2 COLLATE NOCASE ORDER BY exported DESC, file_path LIMIT 50", select),
+        &[&key, &name],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(name, kind, start_line, end_line, exported)` for each symbol
+    fn extract(language: Language, file_path: &str, content: &str) -> Vec<(String, String, usize, usize, bool)> {
+        extract_symbols(language, file_path, content)
+            .into_iter()
+            .map(|s| (s.name, s.kind, s.start_line, s.end_line, s.exported))
+            .collect()
+    }
+
+    fn sym(name: &str, kind: &str, start: usize, end: usize, exported: bool) -> (String, String, usize, usize, bool) {
+        (name.to_string(), kind.to_string(), start, end, exported)
+    }
+
+    #[test]
+    fn rust_declarations() {
+        let source = r#"use std::fmt;
+
+/// Docs
+pub struct Point {
+    x: i32,
+}
+
+enum Shape { Circle, Square }
+
+pub(crate) trait Area {
+    fn area(&self) -> f64;
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")
+    }
+}
+
+pub const MAX_POINTS: usize = 10;
+type Pair = (i32, i32);
+pub mod geometry;
+
+macro_rules! square {
+    ($x:expr) => { $x * $x };
+}
+
+pub async fn load() {}
+"#;
+        assert_eq!(
+            extract(Language::Rust, "src/lib.rs", source),
+            vec![
+                sym("Point", "struct", 4, 6, true),
+                sym("Shape", "enum", 8, 8, false),
+                sym("Area", "trait", 10, 12, true),
+                sym("area", "method", 11, 11, false),
+                sym("Point", "impl", 14, 18, false),
+                sym("fmt", "method", 15, 17, false),
+                sym("MAX_POINTS", "const", 20, 20, true),
+                sym("Pair", "type", 21, 21, false),
+                sym("geometry", "module", 22, 22, true),
+                sym("square", "macro", 24, 26, false),
+                sym("load", "function", 28, 28, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn typescript_declarations() {
+        let source = "export interface Props {
+  name: string;
+}
+
+export type Id = string;
+
+export function greet(name: string): string {
+  if (name) {
+    return name;
+  }
+  return \"\";
+}
+
+const add = (a: number, b: number) => a + b;
+
+export const VERSION = \"1.0\";
+
+export default class Store {
+  load(id: Id): void {
+  }
+}
+
+enum Mode { On, Off }
+";
+        assert_eq!(
+            extract(Language::TypeScript, "src/lib.ts", source),
+            vec![
+                sym("Props", "interface", 1, 3, true),
+                sym("Id", "type", 5, 5, true),
+                sym("greet", "function", 7, 12, true),
+                sym("add", "function", 14, 14, false),
+                sym("VERSION", "const", 16, 16, true),
+                sym("Store", "class", 18, 21, true),
+                sym("load", "method", 19, 20, false),
+                sym("Mode", "enum", 23, 23, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn jsx_components() {
+        let source = "export function Button({ label }: { label: string }) {
+  return <button>{label}</button>;
+}
+
+const Memo = React.memo(() => null);
+
+function helper() {}
+";
+        assert_eq!(
+            extract(Language::TypeScript, "src/button.tsx", source),
+            vec![
+                sym("Button", "component", 1, 3, true),
+                sym("Memo", "component", 5, 5, false),
+                sym("helper", "function", 7, 7, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn javascript_declarations() {
+        let source = "async function fetchAll() {
+  for (const x of []) {
+  }
+}
+
+export const handler = async (event) => {
+  return event;
+};
+";
+        assert_eq!(
+            extract(Language::JavaScript, "index.js", source),
+            vec![
+                sym("fetchAll", "function", 1, 4, false),
+                sym("handler", "function", 6, 8, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_declarations() {
+        let source = "import os
+
+class Repo:
+    def __init__(self):
+        self.path = os.getcwd()
+
+    async def _load(self):
+        pass
+
+def main():
+    Repo()
+
+def _private():
+    pass
+";
+        assert_eq!(
+            extract(Language::Python, "app.py", source),
+            vec![
+                sym("Repo", "class", 3, 8, true),
+                sym("__init__", "method", 4, 5, false),
+                sym("_load", "method", 7, 8, false),
+                sym("main", "function", 10, 11, true),
+                sym("_private", "function", 13, 14, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_attributes() {
+        let source = "// fn commented() {}\n#[derive(Debug)]\n/* struct Hidden; */\nfn visible() {}\n";
+        assert_eq!(extract(Language::Rust, "a.rs", source), vec![sym("visible", "function", 4, 4, false)]);
+    }
+
+    #[test]
+    fn language_from_extension() {
+        assert_eq!(Language::from_path(Path::new("a.rs")), Some(Language::Rust));
+        assert_eq!(Language::from_path(Path::new("a.tsx")), Some(Language::TypeScript));
+        assert_eq!(Language::from_path(Path::new("a.mjs")), Some(Language::JavaScript));
+        assert_eq!(Language::from_path(Path::new("a.py")), Some(Language::Python));
+        assert_eq!(Language::from_path(Path::new("a.go")), None);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::db::get_db;
+use crate::llm::TokenUsage;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelPrice {
+    pub model: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+}
+
+/// Aggregated token counts and cost for one group (project, day or model)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CostSummary {
+    pub key: Option<String>,
+    pub requests: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cost_usd: f64,
+    /// Requests whose model has no entry in the price table
+    pub unpriced_requests: i64,
+}
+
+/// One recorded API round-trip
+pub struct UsageRecord<'a> {
+    pub request_id: &'a str,
+    pub conversation_id: Option<&'a str>,
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub usage: TokenUsage,
+}
+
+/// Record token usage for a round-trip, attributing it to the conversation's project
+pub fn record_usage(conn: &Connection, record: &UsageRecord) -> rusqlite::Result<()> {
+    // Unknown (or deleted) conversations are recorded without a link
+    let (conversation_id, project_id): (Option<String>, Option<String>) = record
+        .conversation_id
+        .and_then(|cid| {
+            conn.query_row(
+                "SELECT id, project_id FROM conversations WHERE id = ?1",
+                [cid],
+                |row| Ok((Some(row.get(0)?), row.get(1)?)),
+            )
+            .ok()
+        })
+        .unwrap_or((None, None));
+
+    conn.execute(
+        "INSERT INTO token_usage (id, request_id, conversation_id, project_id, provider, model,
+                input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![
+            Uuid::new_v4().to_string(),
+            record.request_id,
+            conversation_id,
+            project_id,
+            record.provider,
+            record.model,
+            record.usage.input_tokens,
+            record.usage.output_tokens,
+            record.usage.cache_creation_tokens,
+            record.usage.cache_read_tokens,
+            Utc::now().to_rfc3339(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Group usage by a fixed SQL expression over `token_usage u`
+fn cost_summary(
+    conn: &Connection,
+    group_expr: &str,
+    project_id: Option<&str>,
+    since: Option<&str>,
+) -> rusqlite::Result<Vec<CostSummary>> {
+    let query = format!(
+        "SELECT {group} AS key,
+                COUNT(*),
+                COALESCE(SUM(u.input_tokens), 0),
+                COALESCE(SUM(u.output_tokens), 0),
+                COALESCE(SUM(u.cache_creation_tokens), 0),
+                COALESCE(SUM(u.cache_read_tokens), 0),
+                COALESCE(SUM((u.input_tokens * p.input_per_mtok
+                             + u.output_tokens * p.output_per_mtok
+                             + u.cache_creation_tokens * p.cache_write_per_mtok
+                             + u.cache_read_tokens * p.cache_read_per_mtok) / 1000000.0), 0.0),
+                SUM(CASE WHEN p.model IS NULL THEN 1 ELSE 0 END)
+         FROM token_usage u
+         LEFT JOIN model_prices p ON p.model = u.model
+         WHERE (?1 IS NULL OR u.project_id = ?1)
+           AND (?2 IS NULL OR u.created_at >= ?2)
+         GROUP BY key
+         ORDER BY key",
+        group = group_expr
+    );
+
+    let mut stmt = conn.prepare(&query)?;
+    let rows = stmt
+        .query_map(rusqlite::params![project_id, since], |row| {
+            Ok(CostSummary {
+                key: row.get(0)?,
+                requests: row.get(1)?,
+                input_tokens: row.get(2)?,
+                output_tokens: row.get(3)?,
+                cache_creation_tokens: row.get(4)?,
+                cache_read_tokens: row.get(5)?,
+                cost_usd: row.get(6)?,
+                unpriced_requests: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows)
+}
+
+/// Cost totals per project (key is the project id, None for chats outside a project)
+#[tauri::command]
+pub fn get_usage_by_project(app_handle: AppHandle) -> Result<Vec<CostSummary>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    cost_summary(&conn, "u.project_id", None, None).map_err(|e| e.to_string())
+}
+
+/// Cost totals per UTC day (key is YYYY-MM-DD), optionally limited to one project
+#[tauri::command]
+pub fn get_usage_by_day(
+    app_handle: AppHandle,
+    project_id: Option<String>,
+    days: Option<i64>,
+) -> Result<Vec<CostSummary>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let since = days.map(|d| (Utc::now() - chrono::Duration::days(d)).format("%Y-%m-%d").to_string());
+
+    cost_summary(&conn, "substr(u.created_at, 1, 10)", project_id.as_deref(), since.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Cost totals per model, optionally limited to one project
+#[tauri::command]
+pub fn get_usage_by_model(
+    app_handle: AppHandle,
+    project_id: Option<String>,
+) -> Result<Vec<CostSummary>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    cost_summary(&conn, "u.model", project_id.as_deref(), None).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_model_prices(app_handle: AppHandle) -> Result<Vec<ModelPrice>, String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT model, input_per_mtok, output_per_mtok, cache_write_per_mtok, cache_read_per_mtok
+             FROM model_prices ORDER BY model",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let prices = stmt
+        .query_map([], |row| {
+            Ok(ModelPrice {
+                model: row.get(0)?,
+                input_per_mtok: row.get(1)?,
+                output_per_mtok: row.get(2)?,
+                cache_write_per_mtok: row.get(3)?,
+                cache_read_per_mtok: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(prices)
+}
+
+#[tauri::command]
+pub fn set_model_price(app_handle: AppHandle, price: ModelPrice) -> Result<(), String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO model_prices (model, input_per_mtok, output_per_mtok, cache_write_per_mtok, cache_read_per_mtok)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(model) DO UPDATE SET
+            input_per_mtok = ?2, output_per_mtok = ?3, cache_write_per_mtok = ?4, cache_read_per_mtok = ?5",
+        rusqlite::params![
+            &price.model,
+            price.input_per_mtok,
+            price.output_per_mtok,
+            price.cache_write_per_mtok,
+            price.cache_read_per_mtok,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_model_price(app_handle: AppHandle, model: String) -> Result<(), String> {
+    let db = get_db(&app_handle);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+
+    conn.execute("DELETE FROM model_prices WHERE model = ?1", [&model])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = crate::db::open_in_memory();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, slug) VALUES ('p1', 'App', 'app');
+             INSERT INTO conversations (id, project_id, title) VALUES ('c1', 'p1', 'In a project');
+             INSERT INTO conversations (id, project_id, title) VALUES ('c2', NULL, 'Loose chat');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn record(conn: &Connection, conversation_id: Option<&str>, model: &str, usage: TokenUsage) {
+        record_usage(
+            conn,
+            &UsageRecord {
+                request_id: "req",
+                conversation_id,
+                provider: "anthropic",
+                model,
+                usage,
+            },
+        )
+        .unwrap();
+    }
+
+    fn usage(input_tokens: i64, output_tokens: i64, cache_creation_tokens: i64, cache_read_tokens: i64) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            output_tokens,
+            cache_creation_tokens,
+            cache_read_tokens,
+        }
+    }
+
+    #[test]
+    fn usage_is_attributed_through_the_conversation() {
+        let conn = setup();
+        record(&conn, Some("c1"), "claude-sonnet-4-20250514", usage(10, 20, 0, 0));
+        record(&conn, Some("c2"), "claude-sonnet-4-20250514", usage(1, 2, 0, 0));
+        // A deleted or unknown conversation doesn't fail the insert
+        record(&conn, Some("gone"), "claude-sonnet-4-20250514", usage(1, 2, 0, 0));
+        record(&conn, None, "claude-sonnet-4-20250514", usage(1, 2, 0, 0));
+
+        let links: Vec<(Option<String>, Option<String>)> = conn
+            .prepare("SELECT conversation_id, project_id FROM token_usage ORDER BY input_tokens DESC, rowid")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(links[0], (Some("c1".to_string()), Some("p1".to_string())));
+        assert_eq!(links[1], (Some("c2".to_string()), None));
+        assert_eq!(links[2], (None, None));
+        assert_eq!(links[3], (None, None));
+
+        let by_project = cost_summary(&conn, "u.project_id", None, None).unwrap();
+        let keys: Vec<(Option<&str>, i64)> = by_project.iter().map(|s| (s.key.as_deref(), s.requests)).collect();
+        assert_eq!(keys, [(None, 3), (Some("p1"), 1)]);
+
+        let only_p1 = cost_summary(&conn, "u.model", Some("p1"), None).unwrap();
+        assert_eq!(only_p1.len(), 1);
+        assert_eq!((only_p1[0].input_tokens, only_p1[0].output_tokens), (10, 20));
+    }
+
+    #[test]
+    fn cost_joins_the_price_table() {
+        let conn = setup();
+        // $3 / $15 / $3.75 / $0.30 per million tokens
+        record(&conn, Some("c1"), "claude-sonnet-4-20250514", usage(1_000_000, 100_000, 200_000, 1_000_000));
+        record(&conn, Some("c1"), "claude-sonnet-4-20250514", usage(1_000_000, 0, 0, 0));
+        record(&conn, Some("c1"), "local-llama", usage(5_000_000, 5_000_000, 0, 0));
+
+        let by_model = cost_summary(&conn, "u.model", None, None).unwrap();
+        assert_eq!(by_model.len(), 2);
+
+        let sonnet = &by_model[0];
+        assert_eq!(sonnet.key.as_deref(), Some("claude-sonnet-4-20250514"));
+        assert_eq!(sonnet.requests, 2);
+        assert_eq!(sonnet.input_tokens, 2_000_000);
+        assert_eq!(sonnet.cache_creation_tokens, 200_000);
+        assert!((sonnet.cost_usd - (6.0 + 1.5 + 0.75 + 0.3)).abs() < 1e-9, "{}", sonnet.cost_usd);
+        assert_eq!(sonnet.unpriced_requests, 0);
+
+        // Models without a price row count their tokens but cost nothing
+        let unpriced = &by_model[1];
+        assert_eq!(unpriced.key.as_deref(), Some("local-llama"));
+        assert_eq!(unpriced.output_tokens, 5_000_000);
+        assert_eq!(unpriced.cost_usd, 0.0);
+        assert_eq!(unpriced.unpriced_requests, 1);
+
+        let total = cost_summary(&conn, "u.project_id", None, None).unwrap();
+        assert_eq!((total[0].requests, total[0].unpriced_requests), (3, 1));
+        assert!((total[0].cost_usd - sonnet.cost_usd).abs() < 1e-9);
+    }
+
+    #[test]
+    fn daily_totals_respect_the_since_filter() {
+        let conn = setup();
+        record(&conn, Some("c1"), "gpt-4o", usage(100, 100, 0, 0));
+        conn.execute("UPDATE token_usage SET created_at = '2024-01-01T12:00:00+00:00'", []).unwrap();
+        record(&conn, Some("c1"), "gpt-4o", usage(100, 100, 0, 0));
+
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        let all = cost_summary(&conn, "substr(u.created_at, 1, 10)", None, None).unwrap();
+        let days: Vec<&str> = all.iter().filter_map(|s| s.key.as_deref()).collect();
+        assert_eq!(days, ["2024-01-01", today.as_str()]);
+
+        let recent = cost_summary(&conn, "substr(u.created_at, 1, 10)", Some("p1"), Some("2024-06-01")).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].key.as_deref(), Some(today.as_str()));
+    }
+}
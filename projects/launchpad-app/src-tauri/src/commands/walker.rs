@@ -0,0 +1,304 @@
+use crate::commands::path_policy::{self, PathPolicy};
+use crate::db::get_db;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{DirEntry, WalkBuilder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::AppHandle;
+
+/// Settings key holding per-project ignore overrides as a JSON object of
+/// `{ "<project path>": ["pattern", "!pattern", ...] }`
+pub const IGNORE_OVERRIDES_SETTING: &str = "ignore_overrides";
+
+/// Directories skipped even when a project has no .gitignore. These are exact
+/// directory names, so `outbox/` or `targets/` are still walked. A project can
+/// re-include one with a negated override such as `!dist/`.
+const DEFAULT_IGNORES: &[&str] = &[
+    ".git/",
+    "node_modules/",
+    ".next/",
+    "target/",
+    "dist/",
+    "out/",
+    "coverage/",
+    ".venv/",
+    "venv/",
+    "__pycache__/",
+    ".turbo/",
+];
+
+/// Walks a project directory honouring `.gitignore`, `.ignore`, the built-in
+/// defaults and the project's override list from settings. Every file listing
+/// in the app goes through this so the assistant and the analyzer agree.
+#[derive(Clone)]
+pub struct ProjectWalker {
+    root: PathBuf,
+    max_depth: Option<usize>,
+    /// Honour .gitignore/.ignore files (the built-in defaults always apply)
+    ignore_files: bool,
+    rules: Gitignore,
+    /// Sensitive-path policy checked for every entry, so a walk never descends
+    /// into a denied directory even when the root itself is allowed
+    guard: Option<EntryGuard>,
+}
+
+#[derive(Clone)]
+struct EntryGuard {
+    policy: Arc<PathPolicy>,
+    app_handle: AppHandle,
+}
+
+impl EntryGuard {
+    fn allows(&self, path: &Path) -> bool {
+        let Err(reason) = self.policy.check(path) else {
+            return true;
+        };
+        match get_db(&self.app_handle).conn.lock() {
+            Ok(conn) => path_policy::record_denial(&conn, path, &reason),
+            Err(_) => log::warn!("Blocked access to {}: {}", path.display(), reason),
+        }
+        false
+    }
+}
+
+impl ProjectWalker {
+    /// `rules_root` is the directory anchored override patterns are relative to,
+    /// normally the project root (which may be an ancestor of `root`)
+    pub fn new(root: &Path, rules_root: &Path, overrides: &[String]) -> Self {
+        let mut builder = GitignoreBuilder::new(rules_root);
+        for pattern in DEFAULT_IGNORES.iter().copied().chain(overrides.iter().map(|s| s.as_str())) {
+            if let Err(e) = builder.add_line(None, pattern) {
+                log::warn!("Invalid ignore pattern {:?}: {}", pattern, e);
+            }
+        }
+        let rules = builder.build().unwrap_or_else(|e| {
+            log::warn!("Failed to build ignore rules: {}", e);
+            Gitignore::empty()
+        });
+
+        Self {
+            root: root.to_path_buf(),
+            max_depth: None,
+            ignore_files: true,
+            rules,
+            guard: None,
+        }
+    }
+
+    /// Build a walker using the overrides saved for whichever project contains
+    /// `root`, skipping (and auditing) entries the path policy denies
+    pub fn for_path(app_handle: &AppHandle, root: &Path) -> Self {
+        let mut walker = match load_overrides(app_handle, root) {
+            Some((project_root, overrides)) => Self::new(root, &project_root, &overrides),
+            None => Self::new(root, root, &[]),
+        };
+        match get_db(app_handle).conn.lock() {
+            Ok(conn) => {
+                walker.guard = Some(EntryGuard {
+                    policy: Arc::new(PathPolicy::load(&conn)),
+                    app_handle: app_handle.clone(),
+                })
+            }
+            Err(e) => log::warn!("Failed to load path policy for {}: {}", root.display(), e),
+        }
+        walker
+    }
+
+    /// The same rules applied to a subdirectory, e.g. one package of a monorepo
+    pub fn scoped(&self, root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            max_depth: self.max_depth,
+            ignore_files: self.ignore_files,
+            rules: self.rules.clone(),
+            guard: self.guard.clone(),
+        }
+    }
+
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Also walk files excluded by .gitignore, e.g. to inspect local `.env` files
+    pub fn include_gitignored(mut self) -> Self {
+        self.ignore_files = false;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Entries below the root (the root itself is skipped), directories first
+    /// and then by name at each level
+    pub fn walk(&self) -> impl Iterator<Item = DirEntry> + '_ {
+        let mut builder = WalkBuilder::new(&self.root);
+        builder
+            .hidden(false)
+            .require_git(false)
+            .git_ignore(self.ignore_files)
+            .git_exclude(self.ignore_files)
+            .ignore(self.ignore_files)
+            .max_depth(self.max_depth)
+            .sort_by_file_path(|a, b| b.is_dir().cmp(&a.is_dir()).then_with(|| a.file_name().cmp(&b.file_name())));
+
+        let rules = self.rules.clone();
+        let guard = self.guard.clone();
+        builder.filter_entry(move |entry| {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if rules.matched(entry.path(), is_dir).is_ignore() {
+                return false;
+            }
+            match guard {
+                // The root was already validated by the caller
+                Some(ref guard) if entry.depth() > 0 => guard.allows(entry.path()),
+                _ => true,
+            }
+        });
+
+        builder
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.depth() > 0)
+    }
+
+    /// Files only, skipping directories
+    pub fn files(&self) -> impl Iterator<Item = DirEntry> + '_ {
+        self.walk().filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+    }
+
+    /// Path of an entry relative to the walk root
+    pub fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+/// Find the override list for the project containing `path`. When projects are
+/// nested the most specific one wins.
+fn load_overrides(app_handle: &AppHandle, path: &Path) -> Option<(PathBuf, Vec<String>)> {
+    let db = get_db(app_handle);
+    let conn = db.conn.lock().ok()?;
+
+    let all: HashMap<String, Vec<String>> = conn
+        .query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            [IGNORE_OVERRIDES_SETTING],
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default();
+
+    all.into_iter()
+        .filter_map(|(project, patterns)| {
+            let project = Path::new(&project).canonicalize().ok()?;
+            path.starts_with(&project).then_some((project, patterns))
+        })
+        .max_by_key(|(project, _)| project.components().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(root: &Path, relative: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+
+    fn fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [
+            "src/main.rs",
+            "src/lib.rs",
+            "node_modules/react/index.js",
+            "target/debug/app",
+            "dist/bundle.js",
+            "targets/notes.md",
+            "outbox/mail.txt",
+            "out",
+            "app.log",
+            "secrets/key.pem",
+            "scratch/tmp.txt",
+            "docs/guide.md",
+        ] {
+            write(dir.path(), file);
+        }
+        fs::write(dir.path().join(".gitignore"), "*.log\nsecrets/\n").unwrap();
+        fs::write(dir.path().join(".ignore"), "scratch/\n").unwrap();
+        dir
+    }
+
+    fn files(walker: &ProjectWalker) -> Vec<String> {
+        walker.files().map(|e| walker.relative(e.path()).replace('\\', "/")).collect()
+    }
+
+    #[test]
+    fn defaults_and_ignore_files_are_honoured() {
+        let dir = fixture();
+        let walker = ProjectWalker::new(dir.path(), dir.path(), &[]);
+
+        // Directories first, then by name; `outbox/`, `targets/` and a file named `out` aren't defaults
+        assert_eq!(
+            files(&walker),
+            ["docs/guide.md", "outbox/mail.txt", "src/lib.rs", "src/main.rs", "targets/notes.md", ".gitignore", ".ignore", "out"]
+        );
+    }
+
+    #[test]
+    fn gitignored_files_can_be_included_but_defaults_still_apply() {
+        let dir = fixture();
+        let walker = ProjectWalker::new(dir.path(), dir.path(), &[]).include_gitignored();
+
+        let files = files(&walker);
+        assert!(files.contains(&"app.log".to_string()));
+        assert!(files.contains(&"secrets/key.pem".to_string()));
+        assert!(files.contains(&"scratch/tmp.txt".to_string()));
+        assert!(!files.iter().any(|f| f.starts_with("node_modules/") || f.starts_with("target/")));
+    }
+
+    #[test]
+    fn overrides_add_and_negate_patterns() {
+        let dir = fixture();
+        let overrides = ["!dist/".to_string(), "docs/".to_string(), "*.md".to_string()];
+        let walker = ProjectWalker::new(dir.path(), dir.path(), &overrides);
+
+        let files = files(&walker);
+        assert!(files.contains(&"dist/bundle.js".to_string()));
+        assert!(!files.iter().any(|f| f.starts_with("docs/") || f.ends_with(".md")));
+        assert!(!files.iter().any(|f| f.starts_with("node_modules/")));
+    }
+
+    #[test]
+    fn anchored_overrides_are_relative_to_the_rules_root() {
+        let dir = fixture();
+        write(dir.path(), "apps/web/build/out.js");
+        write(dir.path(), "apps/web/index.ts");
+        write(dir.path(), "apps/api/build/out.js");
+        let overrides = ["/apps/web/build/".to_string()];
+
+        let walker = ProjectWalker::new(dir.path(), dir.path(), &overrides);
+        let web = walker.scoped(&dir.path().join("apps/web"));
+        assert_eq!(files(&web), ["index.ts"]);
+
+        let api = ProjectWalker::new(&dir.path().join("apps/api"), dir.path(), &overrides);
+        assert_eq!(files(&api), ["build/out.js"]);
+    }
+
+    #[test]
+    fn max_depth_limits_the_walk() {
+        let dir = fixture();
+        let walker = ProjectWalker::new(dir.path(), dir.path(), &[]).max_depth(1);
+
+        let entries: Vec<String> = walker.walk().map(|e| walker.relative(e.path())).collect();
+        assert!(entries.contains(&"src".to_string()));
+        assert!(!entries.iter().any(|e| e.contains(std::path::MAIN_SEPARATOR)));
+    }
+}
@@ -0,0 +1,707 @@
+use rusqlite::{Connection, Transaction};
+
+/// A single schema change, applied once and recorded in `PRAGMA user_version`
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Sqlite(rusqlite::Error),
+    DatabaseTooNew { found: i64, supported: i64 },
+    Failed { version: i64, description: &'static str, source: rusqlite::Error },
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::Sqlite(e) => write!(f, "Database error: {}", e),
+            MigrationError::DatabaseTooNew { found, supported } => write!(
+                f,
+                "Database schema version {} is newer than this build supports ({}). Please update Launchpad.",
+                found, supported
+            ),
+            MigrationError::Failed { version, description, source } => {
+                write!(f, "Migration {} ({}) failed: {}", version, description, source)
+            }
+        }
+    }
+}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+/// Ordered list of every schema change. Never edit a released migration -
+/// append a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Initial schema",
+        // Uses IF NOT EXISTS so databases created before versioning
+        // (user_version = 0 with tables present) upgrade cleanly
+        sql: "
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS projects (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                slug TEXT UNIQUE NOT NULL,
+                local_path TEXT,
+                github_url TEXT,
+                current_phase INTEGER DEFAULT 0,
+                status_report TEXT,
+                last_analyzed TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS conversations (
+                id TEXT PRIMARY KEY,
+                project_id TEXT,
+                title TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS messages (
+                id TEXT PRIMARY KEY,
+                conversation_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS roadmap_items (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                sop_number INTEGER NOT NULL,
+                sop_name TEXT NOT NULL,
+                status TEXT DEFAULT 'pending',
+                ai_notes TEXT,
+                completed_at TEXT,
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS sops (
+                id TEXT PRIMARY KEY,
+                sop_number INTEGER NOT NULL,
+                version TEXT NOT NULL,
+                name TEXT NOT NULL,
+                phase TEXT NOT NULL,
+                content TEXT NOT NULL,
+                is_active INTEGER DEFAULT 1,
+                tags TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                archived_at TEXT,
+                UNIQUE(sop_number, version)
+            );
+
+            CREATE TABLE IF NOT EXISTS ideas (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                slug TEXT UNIQUE NOT NULL,
+                problem_statement TEXT,
+                proposed_solution TEXT,
+                source TEXT,
+                status TEXT DEFAULT 'pending',
+                audit_result TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                audited_at TEXT,
+                activated_at TEXT,
+                project_id TEXT,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            );
+
+            CREATE TABLE IF NOT EXISTS shot_clock_sessions (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                phase_number INTEGER NOT NULL,
+                allocated_time_seconds INTEGER NOT NULL,
+                bonus_time_seconds INTEGER DEFAULT 0,
+                time_remaining_seconds INTEGER NOT NULL,
+                status TEXT DEFAULT 'active',
+                started_at TEXT NOT NULL DEFAULT (datetime('now')),
+                completed_at TEXT,
+                locked_until TEXT,
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_messages_conversation
+                ON messages(conversation_id);
+            CREATE INDEX IF NOT EXISTS idx_roadmap_project
+                ON roadmap_items(project_id);
+            CREATE INDEX IF NOT EXISTS idx_conversations_project
+                ON conversations(project_id);
+            CREATE INDEX IF NOT EXISTS idx_sops_number
+                ON sops(sop_number);
+            CREATE INDEX IF NOT EXISTS idx_ideas_status
+                ON ideas(status);
+            CREATE INDEX IF NOT EXISTS idx_shot_clock_project_phase
+                ON shot_clock_sessions(project_id, phase_number);
+        ",
+    },
+    Migration {
+        version: 2,
+        description: "Structured content blocks on messages",
+        // JSON array of Messages API content blocks (text, tool_use, tool_result)
+        sql: "ALTER TABLE messages ADD COLUMN content_blocks TEXT;",
+    },
+    Migration {
+        version: 3,
+        description: "Per-conversation LLM provider and model",
+        sql: "
+            ALTER TABLE conversations ADD COLUMN provider TEXT;
+            ALTER TABLE conversations ADD COLUMN model TEXT;
+        ",
+    },
+    Migration {
+        version: 4,
+        description: "Token usage accounting and model prices",
+        sql: "
+            CREATE TABLE token_usage (
+                id TEXT PRIMARY KEY,
+                request_id TEXT NOT NULL,
+                conversation_id TEXT,
+                project_id TEXT,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL DEFAULT 0,
+                output_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE SET NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE SET NULL
+            );
+
+            CREATE INDEX idx_token_usage_project ON token_usage(project_id);
+            CREATE INDEX idx_token_usage_conversation ON token_usage(conversation_id);
+
+            -- Prices in USD per million tokens
+            CREATE TABLE model_prices (
+                model TEXT PRIMARY KEY,
+                input_per_mtok REAL NOT NULL DEFAULT 0,
+                output_per_mtok REAL NOT NULL DEFAULT 0,
+                cache_write_per_mtok REAL NOT NULL DEFAULT 0,
+                cache_read_per_mtok REAL NOT NULL DEFAULT 0
+            );
+
+            INSERT INTO model_prices (model, input_per_mtok, output_per_mtok, cache_write_per_mtok, cache_read_per_mtok) VALUES
+                ('claude-sonnet-4-20250514', 3.0, 15.0, 3.75, 0.30),
+                ('claude-opus-4-20250514', 15.0, 75.0, 18.75, 1.50),
+                ('claude-3-5-haiku-20241022', 0.80, 4.0, 1.0, 0.08),
+                ('gpt-4o', 2.50, 10.0, 0, 1.25),
+                ('gpt-4o-mini', 0.15, 0.60, 0, 0.075);
+        ",
+    },
+    Migration {
+        version: 5,
+        description: "Staged file changes from the chat agent",
+        sql: "
+            CREATE TABLE file_changes (
+                id TEXT PRIMARY KEY,
+                conversation_id TEXT,
+                base_path TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                original_content TEXT,
+                new_content TEXT NOT NULL,
+                diff TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                resolved_at TEXT,
+                FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE SET NULL
+            );
+
+            CREATE INDEX idx_file_changes_status ON file_changes(status);
+        ",
+    },
+    Migration {
+        version: 6,
+        description: "Log of commands run by the chat agent",
+        sql: "
+            CREATE TABLE command_runs (
+                id TEXT PRIMARY KEY,
+                conversation_id TEXT,
+                working_dir TEXT NOT NULL,
+                command TEXT NOT NULL,
+                status TEXT NOT NULL,
+                exit_code INTEGER,
+                duration_ms INTEGER NOT NULL DEFAULT 0,
+                stdout_bytes INTEGER NOT NULL DEFAULT 0,
+                stderr_bytes INTEGER NOT NULL DEFAULT 0,
+                error TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE SET NULL
+            );
+
+            CREATE INDEX idx_command_runs_created ON command_runs(created_at);
+        ",
+    },
+    Migration {
+        version: 7,
+        description: "Symbol index for linked projects",
+        sql: "
+            CREATE TABLE symbol_files (
+                project_path TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                mtime INTEGER NOT NULL,
+                PRIMARY KEY (project_path, file_path)
+            );
+
+            CREATE TABLE symbols (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_path TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                exported INTEGER NOT NULL DEFAULT 0,
+                FOREIGN KEY (project_path, file_path)
+                    REFERENCES symbol_files(project_path, file_path) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_symbols_file ON symbols(project_path, file_path);
+            CREATE INDEX idx_symbols_name ON symbols(project_path, name);
+        ",
+    },
+    Migration {
+        version: 8,
+        description: "Audit log of denied file access",
+        sql: "
+            CREATE TABLE path_denials (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+        ",
+    },
+    Migration {
+        version: 9,
+        description: "Resolved dependency inventory per saved analysis",
+        sql: "
+            CREATE TABLE dependency_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id TEXT NOT NULL,
+                analyzed_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE snapshot_dependencies (
+                snapshot_id INTEGER NOT NULL,
+                ecosystem TEXT NOT NULL,
+                name TEXT NOT NULL,
+                version TEXT NOT NULL,
+                direct INTEGER NOT NULL DEFAULT 0,
+                FOREIGN KEY (snapshot_id) REFERENCES dependency_snapshots(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_dependency_snapshots_project ON dependency_snapshots(project_id, analyzed_at);
+            CREATE INDEX idx_snapshot_dependencies_snapshot ON snapshot_dependencies(snapshot_id);
+            CREATE INDEX idx_snapshot_dependencies_name ON snapshot_dependencies(name, version);
+        ",
+    },
+    Migration {
+        version: 10,
+        description: "Let analysis findings block roadmap items",
+        sql: "
+            ALTER TABLE roadmap_items ADD COLUMN blocked_reason TEXT;
+        ",
+    },
+    Migration {
+        version: 11,
+        description: "Analysis history per project",
+        sql: "
+            CREATE TABLE project_analyses (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id TEXT NOT NULL,
+                analyzed_at TEXT NOT NULL,
+                phase INTEGER NOT NULL,
+                phase_name TEXT NOT NULL,
+                file_count INTEGER NOT NULL,
+                has_tests INTEGER NOT NULL DEFAULT 0,
+                services TEXT NOT NULL DEFAULT '[]',
+                recommendation_count INTEGER NOT NULL DEFAULT 0,
+                report TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_project_analyses_project ON project_analyses(project_id, analyzed_at);
+
+            ALTER TABLE dependency_snapshots ADD COLUMN analysis_id INTEGER
+                REFERENCES project_analyses(id) ON DELETE CASCADE;
+        ",
+    },
+    Migration {
+        version: 12,
+        description: "Machine-checkable gates per SOP version",
+        sql: "
+            ALTER TABLE sops ADD COLUMN gates TEXT;
+        ",
+    },
+    Migration {
+        version: 13,
+        description: "Recommendation rules and per-project silencing",
+        sql: r#"
+            CREATE TABLE recommendation_rules (
+                id TEXT PRIMARY KEY,
+                condition TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                message TEXT NOT NULL,
+                sop_number INTEGER,
+                project_types TEXT NOT NULL DEFAULT '[]',
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE silenced_recommendations (
+                project_id TEXT NOT NULL,
+                rule_id TEXT NOT NULL,
+                silenced_at TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (project_id, rule_id),
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+                FOREIGN KEY (rule_id) REFERENCES recommendation_rules(id) ON DELETE CASCADE
+            );
+
+            INSERT INTO recommendation_rules (id, condition, severity, message, sop_number, project_types) VALUES
+                ('committed-secrets',
+                 '{"op":"present","field":"secret_scan.findings[severity=critical]"}',
+                 'critical',
+                 'Remove and rotate credentials found in tracked files: {secret_scan.findings[severity=critical].file}',
+                 9, '[]'),
+                ('critical-vulnerabilities',
+                 '{"op":"present","field":"vulnerability_audit.findings[severity=critical]"}',
+                 'critical',
+                 'Upgrade dependencies with critical vulnerabilities before shipping: {vulnerability_audit.findings[severity=critical].package}',
+                 9, '[]'),
+                ('env-example',
+                 '{"op":"not","condition":{"op":"present","field":"env_contract.example_files"}}',
+                 'medium',
+                 'Add .env.example file to document required environment variables',
+                 9, '[]'),
+                ('env-undocumented',
+                 '{"op":"all","conditions":[{"op":"present","field":"env_contract.example_files"},{"op":"present","field":"env_contract.undocumented"}]}',
+                 'medium',
+                 'Document these environment variables in .env.example: {env_contract.undocumented}',
+                 9, '[]'),
+                ('tests',
+                 '{"op":"not","condition":{"op":"present","field":"has_tests"}}',
+                 'high',
+                 'Add tests to ensure code quality before shipping',
+                 8, '[]'),
+                ('ci',
+                 '{"op":"not","condition":{"op":"present","field":"has_ci"}}',
+                 'medium',
+                 'Set up CI/CD pipeline (GitHub Actions, Vercel) for automated deployments',
+                 9, '[]'),
+                ('auth',
+                 '{"op":"not","condition":{"op":"contains","field":"detected_services","value":"Auth"}}',
+                 'low',
+                 'Consider adding authentication (Clerk recommended)',
+                 6, '["web"]'),
+                ('payments',
+                 '{"op":"not","condition":{"op":"contains","field":"detected_services","value":"Payments"}}',
+                 'low',
+                 'Set up payment processing (Stripe) for monetization',
+                 3, '["web"]'),
+                ('error-tracking',
+                 '{"op":"not","condition":{"op":"contains","field":"detected_services","value":"Error"}}',
+                 'medium',
+                 'Add error tracking (Sentry) for production monitoring',
+                 11, '["web", "api"]');
+        "#,
+    },
+    Migration {
+        version: 14,
+        description: "Line counts per saved analysis",
+        sql: "
+            ALTER TABLE project_analyses ADD COLUMN code_lines INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE project_analyses ADD COLUMN comment_lines INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE project_analyses ADD COLUMN test_to_source_ratio REAL;
+            ALTER TABLE project_analyses ADD COLUMN languages TEXT NOT NULL DEFAULT '{}';
+        ",
+    },
+    Migration {
+        version: 15,
+        description: "Recommendation rules for repository activity and remotes",
+        sql: r#"
+            INSERT OR IGNORE INTO recommendation_rules (id, condition, severity, message, sop_number, project_types) VALUES
+                ('stale-repo',
+                 '{"op":"contains","field":"git.activity","value":"stale"}',
+                 'low',
+                 'No commits in {git.days_since_last_commit} days; pick the project back up or park it',
+                 NULL, '[]'),
+                ('abandoned-repo',
+                 '{"op":"contains","field":"git.activity","value":"abandoned"}',
+                 'medium',
+                 'No commits in {git.days_since_last_commit} days; archive the project or plan its next step',
+                 NULL, '[]'),
+                ('origin-mismatch',
+                 '{"op":"contains","field":"git.origin_status","value":"mismatch"}',
+                 'medium',
+                 'The origin remote ({git.origin_url}) does not match the project''s GitHub URL',
+                 NULL, '[]'),
+                ('unpushed-commits',
+                 '{"op":"at_least","field":"git.ahead","value":1}',
+                 'low',
+                 'Push {git.ahead} local commit(s) to {git.upstream}',
+                 NULL, '[]');
+        "#,
+    },
+    Migration {
+        version: 16,
+        description: "Test line counts per saved analysis",
+        sql: "
+            ALTER TABLE project_analyses ADD COLUMN test_lines INTEGER;
+            UPDATE project_analyses SET test_lines = json_extract(report, '$.code_stats.test_code');
+        ",
+    },
+];
+
+/// Highest schema version this build knows about
+#[cfg(test)]
+pub fn latest_version() -> i64 {
+    latest_version_of(MIGRATIONS)
+}
+
+fn latest_version_of(migrations: &[Migration]) -> i64 {
+    migrations.iter().map(|m| m.version).max().unwrap_or(0)
+}
+
+pub fn current_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Bring the database up to the latest schema version
+pub fn run(conn: &mut Connection) -> Result<(), MigrationError> {
+    apply(conn, MIGRATIONS)
+}
+
+fn apply(conn: &mut Connection, migrations: &[Migration]) -> Result<(), MigrationError> {
+    let current = current_version(conn)?;
+    let supported = latest_version_of(migrations);
+
+    // Refuse to touch a database written by a newer build
+    if current > supported {
+        return Err(MigrationError::DatabaseTooNew { found: current, supported });
+    }
+
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        apply_one(&tx, migration).map_err(|source| MigrationError::Failed {
+            version: migration.version,
+            description: migration.description,
+            source,
+        })?;
+        // Dropping the transaction on error rolls back, leaving the database untouched
+        tx.commit()?;
+
+        log::info!(
+            "Applied database migration {}: {}",
+            migration.version,
+            migration.description
+        );
+    }
+
+    Ok(())
+}
+
+fn apply_one(tx: &Transaction, migration: &Migration) -> rusqlite::Result<()> {
+    tx.execute_batch(migration.sql)?;
+    tx.pragma_update(None, "user_version", migration.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Schema written by `Database::init` before versioned migrations existed
+    const LEGACY_FIXTURE: &str = "
+        CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+        CREATE TABLE projects (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            slug TEXT UNIQUE NOT NULL,
+            local_path TEXT,
+            github_url TEXT,
+            current_phase INTEGER DEFAULT 0,
+            status_report TEXT,
+            last_analyzed TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE conversations (
+            id TEXT PRIMARY KEY,
+            project_id TEXT,
+            title TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE TABLE messages (
+            id TEXT PRIMARY KEY,
+            conversation_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+        );
+        INSERT INTO settings (key, value) VALUES ('theme', 'dark');
+        INSERT INTO projects (id, name, slug) VALUES ('p1', 'Legacy', 'legacy');
+        INSERT INTO conversations (id, project_id, title) VALUES ('c1', 'p1', 'Hello');
+        INSERT INTO messages (id, conversation_id, role, content) VALUES ('m1', 'c1', 'user', 'hi');
+    ";
+
+    fn open() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        conn
+    }
+
+    fn table_exists(conn: &Connection, name: &str) -> bool {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            [name],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn versions_are_strictly_increasing() {
+        let mut previous = 0;
+        for m in MIGRATIONS {
+            assert!(m.version > previous, "migration {} is out of order", m.version);
+            previous = m.version;
+        }
+    }
+
+    #[test]
+    fn migrates_empty_database() {
+        let mut conn = open();
+        run(&mut conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(table_exists(&conn, "shot_clock_sessions"));
+
+        // Running again is a no-op
+        run(&mut conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn upgrades_legacy_unversioned_database() {
+        let mut conn = open();
+        conn.execute_batch(LEGACY_FIXTURE).unwrap();
+        run(&mut conn).unwrap();
+
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        let content: String = conn
+            .query_row("SELECT content FROM messages WHERE id = 'm1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(content, "hi");
+        let theme: String = conn
+            .query_row("SELECT value FROM settings WHERE key = 'theme'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(theme, "dark");
+    }
+
+    #[test]
+    fn test_lines_are_backfilled_from_saved_reports() {
+        let mut conn = open();
+        let before: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version < 16).collect();
+        for m in before {
+            let tx = conn.transaction().unwrap();
+            apply_one(&tx, m).unwrap();
+            tx.commit().unwrap();
+        }
+        conn.execute_batch(
+            r#"
+            INSERT INTO projects (id, name, slug) VALUES ('p1', 'Fixture', 'fixture');
+            INSERT INTO project_analyses (project_id, analyzed_at, phase, phase_name, file_count, report) VALUES
+                ('p1', '2024-01-01', 1, 'Idea', 0, '{"code_stats":{"test_code":120}}'),
+                ('p1', '2024-02-01', 1, 'Idea', 0, '{}');
+            "#,
+        )
+        .unwrap();
+
+        run(&mut conn).unwrap();
+        let test_lines: Vec<Option<i64>> = conn
+            .prepare("SELECT test_lines FROM project_analyses ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(test_lines, [Some(120), None]);
+    }
+
+    #[test]
+    fn upgrades_from_every_historical_version() {
+        for start in 1..=latest_version() {
+            let mut conn = open();
+            let historical: Vec<&Migration> =
+                MIGRATIONS.iter().filter(|m| m.version <= start).collect();
+            for m in &historical {
+                let tx = conn.transaction().unwrap();
+                apply_one(&tx, m).unwrap();
+                tx.commit().unwrap();
+            }
+            conn.execute(
+                "INSERT INTO projects (id, name, slug) VALUES ('p1', 'Fixture', 'fixture')",
+                [],
+            )
+            .unwrap();
+
+            run(&mut conn).unwrap_or_else(|e| panic!("upgrade from v{} failed: {}", start, e));
+            assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+            let name: String = conn
+                .query_row("SELECT name FROM projects WHERE id = 'p1'", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(name, "Fixture", "data lost upgrading from v{}", start);
+        }
+    }
+
+    #[test]
+    fn refuses_newer_database() {
+        let mut conn = open();
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+
+        match run(&mut conn) {
+            Err(MigrationError::DatabaseTooNew { found, supported }) => {
+                assert_eq!(found, latest_version() + 1);
+                assert_eq!(supported, latest_version());
+            }
+            other => panic!("expected DatabaseTooNew, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn failed_migration_leaves_database_untouched() {
+        let broken = [
+            Migration { version: 1, description: "ok", sql: "CREATE TABLE a (id TEXT);" },
+            Migration {
+                version: 2,
+                description: "broken",
+                sql: "CREATE TABLE b (id TEXT); INSERT INTO missing VALUES (1);",
+            },
+        ];
+
+        let mut conn = open();
+        let err = apply(&mut conn, &broken).unwrap_err();
+        assert!(matches!(err, MigrationError::Failed { version: 2, .. }));
+
+        // Migration 1 committed, migration 2 fully rolled back
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        assert!(table_exists(&conn, "a"));
+        assert!(!table_exists(&conn, "b"));
+    }
+}
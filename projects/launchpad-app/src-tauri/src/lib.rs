@@ -1,5 +1,7 @@
+mod analysis;
 mod commands;
 mod db;
+mod llm;
 
 use tauri::{
     Manager,
@@ -15,10 +17,18 @@ use commands::{
     list_projects, get_project, create_project, update_project, delete_project,
     get_roadmap, update_roadmap_item,
     // Chat
-    list_conversations, get_conversation_messages, create_conversation,
-    save_message, delete_conversation, send_chat_message,
+    list_conversations, get_conversation_messages, create_conversation, set_conversation_model,
+    save_message, delete_conversation, send_chat_message, cancel_chat_request,
+    ChatRequestRegistry,
     // Analyzer
     analyze_project, save_project_analysis,
+    // Analysis History
+    get_analysis_history, diff_analyses,
+    // Dependency Inventory
+    list_dependency_snapshots, find_dependency_usage, diff_dependency_snapshots,
+    // Recommendation Rules
+    list_recommendation_rules, save_recommendation_rule, delete_recommendation_rule,
+    list_silenced_recommendations, silence_recommendation, unsilence_recommendation,
     // File Tools
     list_files, read_file, grep_files, get_directory_tree,
     // Ideas
@@ -28,6 +38,17 @@ use commands::{
     // Shot Clock
     get_shot_clock, list_shot_clocks, start_shot_clock, update_shot_clock_time,
     add_bonus_time, complete_shot_clock, lock_shot_clock, delete_shot_clock, init_project_shot_clocks,
+    // Usage & Costs
+    get_usage_by_project, get_usage_by_day, get_usage_by_model,
+    list_model_prices, set_model_price, delete_model_price,
+    // File Changes
+    list_file_changes, approve_file_change, reject_file_change, revert_file_change,
+    // Command Runner
+    list_command_runs,
+    // Symbols
+    get_symbols, find_definition,
+    // Path Policy
+    get_path_policy, set_path_policy, list_path_denials,
 };
 use db::Database;
 
@@ -52,6 +73,7 @@ pub fn run() {
             db.init()
                 .map_err(|e| format!("Failed to run database migrations: {}", e))?;
             app.manage(db);
+            app.manage(ChatRequestRegistry::default());
 
             // Focus main window on startup
             if let Some(window) = app.get_webview_window("main") {
@@ -132,12 +154,28 @@ pub fn run() {
             list_conversations,
             get_conversation_messages,
             create_conversation,
+            set_conversation_model,
             save_message,
             delete_conversation,
             send_chat_message,
+            cancel_chat_request,
             // Analyzer
             analyze_project,
             save_project_analysis,
+            // Analysis History
+            get_analysis_history,
+            diff_analyses,
+            // Dependency Inventory
+            list_dependency_snapshots,
+            find_dependency_usage,
+            diff_dependency_snapshots,
+            // Recommendation Rules
+            list_recommendation_rules,
+            save_recommendation_rule,
+            delete_recommendation_rule,
+            list_silenced_recommendations,
+            silence_recommendation,
+            unsilence_recommendation,
             // File Tools
             list_files,
             read_file,
@@ -167,6 +205,27 @@ pub fn run() {
             lock_shot_clock,
             delete_shot_clock,
             init_project_shot_clocks,
+            // Usage & Costs
+            get_usage_by_project,
+            get_usage_by_day,
+            get_usage_by_model,
+            list_model_prices,
+            set_model_price,
+            delete_model_price,
+            // File Changes
+            list_file_changes,
+            approve_file_change,
+            reject_file_change,
+            revert_file_change,
+            // Command Runner
+            list_command_runs,
+            // Symbols
+            get_symbols,
+            find_definition,
+            // Path Policy
+            get_path_policy,
+            set_path_policy,
+            list_path_denials,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
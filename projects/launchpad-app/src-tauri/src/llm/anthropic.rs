@@ -0,0 +1,314 @@
+use super::{
+    drain_sse_events, next_chunk, send_cancellable, LlmError, LlmProvider, LlmRequest,
+    LlmResponse, StopReason, StreamUpdate, TokenUsage,
+};
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+const API_VERSION: &str = "2023-06-01";
+
+/// Anthropic Messages API
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: String, base_url: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key,
+            base_url: base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    async fn stream_message(
+        &self,
+        request: &LlmRequest<'_>,
+        cancel: &CancellationToken,
+        on_update: &mut (dyn FnMut(StreamUpdate) + Send),
+    ) -> Result<LlmResponse, LlmError> {
+        let mut body = serde_json::json!({
+            "model": request.model,
+            "max_tokens": request.max_tokens,
+            "system": request.system,
+            "messages": request.messages,
+            "stream": true
+        });
+
+        if let Some(tools) = request.tools {
+            body["tools"] = tools.clone();
+        }
+
+        let http_request = self
+            .client
+            .post(format!("{}/messages", self.base_url.trim_end_matches('/')))
+            .header("Content-Type", "application/json")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(&body);
+
+        let mut response = send_cancellable(http_request, cancel).await?;
+
+        // Read the event stream, reporting deltas as they arrive
+        let mut streamed = StreamedMessage::default();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = next_chunk(&mut response, cancel).await? {
+            buffer.extend_from_slice(chunk.as_ref());
+
+            for data in drain_sse_events(&mut buffer) {
+                let event: serde_json::Value = serde_json::from_str(&data)
+                    .map_err(|e| format!("Failed to parse stream event: {}", e))?;
+
+                if let Some(update) = streamed.apply(&event)? {
+                    on_update(update);
+                }
+            }
+        }
+
+        Ok(streamed.finish())
+    }
+}
+
+/// Assembles streamed Messages API events back into a complete message
+#[derive(Default)]
+struct StreamedMessage {
+    content: Vec<serde_json::Value>,
+    partial_json: Vec<String>,
+    stop_reason: Option<String>,
+    usage: TokenUsage,
+}
+
+impl StreamedMessage {
+    fn apply(&mut self, event: &serde_json::Value) -> Result<Option<StreamUpdate>, String> {
+        match event["type"].as_str().unwrap_or("") {
+            "message_start" => {
+                let usage = &event["message"]["usage"];
+                self.usage.input_tokens = usage["input_tokens"].as_i64().unwrap_or(0);
+                self.usage.output_tokens = usage["output_tokens"].as_i64().unwrap_or(0);
+                self.usage.cache_creation_tokens =
+                    usage["cache_creation_input_tokens"].as_i64().unwrap_or(0);
+                self.usage.cache_read_tokens = usage["cache_read_input_tokens"].as_i64().unwrap_or(0);
+                Ok(None)
+            }
+            "content_block_start" => {
+                let index = event["index"].as_u64().unwrap_or(0) as usize;
+                let block = event["content_block"].clone();
+                while self.content.len() <= index {
+                    self.content.push(serde_json::Value::Null);
+                    self.partial_json.push(String::new());
+                }
+
+                let update = if block["type"].as_str() == Some("tool_use") {
+                    Some(StreamUpdate::ToolStart {
+                        id: block["id"].as_str().unwrap_or("").to_string(),
+                        name: block["name"].as_str().unwrap_or("").to_string(),
+                    })
+                } else {
+                    None
+                };
+
+                self.content[index] = block;
+                Ok(update)
+            }
+            "content_block_delta" => {
+                let index = event["index"].as_u64().unwrap_or(0) as usize;
+                let delta = &event["delta"];
+                let block = self
+                    .content
+                    .get_mut(index)
+                    .ok_or("Stream delta for unknown content block")?;
+
+                match delta["type"].as_str().unwrap_or("") {
+                    "text_delta" => {
+                        let text = delta["text"].as_str().unwrap_or("");
+                        let existing = block["text"].as_str().unwrap_or("").to_string();
+                        block["text"] = serde_json::Value::String(existing + text);
+                        Ok(Some(StreamUpdate::Text(text.to_string())))
+                    }
+                    "input_json_delta" => {
+                        self.partial_json[index].push_str(delta["partial_json"].as_str().unwrap_or(""));
+                        Ok(None)
+                    }
+                    _ => Ok(None),
+                }
+            }
+            "content_block_stop" => {
+                let index = event["index"].as_u64().unwrap_or(0) as usize;
+                if let Some(block) = self.content.get_mut(index) {
+                    if block["type"].as_str() == Some("tool_use") {
+                        let raw = &self.partial_json[index];
+                        block["input"] = if raw.trim().is_empty() {
+                            serde_json::json!({})
+                        } else {
+                            serde_json::from_str(raw)
+                                .map_err(|e| format!("Invalid tool input JSON: {}", e))?
+                        };
+                    }
+                }
+                Ok(None)
+            }
+            "message_delta" => {
+                if let Some(reason) = event["delta"]["stop_reason"].as_str() {
+                    self.stop_reason = Some(reason.to_string());
+                }
+                // Output token count here is cumulative for the message
+                if let Some(output) = event["usage"]["output_tokens"].as_i64() {
+                    self.usage.output_tokens = output;
+                }
+                Ok(None)
+            }
+            "error" => Err(format!(
+                "API error: {}",
+                event["error"]["message"].as_str().unwrap_or("unknown stream error")
+            )),
+            // message_stop, ping
+            _ => Ok(None),
+        }
+    }
+
+    fn finish(self) -> LlmResponse {
+        let stop_reason = match self.stop_reason.as_deref() {
+            Some("tool_use") => StopReason::ToolUse,
+            Some("max_tokens") => StopReason::MaxTokens,
+            Some("end_turn") | None => StopReason::EndTurn,
+            Some(other) => StopReason::Other(other.to_string()),
+        };
+
+        LlmResponse {
+            content: self.content,
+            stop_reason,
+            usage: self.usage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::drain_sse_events;
+    use serde_json::json;
+
+    /// Feed raw body chunks through the same path `stream_message` uses
+    fn assemble(chunks: &[&str]) -> Result<(LlmResponse, Vec<String>), String> {
+        let mut streamed = StreamedMessage::default();
+        let mut buffer = Vec::new();
+        let mut updates = Vec::new();
+        for chunk in chunks {
+            buffer.extend_from_slice(chunk.as_bytes());
+            for data in drain_sse_events(&mut buffer) {
+                let event: serde_json::Value = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+                match streamed.apply(&event)? {
+                    Some(StreamUpdate::Text(text)) => updates.push(text),
+                    Some(StreamUpdate::ToolStart { name, .. }) => updates.push(format!("tool:{}", name)),
+                    None => {}
+                }
+            }
+        }
+        Ok((streamed.finish(), updates))
+    }
+
+    fn event(value: serde_json::Value) -> String {
+        format!("event: {}\ndata: {}\n\n", value["type"].as_str().unwrap(), value)
+    }
+
+    #[test]
+    fn text_and_tool_use_blocks_are_reassembled() {
+        let body = [
+            event(json!({"type": "message_start", "message": {"usage": {
+                "input_tokens": 12, "output_tokens": 1,
+                "cache_creation_input_tokens": 3, "cache_read_input_tokens": 40}}})),
+            event(json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}})),
+            event(json!({"type": "ping"})),
+            event(json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Let me "}})),
+            event(json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "look."}})),
+            event(json!({"type": "content_block_stop", "index": 0})),
+            event(json!({"type": "content_block_start", "index": 1,
+                "content_block": {"type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {}}})),
+            event(json!({"type": "content_block_delta", "index": 1,
+                "delta": {"type": "input_json_delta", "partial_json": "{\"file_path\": \"src/ma"}})),
+            event(json!({"type": "content_block_delta", "index": 1,
+                "delta": {"type": "input_json_delta", "partial_json": "in.rs\"}"}})),
+            event(json!({"type": "content_block_stop", "index": 1})),
+            event(json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 57}})),
+            event(json!({"type": "message_stop"})),
+        ]
+        .concat();
+
+        // Split into awkward chunks, including mid-event and mid-separator
+        let chunks: Vec<&str> = body.as_bytes().chunks(7).map(|c| std::str::from_utf8(c).unwrap()).collect();
+        let (response, updates) = assemble(&chunks).unwrap();
+
+        assert_eq!(updates, ["Let me ", "look.", "tool:read_file"]);
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(
+            response.content,
+            [
+                json!({"type": "text", "text": "Let me look."}),
+                json!({"type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {"file_path": "src/main.rs"}}),
+            ]
+        );
+        assert_eq!(
+            response.usage,
+            TokenUsage {
+                input_tokens: 12,
+                output_tokens: 57,
+                cache_creation_tokens: 3,
+                cache_read_tokens: 40,
+            }
+        );
+    }
+
+    #[test]
+    fn crlf_framed_streams_parse_the_same() {
+        let body = [
+            event(json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}})),
+            event(json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "hi"}})),
+            event(json!({"type": "message_delta", "delta": {"stop_reason": "max_tokens"}})),
+        ]
+        .concat()
+        .replace('\n', "\r\n");
+        let (response, _) = assemble(&[&body]).unwrap();
+        assert_eq!(response.content, [json!({"type": "text", "text": "hi"})]);
+        assert_eq!(response.stop_reason, StopReason::MaxTokens);
+    }
+
+    #[test]
+    fn tool_calls_without_input_get_an_empty_object() {
+        let body = [
+            event(json!({"type": "content_block_start", "index": 0,
+                "content_block": {"type": "tool_use", "id": "toolu_1", "name": "git_status", "input": {}}})),
+            event(json!({"type": "content_block_stop", "index": 0})),
+        ]
+        .concat();
+        let (response, _) = assemble(&[&body]).unwrap();
+        assert_eq!(response.content[0]["input"], json!({}));
+        assert_eq!(response.stop_reason, StopReason::EndTurn);
+    }
+
+    #[test]
+    fn truncated_tool_input_and_stream_errors_fail() {
+        let body = [
+            event(json!({"type": "content_block_start", "index": 0,
+                "content_block": {"type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {}}})),
+            event(json!({"type": "content_block_delta", "index": 0,
+                "delta": {"type": "input_json_delta", "partial_json": "{\"file_path\": \"src"}})),
+            event(json!({"type": "content_block_stop", "index": 0})),
+        ]
+        .concat();
+        let err = assemble(&[&body]).map(|_| ()).unwrap_err();
+        assert!(err.starts_with("Invalid tool input JSON"), "{}", err);
+
+        let body = event(json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}));
+        assert_eq!(assemble(&[&body]).map(|_| ()).unwrap_err(), "API error: Overloaded");
+
+        let body = event(json!({"type": "content_block_delta", "index": 3, "delta": {"type": "text_delta", "text": "x"}}));
+        assert!(assemble(&[&body]).is_err());
+    }
+}
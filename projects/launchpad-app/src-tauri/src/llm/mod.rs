@@ -0,0 +1,248 @@
+pub mod anthropic;
+pub mod openai;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+pub use anthropic::AnthropicProvider;
+pub use openai::OpenAiProvider;
+
+pub const DEFAULT_ANTHROPIC_MODEL: &str = "claude-sonnet-4-20250514";
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Which backend serves a conversation
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    Anthropic,
+    /// Any OpenAI-compatible chat completions server (OpenAI, Ollama, llama.cpp, ...)
+    OpenAi,
+}
+
+impl ProviderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::Anthropic => "anthropic",
+            ProviderKind::OpenAi => "openai",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "anthropic" => Some(ProviderKind::Anthropic),
+            "openai" | "openai_compatible" | "ollama" | "llama_cpp" => Some(ProviderKind::OpenAi),
+            _ => None,
+        }
+    }
+}
+
+/// Resolved provider settings for a single chat request
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProviderConfig {
+    pub kind: ProviderKind,
+    pub model: String,
+    pub base_url: Option<String>,
+    #[serde(skip)]
+    pub api_key: Option<String>,
+}
+
+/// A single model round-trip. Messages and tools use the Anthropic content-block
+/// shape as the canonical format; providers translate as needed.
+pub struct LlmRequest<'a> {
+    pub model: &'a str,
+    pub system: &'a str,
+    pub messages: &'a [serde_json::Value],
+    pub tools: Option<&'a serde_json::Value>,
+    pub max_tokens: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    EndTurn,
+    ToolUse,
+    MaxTokens,
+    Other(String),
+}
+
+/// Token counts reported for one round-trip. `input_tokens` excludes cached tokens.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+}
+
+/// A complete assistant reply in canonical content blocks
+#[derive(Debug, Clone)]
+pub struct LlmResponse {
+    pub content: Vec<serde_json::Value>,
+    pub stop_reason: StopReason,
+    pub usage: TokenUsage,
+}
+
+/// Incremental output reported while a reply streams in
+pub enum StreamUpdate {
+    Text(String),
+    ToolStart { id: String, name: String },
+}
+
+#[derive(Debug)]
+pub enum LlmError {
+    Cancelled,
+    Request(String),
+}
+
+impl std::fmt::Display for LlmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LlmError::Cancelled => write!(f, "Request cancelled"),
+            LlmError::Request(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<String> for LlmError {
+    fn from(msg: String) -> Self {
+        LlmError::Request(msg)
+    }
+}
+
+impl From<&str> for LlmError {
+    fn from(msg: &str) -> Self {
+        LlmError::Request(msg.to_string())
+    }
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Send one request, streaming text and tool-call starts through `on_update`.
+    /// Returns `LlmError::Cancelled` as soon as `cancel` fires.
+    async fn stream_message(
+        &self,
+        request: &LlmRequest<'_>,
+        cancel: &CancellationToken,
+        on_update: &mut (dyn FnMut(StreamUpdate) + Send),
+    ) -> Result<LlmResponse, LlmError>;
+}
+
+pub fn build_provider(config: &ProviderConfig) -> Box<dyn LlmProvider> {
+    match config.kind {
+        ProviderKind::Anthropic => Box::new(AnthropicProvider::new(
+            config.api_key.clone().unwrap_or_default(),
+            config.base_url.clone(),
+        )),
+        ProviderKind::OpenAi => Box::new(OpenAiProvider::new(
+            config.api_key.clone(),
+            config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_OPENAI_BASE_URL.to_string()),
+        )),
+    }
+}
+
+/// Send a prepared request, aborting it if the token fires first
+async fn send_cancellable(
+    request: reqwest::RequestBuilder,
+    cancel: &CancellationToken,
+) -> Result<reqwest::Response, LlmError> {
+    let response = tokio::select! {
+        _ = cancel.cancelled() => return Err(LlmError::Cancelled),
+        response = request.send() => response.map_err(|e| format!("Request failed: {}", e))?,
+    };
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(LlmError::Request(format!("API error: {}", error_text)));
+    }
+
+    Ok(response)
+}
+
+/// Read the next body chunk, aborting if the token fires first
+async fn next_chunk(
+    response: &mut reqwest::Response,
+    cancel: &CancellationToken,
+) -> Result<Option<impl AsRef<[u8]>>, LlmError> {
+    tokio::select! {
+        _ = cancel.cancelled() => Err(LlmError::Cancelled),
+        chunk = response.chunk() => chunk.map_err(|e| LlmError::Request(format!("Stream interrupted: {}", e))),
+    }
+}
+
+/// Pull complete server-sent events out of the buffer, returning their data payloads
+fn drain_sse_events(buffer: &mut Vec<u8>) -> Vec<String> {
+    let mut events = Vec::new();
+
+    loop {
+        // Whichever separator comes first, so a server mixing them can't merge two events
+        let boundary = [&b"\n\n"[..], b"\r\n\r\n"]
+            .iter()
+            .filter_map(|sep| buffer.windows(sep.len()).position(|w| w == *sep).map(|pos| (pos, sep.len())))
+            .min();
+
+        let Some((pos, sep_len)) = boundary else {
+            break;
+        };
+
+        let frame: Vec<u8> = buffer.drain(..pos + sep_len).collect();
+        let frame = String::from_utf8_lossy(&frame[..pos]);
+
+        let data: Vec<&str> = frame
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|d| d.strip_prefix(' ').unwrap_or(d))
+            .collect();
+
+        if !data.is_empty() {
+            events.push(data.join("\n"));
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_events_are_drained_and_partial_ones_kept() {
+        let mut buffer = b"event: ping\ndata: {\"type\":\"ping\"}\n\ndata: {\"a\":".to_vec();
+        assert_eq!(drain_sse_events(&mut buffer), [r#"{"type":"ping"}"#]);
+        assert_eq!(buffer, b"data: {\"a\":");
+
+        buffer.extend_from_slice(b"1}\n");
+        assert!(drain_sse_events(&mut buffer).is_empty());
+        buffer.extend_from_slice(b"\n");
+        assert_eq!(drain_sse_events(&mut buffer), [r#"{"a":1}"#]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn crlf_framing_and_multi_line_data() {
+        let mut buffer = b"event: a\r\ndata: one\r\n\r\ndata:two\r\ndata: three\r\n\r\n".to_vec();
+        assert_eq!(drain_sse_events(&mut buffer), ["one", "two\nthree"]);
+        assert!(buffer.is_empty());
+
+        // A separator split across chunks
+        let mut buffer = b"data: x\r\n\r".to_vec();
+        assert!(drain_sse_events(&mut buffer).is_empty());
+        buffer.extend_from_slice(b"\n");
+        assert_eq!(drain_sse_events(&mut buffer), ["x"]);
+    }
+
+    #[test]
+    fn mixed_separators_stay_separate_events() {
+        let mut buffer = b"data: one\r\n\r\ndata: two\n\n".to_vec();
+        assert_eq!(drain_sse_events(&mut buffer), ["one", "two"]);
+    }
+
+    #[test]
+    fn comments_and_data_less_frames_are_skipped() {
+        let mut buffer = b": keep-alive\n\nevent: ping\n\ndata: [DONE]\n\n".to_vec();
+        assert_eq!(drain_sse_events(&mut buffer), ["[DONE]"]);
+    }
+}
@@ -0,0 +1,444 @@
+use super::{
+    drain_sse_events, next_chunk, send_cancellable, LlmError, LlmProvider, LlmRequest,
+    LlmResponse, StopReason, StreamUpdate, TokenUsage,
+};
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+
+/// OpenAI-compatible chat completions API. Also covers local servers such as
+/// Ollama (`http://localhost:11434/v1`) and llama.cpp (`http://localhost:8080/v1`).
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    base_url: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: Option<String>, base_url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key,
+            base_url,
+        }
+    }
+}
+
+/// Convert canonical tool definitions to OpenAI function tools
+fn to_openai_tools(tools: &serde_json::Value) -> serde_json::Value {
+    let converted: Vec<serde_json::Value> = tools
+        .as_array()
+        .map(|tools| {
+            tools
+                .iter()
+                .map(|t| {
+                    serde_json::json!({
+                        "type": "function",
+                        "function": {
+                            "name": t["name"],
+                            "description": t["description"],
+                            "parameters": t["input_schema"]
+                        }
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    serde_json::Value::Array(converted)
+}
+
+/// Convert canonical content-block messages to OpenAI chat messages
+fn to_openai_messages(system: &str, messages: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let mut converted = vec![serde_json::json!({ "role": "system", "content": system })];
+
+    for message in messages {
+        let role = message["role"].as_str().unwrap_or("user");
+        let Some(blocks) = message["content"].as_array() else {
+            converted.push(serde_json::json!({ "role": role, "content": message["content"] }));
+            continue;
+        };
+
+        let text: Vec<&str> = blocks
+            .iter()
+            .filter(|b| b["type"].as_str() == Some("text"))
+            .filter_map(|b| b["text"].as_str())
+            .collect();
+
+        if role == "assistant" {
+            let tool_calls: Vec<serde_json::Value> = blocks
+                .iter()
+                .filter(|b| b["type"].as_str() == Some("tool_use"))
+                .map(|b| {
+                    serde_json::json!({
+                        "id": b["id"],
+                        "type": "function",
+                        "function": {
+                            "name": b["name"],
+                            "arguments": b["input"].to_string()
+                        }
+                    })
+                })
+                .collect();
+
+            let mut assistant = serde_json::json!({ "role": "assistant", "content": text.join("\n") });
+            if !tool_calls.is_empty() {
+                assistant["tool_calls"] = serde_json::Value::Array(tool_calls);
+            }
+            converted.push(assistant);
+        } else {
+            // Tool results become individual "tool" messages
+            for block in blocks.iter().filter(|b| b["type"].as_str() == Some("tool_result")) {
+                let content = match &block["content"] {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                converted.push(serde_json::json!({
+                    "role": "tool",
+                    "tool_call_id": block["tool_use_id"],
+                    "content": content
+                }));
+            }
+
+            if !text.is_empty() {
+                converted.push(serde_json::json!({ "role": role, "content": text.join("\n") }));
+            }
+        }
+    }
+
+    converted
+}
+
+#[derive(Default)]
+struct PendingToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+    announced: bool,
+}
+
+/// Assembles streamed chat completion chunks back into canonical content blocks
+#[derive(Default)]
+struct StreamedCompletion {
+    text: String,
+    tool_calls: Vec<PendingToolCall>,
+    finish_reason: Option<String>,
+    usage: TokenUsage,
+}
+
+impl StreamedCompletion {
+    fn apply(&mut self, chunk: &serde_json::Value, updates: &mut Vec<StreamUpdate>) -> Result<(), String> {
+        if let Some(message) = chunk["error"]["message"].as_str() {
+            return Err(format!("API error: {}", message));
+        }
+
+        // Sent as a final chunk with empty choices when include_usage is set
+        if chunk["usage"].is_object() {
+            let usage = &chunk["usage"];
+            let cached = usage["prompt_tokens_details"]["cached_tokens"].as_i64().unwrap_or(0);
+            self.usage = TokenUsage {
+                input_tokens: usage["prompt_tokens"].as_i64().unwrap_or(0) - cached,
+                output_tokens: usage["completion_tokens"].as_i64().unwrap_or(0),
+                cache_creation_tokens: 0,
+                cache_read_tokens: cached,
+            };
+        }
+
+        let Some(choice) = chunk["choices"].get(0) else {
+            return Ok(());
+        };
+        let delta = &choice["delta"];
+
+        if let Some(text) = delta["content"].as_str() {
+            if !text.is_empty() {
+                self.text.push_str(text);
+                updates.push(StreamUpdate::Text(text.to_string()));
+            }
+        }
+
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let index = call["index"].as_u64().unwrap_or(self.tool_calls.len() as u64) as usize;
+            while self.tool_calls.len() <= index {
+                self.tool_calls.push(PendingToolCall::default());
+            }
+
+            let pending = &mut self.tool_calls[index];
+            if let Some(id) = call["id"].as_str() {
+                pending.id = id.to_string();
+            }
+            if let Some(name) = call["function"]["name"].as_str() {
+                pending.name.push_str(name);
+            }
+            if let Some(args) = call["function"]["arguments"].as_str() {
+                pending.arguments.push_str(args);
+            }
+
+            if !pending.announced && !pending.name.is_empty() {
+                pending.announced = true;
+                if pending.id.is_empty() {
+                    pending.id = format!("call_{}", index);
+                }
+                updates.push(StreamUpdate::ToolStart {
+                    id: pending.id.clone(),
+                    name: pending.name.clone(),
+                });
+            }
+        }
+
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            self.finish_reason = Some(reason.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<LlmResponse, String> {
+        let mut content = Vec::new();
+
+        if !self.text.is_empty() {
+            content.push(serde_json::json!({ "type": "text", "text": self.text }));
+        }
+
+        let has_tool_calls = !self.tool_calls.is_empty();
+        for (index, call) in self.tool_calls.into_iter().enumerate() {
+            let input: serde_json::Value = if call.arguments.trim().is_empty() {
+                serde_json::json!({})
+            } else {
+                serde_json::from_str(&call.arguments)
+                    .map_err(|e| format!("Invalid tool arguments JSON: {}", e))?
+            };
+            let id = if call.id.is_empty() { format!("call_{}", index) } else { call.id };
+
+            content.push(serde_json::json!({
+                "type": "tool_use",
+                "id": id,
+                "name": call.name,
+                "input": input
+            }));
+        }
+
+        let stop_reason = match self.finish_reason.as_deref() {
+            Some("tool_calls") => StopReason::ToolUse,
+            _ if has_tool_calls => StopReason::ToolUse,
+            Some("length") => StopReason::MaxTokens,
+            Some("stop") | None => StopReason::EndTurn,
+            Some(other) => StopReason::Other(other.to_string()),
+        };
+
+        Ok(LlmResponse {
+            content,
+            stop_reason,
+            usage: self.usage,
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    async fn stream_message(
+        &self,
+        request: &LlmRequest<'_>,
+        cancel: &CancellationToken,
+        on_update: &mut (dyn FnMut(StreamUpdate) + Send),
+    ) -> Result<LlmResponse, LlmError> {
+        let mut body = serde_json::json!({
+            "model": request.model,
+            "max_tokens": request.max_tokens,
+            "messages": to_openai_messages(request.system, request.messages),
+            "stream": true,
+            "stream_options": { "include_usage": true }
+        });
+
+        if let Some(tools) = request.tools {
+            body["tools"] = to_openai_tools(tools);
+        }
+
+        let mut http_request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url.trim_end_matches('/')))
+            .header("Content-Type", "application/json")
+            .json(&body);
+
+        // Local servers usually run without authentication
+        if let Some(ref key) = self.api_key {
+            http_request = http_request.bearer_auth(key);
+        }
+
+        let mut response = send_cancellable(http_request, cancel).await?;
+
+        let mut streamed = StreamedCompletion::default();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut updates = Vec::new();
+
+        'stream: while let Some(chunk) = next_chunk(&mut response, cancel).await? {
+            buffer.extend_from_slice(chunk.as_ref());
+
+            for data in drain_sse_events(&mut buffer) {
+                if data.trim() == "[DONE]" {
+                    break 'stream;
+                }
+
+                let event: serde_json::Value = serde_json::from_str(&data)
+                    .map_err(|e| format!("Failed to parse stream event: {}", e))?;
+
+                streamed.apply(&event, &mut updates)?;
+                for update in updates.drain(..) {
+                    on_update(update);
+                }
+            }
+        }
+
+        Ok(streamed.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assemble(chunks: &[serde_json::Value]) -> Result<(LlmResponse, Vec<String>), String> {
+        let mut streamed = StreamedCompletion::default();
+        let mut updates = Vec::new();
+        for chunk in chunks {
+            streamed.apply(chunk, &mut updates)?;
+        }
+        let updates = updates
+            .into_iter()
+            .map(|update| match update {
+                StreamUpdate::Text(text) => text,
+                StreamUpdate::ToolStart { id, name } => format!("tool:{}:{}", id, name),
+            })
+            .collect();
+        Ok((streamed.finish()?, updates))
+    }
+
+    fn delta(delta: serde_json::Value) -> serde_json::Value {
+        json!({ "choices": [{ "index": 0, "delta": delta, "finish_reason": null }] })
+    }
+
+    fn finish(reason: &str) -> serde_json::Value {
+        json!({ "choices": [{ "index": 0, "delta": {}, "finish_reason": reason }] })
+    }
+
+    #[test]
+    fn messages_convert_to_chat_format() {
+        let messages = [
+            json!({ "role": "user", "content": "What's in main.rs?" }),
+            json!({ "role": "assistant", "content": [
+                { "type": "text", "text": "Let me look." },
+                { "type": "tool_use", "id": "toolu_1", "name": "read_file", "input": { "file_path": "src/main.rs" } },
+                { "type": "tool_use", "id": "toolu_2", "name": "git_status", "input": {} },
+            ] }),
+            json!({ "role": "user", "content": [
+                { "type": "tool_result", "tool_use_id": "toolu_1", "content": "fn main() {}" },
+                { "type": "tool_result", "tool_use_id": "toolu_2", "content": [{ "type": "text", "text": "clean" }], "is_error": false },
+                { "type": "text", "text": "Also check the tests." },
+            ] }),
+            json!({ "role": "assistant", "content": [{ "type": "text", "text": "Done." }] }),
+        ];
+
+        let converted = to_openai_messages("Be brief.", &messages);
+        assert_eq!(
+            converted,
+            [
+                json!({ "role": "system", "content": "Be brief." }),
+                json!({ "role": "user", "content": "What's in main.rs?" }),
+                json!({ "role": "assistant", "content": "Let me look.", "tool_calls": [
+                    { "id": "toolu_1", "type": "function",
+                      "function": { "name": "read_file", "arguments": r#"{"file_path":"src/main.rs"}"# } },
+                    { "id": "toolu_2", "type": "function",
+                      "function": { "name": "git_status", "arguments": "{}" } },
+                ] }),
+                json!({ "role": "tool", "tool_call_id": "toolu_1", "content": "fn main() {}" }),
+                json!({ "role": "tool", "tool_call_id": "toolu_2", "content": r#"[{"text":"clean","type":"text"}]"# }),
+                json!({ "role": "user", "content": "Also check the tests." }),
+                json!({ "role": "assistant", "content": "Done." }),
+            ]
+        );
+    }
+
+    #[test]
+    fn tools_convert_to_functions() {
+        let tools = json!([{ "name": "read_file", "description": "Read a file",
+            "input_schema": { "type": "object", "properties": { "file_path": { "type": "string" } } } }]);
+        assert_eq!(
+            to_openai_tools(&tools),
+            json!([{ "type": "function", "function": { "name": "read_file", "description": "Read a file",
+                "parameters": { "type": "object", "properties": { "file_path": { "type": "string" } } } } }])
+        );
+        assert_eq!(to_openai_tools(&json!(null)), json!([]));
+    }
+
+    #[test]
+    fn tool_call_deltas_accumulate_per_index() {
+        let chunks = [
+            delta(json!({ "role": "assistant", "content": "Checking" })),
+            delta(json!({ "content": "." })),
+            delta(json!({ "tool_calls": [{ "index": 0, "id": "call_a", "type": "function",
+                "function": { "name": "read_file", "arguments": "" } }] })),
+            delta(json!({ "tool_calls": [{ "index": 0, "function": { "arguments": "{\"file_pa" } }] })),
+            delta(json!({ "tool_calls": [{ "index": 1, "id": "call_b", "type": "function",
+                "function": { "name": "git_status", "arguments": "" } }] })),
+            delta(json!({ "tool_calls": [{ "index": 0, "function": { "arguments": "th\": \"a.rs\"}" } }] })),
+            finish("tool_calls"),
+            json!({ "choices": [], "usage": { "prompt_tokens": 120, "completion_tokens": 30,
+                "prompt_tokens_details": { "cached_tokens": 100 } } }),
+        ];
+        let (response, updates) = assemble(&chunks).unwrap();
+
+        assert_eq!(updates, ["Checking", ".", "tool:call_a:read_file", "tool:call_b:git_status"]);
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(
+            response.content,
+            [
+                json!({ "type": "text", "text": "Checking." }),
+                json!({ "type": "tool_use", "id": "call_a", "name": "read_file", "input": { "file_path": "a.rs" } }),
+                json!({ "type": "tool_use", "id": "call_b", "name": "git_status", "input": {} }),
+            ]
+        );
+        assert_eq!(
+            response.usage,
+            TokenUsage {
+                input_tokens: 20,
+                output_tokens: 30,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn tool_calls_without_ids_get_generated_ones() {
+        // Some local servers omit ids and finish with "stop" even after a tool call
+        let chunks = [
+            delta(json!({ "tool_calls": [{ "function": { "name": "list_files", "arguments": "{}" } }] })),
+            finish("stop"),
+        ];
+        let (response, updates) = assemble(&chunks).unwrap();
+        assert_eq!(updates, ["tool:call_0:list_files"]);
+        assert_eq!(response.content[0]["id"], "call_0");
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+    }
+
+    #[test]
+    fn finish_reasons_map_to_stop_reasons() {
+        let (response, _) = assemble(&[delta(json!({ "content": "a" })), finish("length")]).unwrap();
+        assert_eq!(response.stop_reason, StopReason::MaxTokens);
+        let (response, _) = assemble(&[delta(json!({ "content": "a" }))]).unwrap();
+        assert_eq!(response.stop_reason, StopReason::EndTurn);
+        let (response, _) = assemble(&[finish("content_filter")]).unwrap();
+        assert_eq!(response.stop_reason, StopReason::Other("content_filter".to_string()));
+        assert!(response.content.is_empty());
+    }
+
+    #[test]
+    fn errors_and_malformed_arguments_fail() {
+        let err = assemble(&[json!({ "error": { "message": "model not found" } })]).map(|_| ()).unwrap_err();
+        assert_eq!(err, "API error: model not found");
+
+        let chunks = [delta(json!({ "tool_calls": [{ "index": 0, "id": "call_a",
+            "function": { "name": "read_file", "arguments": "{\"file_path\": " } }] }))];
+        let err = assemble(&chunks).map(|_| ()).unwrap_err();
+        assert!(err.starts_with("Invalid tool arguments JSON"), "{}", err);
+    }
+}
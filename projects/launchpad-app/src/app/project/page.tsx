@@ -2,7 +2,7 @@
 
 import { useEffect, useState } from "react";
 import { useSearchParams, useRouter } from "next/navigation";
-import { useAppStore } from "@/lib/store";
+import { useAppStore, parseStatusReport } from "@/lib/store";
 import type { RoadmapItem, ProjectAnalysis } from "@/lib/types";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { ChatContainer } from "@/components/chat/chat-container";
@@ -26,6 +26,7 @@ import {
   Shield,
   Cpu,
   Activity,
+  X,
 } from "lucide-react";
 import { cn } from "@/lib/utils";
 
@@ -43,6 +44,7 @@ export default function ProjectPage() {
     updateRoadmapItem,
     analyzeProject,
     saveAnalysis,
+    silenceRecommendation,
     currentAnalysis,
     analyzing,
   } = useAppStore();
@@ -68,7 +70,7 @@ export default function ProjectPage() {
   }, [slug, getProject, fetchRoadmap]);
 
   const analysis: ProjectAnalysis | null = currentProject?.status_report
-    ? JSON.parse(currentProject.status_report)
+    ? parseStatusReport(currentProject.status_report)
     : currentAnalysis;
 
   const handleSync = async () => {
@@ -91,7 +93,7 @@ export default function ProjectPage() {
   const handleAnalyze = async () => {
     if (!currentProject?.local_path) return;
     try {
-      const result = await analyzeProject(currentProject.local_path);
+      const result = await analyzeProject(currentProject.local_path, currentProject.id);
       await saveAnalysis(currentProject.id, result);
     } catch (err) {
       console.error("Failed to analyze project:", err);
@@ -289,8 +291,22 @@ export default function ProjectPage() {
                       ))}
                     </div>
                     <p className="text-xs text-[var(--normandy-text-secondary)]">
-                      <span className="normandy-mono text-[var(--normandy-cyan)]">{analysis.file_count}</span> files | Phase: {analysis.sop_progress.phase_name}
+                      <span className="normandy-mono text-[var(--normandy-cyan)]">{analysis.file_count}</span> files
+                      {analysis.code_stats && (
+                        <> | <span className="normandy-mono text-[var(--normandy-cyan)]">{analysis.code_stats.code.toLocaleString()}</span> lines of code</>
+                      )}
+                      {" "}| Phase: {analysis.sop_progress.phase_name}
                     </p>
+                    {analysis.git && (
+                      <p className="text-xs text-[var(--normandy-text-secondary)]">
+                        <span className="normandy-mono">{analysis.git.branch ?? "detached"}</span>
+                        {analysis.git.dirty_files + analysis.git.untracked_files > 0 &&
+                          ` | ${analysis.git.dirty_files} changed, ${analysis.git.untracked_files} untracked`}
+                        {!!analysis.git.ahead && ` | ${analysis.git.ahead} unpushed`}
+                        {analysis.git.days_since_last_commit !== null &&
+                          ` | Last commit ${analysis.git.days_since_last_commit}d ago`}
+                      </p>
+                    )}
                     {currentProject.last_analyzed && (
                       <p className="text-[10px] text-[var(--normandy-text-muted)]">
                         Last scan: {new Date(currentProject.last_analyzed).toLocaleDateString()}
@@ -323,10 +339,26 @@ export default function ProjectPage() {
                   <span className="normandy-label text-[var(--normandy-warning)]">Tactical Recommendations</span>
                 </div>
                 <ul className="space-y-2">
-                  {analysis.recommendations.map((rec, i) => (
-                    <li key={i} className="flex items-start gap-2 text-sm text-[var(--normandy-text-primary)]">
+                  {analysis.recommendations.map((rec, index) => (
+                    <li key={rec.rule_id || index} className="group flex items-start gap-2 text-sm text-[var(--normandy-text-primary)]">
                       <ChevronRight className="mt-0.5 h-4 w-4 shrink-0 text-[var(--normandy-orange)]" />
-                      {rec}
+                      <span className="flex-1">
+                        {rec.message}
+                        {rec.sop_number != null && (
+                          <span className="ml-2 normandy-mono text-xs text-[var(--normandy-text-muted)]">
+                            SOP-{rec.sop_number.toString().padStart(2, "0")}
+                          </span>
+                        )}
+                      </span>
+                      {rec.rule_id && (
+                        <button
+                          onClick={() => silenceRecommendation(currentProject.id, rec.rule_id)}
+                          className="opacity-0 group-hover:opacity-100 text-[var(--normandy-text-muted)] hover:text-[var(--normandy-text-primary)]"
+                          title="Don't recommend this for this project"
+                        >
+                          <X className="h-4 w-4" />
+                        </button>
+                      )}
                     </li>
                   ))}
                 </ul>
@@ -397,6 +429,12 @@ export default function ProjectPage() {
                             {item.ai_notes}
                           </p>
                         )}
+                        {item.blocked_reason && (
+                          <p className="mt-1 flex items-center gap-1 text-xs text-[var(--normandy-warning)]">
+                            <AlertCircle className="h-3 w-3" />
+                            Blocked: {item.blocked_reason}
+                          </p>
+                        )}
                       </div>
                       {/* Status Badge */}
                       <span
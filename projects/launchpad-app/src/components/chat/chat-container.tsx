@@ -56,7 +56,10 @@ export function ChatContainer({ projectId, projectName }: ChatContainerProps) {
   // Use individual selectors to prevent unnecessary re-renders
   const messages = useAppStore((state) => state.messages);
   const sendMessage = useAppStore((state) => state.sendMessage);
+  const cancelMessage = useAppStore((state) => state.cancelMessage);
   const sendingMessage = useAppStore((state) => state.sendingMessage);
+  const streamingText = useAppStore((state) => state.streamingText);
+  const activeTool = useAppStore((state) => state.activeTool);
   const settings = useAppStore((state) => state.settings);
   const setCurrentConversation = useAppStore((state) => state.setCurrentConversation);
   const createConversation = useAppStore((state) => state.createConversation);
@@ -69,7 +72,7 @@ export function ChatContainer({ projectId, projectName }: ChatContainerProps) {
 
   useEffect(() => {
     scrollToBottom();
-  }, [messages]);
+  }, [messages, streamingText]);
 
   const handleSend = async (content: string) => {
     try {
@@ -242,10 +245,23 @@ export function ChatContainer({ projectId, projectName }: ChatContainerProps) {
               aria-label="Chat messages"
               aria-live="polite"
             >
-              {messages.map((message) => (
-                <ChatMessage key={message.id} message={message} />
-              ))}
-              {sendingMessage && <TypingIndicator />}
+              {messages
+                .filter((message) => message.content.trim() !== "")
+                .map((message) => (
+                  <ChatMessage key={message.id} message={message} />
+                ))}
+              {sendingMessage && streamingText.trim() !== "" && (
+                <ChatMessage
+                  message={{
+                    id: "streaming",
+                    conversation_id: "",
+                    role: "assistant",
+                    content: streamingText,
+                    created_at: new Date().toISOString(),
+                  }}
+                />
+              )}
+              {sendingMessage && <TypingIndicator tool={activeTool} />}
               <div ref={messagesEndRef} className="h-4" />
             </div>
           )}
@@ -256,6 +272,7 @@ export function ChatContainer({ projectId, projectName }: ChatContainerProps) {
           <div className="mx-auto max-w-3xl">
             <ChatInput
               onSend={handleSend}
+              onCancel={cancelMessage}
               loading={sendingMessage}
               disabled={!hasApiKey}
               placeholder={
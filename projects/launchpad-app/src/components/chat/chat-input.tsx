@@ -1,11 +1,13 @@
 "use client";
 
 import { useState, useRef, useEffect } from "react";
-import { Send, Loader2, Radio } from "lucide-react";
+import { Send, Loader2, Radio, Square } from "lucide-react";
 import { cn } from "@/lib/utils";
 
 interface ChatInputProps {
   onSend: (message: string) => void;
+  // Shown as a stop button while loading
+  onCancel?: () => void;
   disabled?: boolean;
   loading?: boolean;
   placeholder?: string;
@@ -13,6 +15,7 @@ interface ChatInputProps {
 
 export function ChatInput({
   onSend,
+  onCancel,
   disabled = false,
   loading = false,
   placeholder = "Enter command...",
@@ -79,22 +82,33 @@ export function ChatInput({
             <span className="text-[var(--normandy-cyan)] uppercase tracking-wider">Transmitting</span>
           </div>
         )}
-        <button
-          onClick={handleSubmit}
-          disabled={!message.trim() || disabled || loading}
-          className={cn(
-            "normandy-btn flex h-9 w-9 shrink-0 items-center justify-center rounded transition-all",
-            message.trim() && !disabled && !loading
-              ? "normandy-btn-primary"
-              : "opacity-50 cursor-not-allowed"
-          )}
-        >
-          {loading ? (
-            <Loader2 className="h-4 w-4 animate-spin" />
-          ) : (
-            <Send className="h-4 w-4" />
-          )}
-        </button>
+        {loading && onCancel ? (
+          <button
+            onClick={onCancel}
+            className="normandy-btn flex h-9 w-9 shrink-0 items-center justify-center rounded transition-all"
+            title="Stop"
+            aria-label="Stop generating response"
+          >
+            <Square className="h-4 w-4" />
+          </button>
+        ) : (
+          <button
+            onClick={handleSubmit}
+            disabled={!message.trim() || disabled || loading}
+            className={cn(
+              "normandy-btn flex h-9 w-9 shrink-0 items-center justify-center rounded transition-all",
+              message.trim() && !disabled && !loading
+                ? "normandy-btn-primary"
+                : "opacity-50 cursor-not-allowed"
+            )}
+          >
+            {loading ? (
+              <Loader2 className="h-4 w-4 animate-spin" />
+            ) : (
+              <Send className="h-4 w-4" />
+            )}
+          </button>
+        )}
       </div>
     </div>
   );
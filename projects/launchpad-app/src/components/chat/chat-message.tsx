@@ -199,7 +199,7 @@ export const ChatMessage = memo(function ChatMessage({ message }: ChatMessagePro
 });
 
 // Typing indicator component
-export function TypingIndicator() {
+export function TypingIndicator({ tool }: { tool?: string | null }) {
   return (
     <div className="flex gap-4 px-4 py-5 bg-[var(--normandy-cyan-subtle)] border-l-2 border-[var(--normandy-cyan)]">
       <div className="relative flex h-8 w-8 shrink-0 items-center justify-center rounded bg-[var(--normandy-cyan-subtle)] border border-[var(--normandy-cyan)] border-opacity-40">
@@ -214,7 +214,9 @@ export function TypingIndicator() {
           <span className="h-1.5 w-1.5 animate-bounce rounded-full bg-[var(--normandy-cyan)] [animation-delay:-0.15s]" />
           <span className="h-1.5 w-1.5 animate-bounce rounded-full bg-[var(--normandy-cyan)]" />
         </div>
-        <span className="text-[10px] text-[var(--normandy-text-muted)] ml-2">PROCESSING</span>
+        <span className="text-[10px] text-[var(--normandy-text-muted)] ml-2">
+          {tool ? `RUNNING ${tool.toUpperCase()}` : "PROCESSING"}
+        </span>
       </div>
     </div>
   );
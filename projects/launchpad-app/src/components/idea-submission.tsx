@@ -3,7 +3,7 @@
 import { useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useAppStore } from "@/lib/store";
-import type { IdeaAuditResult, CreateIdeaInput } from "@/lib/types";
+import type { IdeaAuditResult, CreateIdeaInput, ChatReply } from "@/lib/types";
 import {
   GlassDialog,
   GlassDialogTrigger,
@@ -113,7 +113,7 @@ Scoring guide:
 - 5-7: Decent idea but needs refinement or has notable risks
 - 1-4: Weak idea with fundamental issues or saturated market`;
 
-      const response = await invoke<string>("send_chat_message", {
+      const reply = await invoke<ChatReply>("send_chat_message", {
         apiKey: settings.anthropic_api_key,
         messages: [{ role: "user", content: auditPrompt }],
         systemPrompt: "You are a JSON-only API. Return only valid JSON objects, no markdown formatting.",
@@ -121,7 +121,7 @@ Scoring guide:
       });
 
       // Parse the response - handle potential markdown code blocks
-      let jsonStr = response.trim();
+      let jsonStr = reply.text.trim();
       if (jsonStr.startsWith("```")) {
         jsonStr = jsonStr.replace(/```json?\s*/, "").replace(/```\s*$/, "");
       }
@@ -34,6 +34,7 @@ export interface RoadmapItem {
   status: RoadmapStatus;
   ai_notes: string | null;
   completed_at: string | null;
+  blocked_reason: string | null;
 }
 
 // SOP names for reference
@@ -61,6 +62,8 @@ export interface Conversation {
   id: string;
   project_id: string | null;
   title: string | null;
+  provider: string | null;
+  model: string | null;
   created_at: string;
 }
 
@@ -69,9 +72,26 @@ export interface Message {
   conversation_id: string;
   role: "user" | "assistant";
   content: string;
+  content_blocks?: unknown[] | null;
   created_at: string;
 }
 
+// Emitted on the "chat-stream" event while send_chat_message runs
+export type ChatStreamEvent =
+  | { type: "text_delta"; request_id: string; text: string }
+  | { type: "tool_call_start"; request_id: string; tool_use_id: string; name: string }
+  | { type: "tool_result"; request_id: string; tool_use_id: string; name: string; content: string; is_error: boolean }
+  | { type: "complete"; request_id: string; text: string }
+  | { type: "cancelled"; request_id: string; partial_text: string }
+  | { type: "error"; request_id: string; message: string };
+
+// Returned by send_chat_message; text is the partial reply when cancelled
+export interface ChatReply {
+  request_id: string;
+  text: string;
+  cancelled: boolean;
+}
+
 // ============================================
 // SOP Types (with Versioning)
 // ============================================
@@ -89,6 +109,7 @@ export interface SOP {
   tags: string | null; // JSON array string
   created_at: string;
   archived_at: string | null;
+  gates: string | null; // JSON array of SopGate, null for the built-in gates
 }
 
 export interface CreateSOPInput {
@@ -98,6 +119,30 @@ export interface CreateSOPInput {
   phase: SOPPhase;
   content: string;
   tags?: string[];
+  gates?: SopGate[];
+}
+
+export type SopGate =
+  | { type: "file_exists"; any_of: string[] }
+  | { type: "dependency"; any_of: string[] }
+  | { type: "script"; name: string }
+  | { type: "service"; any_of: string[] }
+  | { type: "file_count"; min: number }
+  | { type: "test_files"; min: number }
+  | { type: "tests_passing" }
+  | { type: "env_documented" };
+
+export interface GateResult {
+  gate: SopGate;
+  passed: boolean;
+  detail: string;
+}
+
+export interface PhaseEvidence {
+  sop_number: number;
+  name: string;
+  passed: boolean | null;
+  gates: GateResult[];
 }
 
 export const SOP_PHASES = [
@@ -189,6 +234,12 @@ export interface AppSettings {
   anthropic_api_key: string | null;
   theme: "light" | "dark" | "system";
   auto_analyze: boolean;
+  llm_provider: "anthropic" | "openai";
+  llm_model: string | null;
+  openai_base_url: string | null;
+  command_allowlist: string[];
+  ignore_overrides: Record<string, string[]>;
+  advisory_db_paths: string[];
 }
 
 // ============================================
@@ -218,30 +269,333 @@ export interface PackageJsonInfo {
   scripts: string[];
 }
 
+export interface CargoDependency {
+  name: string;
+  package: string | null;
+  version_req: string | null;
+  path: string | null;
+  git: string | null;
+  optional: boolean;
+  features: string[];
+  target: string | null;
+  workspace: boolean;
+}
+
+export interface CargoWorkspaceMember {
+  path: string;
+  manifest: CargoTomlInfo;
+}
+
 export interface CargoTomlInfo {
   name: string | null;
+  version: string | null;
+  edition: string | null;
+  description: string | null;
+  license: string | null;
+  rust_version: string | null;
+  dependencies: CargoDependency[];
+  dev_dependencies: CargoDependency[];
+  build_dependencies: CargoDependency[];
+  features: Record<string, string[]>;
+  workspace_members: CargoWorkspaceMember[];
+}
+
+export interface ResolvedDependency {
+  ecosystem: string;
+  name: string;
+  version: string;
+  direct: boolean;
+}
+
+export interface DependencyInventory {
+  lockfiles: string[];
+  dependencies: ResolvedDependency[];
+}
+
+export interface DependencySnapshot {
+  id: number;
+  project_id: string;
+  analyzed_at: string;
+  dependency_count: number;
+}
+
+export interface DependencyUsage {
+  project_id: string;
+  project_name: string;
+  snapshot_id: number;
+  analyzed_at: string;
+  dependency: ResolvedDependency;
+}
+
+export interface VersionChange {
+  ecosystem: string;
+  name: string;
+  from: string[];
+  to: string[];
+}
+
+export interface InventoryDiff {
+  added: ResolvedDependency[];
+  removed: ResolvedDependency[];
+  changed: VersionChange[];
+}
+
+export interface AnalysisSnapshot {
+  id: number;
+  project_id: string;
+  analyzed_at: string;
+  phase: number;
+  phase_name: string;
+  file_count: number;
+  has_tests: boolean;
+  services: string[];
+  recommendation_count: number;
+  code_lines: number;
+  comment_lines: number;
+  test_to_source_ratio: number | null;
+  test_lines: number | null;
+  languages: Record<string, number>;
+}
+
+export interface AnalysisDiff {
+  from: AnalysisSnapshot;
+  to: AnalysisSnapshot;
+  phase_change: number;
+  file_count_change: number;
+  code_lines_change: number;
+  services_added: string[];
+  services_removed: string[];
+  test_lines_change: number | null;
+  dependencies: InventoryDiff | null;
+}
+
+export type Severity = "unknown" | "low" | "medium" | "high" | "critical";
+
+export interface VulnerabilityFinding {
+  advisory_id: string;
+  aliases: string[];
+  ecosystem: string;
+  package: string;
+  version: string;
+  direct: boolean;
+  severity: Severity;
+  summary: string;
+  fixed: string[];
+  url: string | null;
+}
+
+export interface VulnerabilityAudit {
+  sources: string[];
+  advisories_loaded: number;
+  findings: VulnerabilityFinding[];
+}
+
+export interface SecretFinding {
+  rule_id: string;
+  description: string;
+  severity: Severity;
+  file: string;
+  line: number;
+  masked: string;
+  gitignored: boolean;
+}
+
+export interface SecretScan {
+  files_scanned: number;
+  findings: SecretFinding[];
+  truncated: boolean;
+}
+
+export interface EnvVariableUsage {
+  name: string;
+  locations: string[];
+}
+
+export interface EnvContract {
+  example_files: string[];
+  template_files: string[];
+  used: EnvVariableUsage[];
+  undocumented: string[];
+  unused: string[];
+  missing_values: string[];
+}
+
+export interface LanguageStats {
+  language: string;
+  files: number;
+  code: number;
+  comments: number;
+  blanks: number;
+}
+
+export interface FileLines {
+  path: string;
+  language: string;
+  code: number;
+}
+
+export interface CodeStats {
+  languages: LanguageStats[];
+  code: number;
+  comments: number;
+  blanks: number;
+  largest_files: FileLines[];
+  test_code: number;
+  source_code: number;
+  test_to_source_ratio: number | null;
+  skipped_files: number;
+}
+
+export interface PythonProjectInfo {
+  name: string | null;
+  requires_python: string | null;
   dependencies: string[];
+  sources: string[];
+}
+
+export interface GoRequire {
+  path: string;
+  version: string;
+  indirect: boolean;
+}
+
+export interface GoModInfo {
+  module: string | null;
+  go_version: string | null;
+  requires: GoRequire[];
+}
+
+export interface DenoConfigInfo {
+  file: string;
+  name: string | null;
+  imports: string[];
+  tasks: string[];
+}
+
+export interface ComposeService {
+  name: string;
+  image: string | null;
+  ports: string[];
+}
+
+export interface DockerInfo {
+  has_dockerfile: boolean;
+  base_images: string[];
+  exposed_ports: string[];
+  compose_file: string | null;
+  compose_services: ComposeService[];
+}
+
+export interface EcosystemInfo {
+  python: PythonProjectInfo | null;
+  go: GoModInfo | null;
+  deno: DenoConfigInfo | null;
+  bun: boolean;
+  docker: DockerInfo | null;
+}
+
+export type GitActivity = "active" | "stale" | "abandoned" | "no_commits";
+
+export type OriginStatus = "matches" | "mismatch" | "no_origin" | "unknown";
+
+export interface CommitSummary {
+  hash: string;
+  date: string;
+  author: string;
+  subject: string;
+}
+
+export interface WeeklyCommits {
+  week_start: string;
+  commits: number;
+}
+
+export interface GitInsight {
+  branch: string | null;
+  upstream: string | null;
+  ahead: number | null;
+  behind: number | null;
+  dirty_files: number;
+  untracked_files: number;
+  last_commit: CommitSummary | null;
+  days_since_last_commit: number | null;
+  activity: GitActivity;
+  commits_per_week: WeeklyCommits[];
+  tags: string[];
+  tag_count: number;
+  origin_url: string | null;
+  origin_status: OriginStatus;
+}
+
+export interface PackageAnalysis {
+  path: string;
+  name: string | null;
+  ecosystems: string[];
+  source: string;
+  tech_stack: string[];
+  frameworks: string[];
+  detected_services: string[];
+  has_tests: boolean;
+  package_json: PackageJsonInfo | null;
+  cargo_toml: CargoTomlInfo | null;
+  manifests: EcosystemInfo;
 }
 
 export interface SopProgress {
   estimated_phase: number;
   phase_name: string;
   evidence: string[];
+  phases: PhaseEvidence[];
 }
 
 export interface ProjectAnalysis {
   project_path: string;
   tech_stack: string[];
   frameworks: string[];
+  project_type: ProjectType;
   has_git: boolean;
+  git: GitInsight | null;
   has_tests: boolean;
   has_ci: boolean;
-  has_env_example: boolean;
+  env_contract: EnvContract;
   file_count: number;
+  code_stats: CodeStats;
   directory_structure: string[];
   detected_services: string[];
   package_json: PackageJsonInfo | null;
   cargo_toml: CargoTomlInfo | null;
-  recommendations: string[];
+  manifests: EcosystemInfo;
+  packages: PackageAnalysis[];
+  dependency_inventory: DependencyInventory;
+  vulnerability_audit: VulnerabilityAudit | null;
+  secret_scan: SecretScan;
+  recommendations: Recommendation[];
   sop_progress: SopProgress;
 }
+
+export type ProjectType = "web" | "api" | "desktop" | "cli" | "library" | "unknown";
+
+export type RuleCondition =
+  | { op: "present"; field: string }
+  | { op: "contains"; field: string; value: string }
+  | { op: "at_least"; field: string; value: number }
+  | { op: "all"; conditions: RuleCondition[] }
+  | { op: "any"; conditions: RuleCondition[] }
+  | { op: "not"; condition: RuleCondition };
+
+export interface RecommendationRule {
+  id: string;
+  condition: RuleCondition;
+  severity: Severity;
+  message: string; // {field} placeholders are filled from the analysis
+  sop_number: number | null;
+  project_types: ProjectType[]; // empty for every type
+  enabled: boolean;
+}
+
+export interface Recommendation {
+  rule_id: string;
+  severity: Severity;
+  message: string;
+  sop_number: number | null;
+}